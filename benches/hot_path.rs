@@ -0,0 +1,63 @@
+//! `cargo bench --bench hot_path [-- REPORTS]`: times parsing and counting on
+//! a generated puzzle of `REPORTS` reports, 1 000 000 by default, and prints
+//! the best of a few runs of each.
+//!
+//! Timed with `std::time::Instant` rather than a benchmarking crate, so that
+//! the suite builds offline like the rest of the package.
+
+use std::{
+    env,
+    hint::black_box,
+    time::{Duration, Instant}
+};
+
+use day_02::{
+    count_safe_from_reader,
+    generate::{generate_puzzle, write_to, GeneratorConfig},
+    ParseOptions, Puzzle, Report, SafeReportsExt, SafetyRules
+};
+
+const RUNS: usize = 5;
+
+fn best_of(name: &str, reports: usize, mut run: impl FnMut() -> usize) {
+    let mut best = Duration::MAX;
+    let mut result = 0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        result = black_box(run());
+        best = best.min(start.elapsed());
+    }
+    let per_report = best.as_nanos() / reports.max(1) as u128;
+    println!("{name:<32} {best:>12.2?} {per_report:>6} ns/report  ({result})");
+}
+
+fn main() {
+    // `cargo bench` passes `--bench`, which is not a report count.
+    let reports = env::args()
+    .skip(1)
+    .find_map(|arg| arg.parse().ok())
+    .unwrap_or(1_000_000);
+    let puzzle = generate_puzzle(&GeneratorConfig {
+        reports,
+        levels: 5..40,
+        seed: 2024,
+        ..GeneratorConfig::default()
+    });
+    let mut input = Vec::new();
+    write_to(&puzzle, &mut input).unwrap();
+    let rules = SafetyRules::default();
+
+    println!("{reports} reports, {} bytes", input.len());
+    best_of("parse", reports, || Puzzle::from_bytes(&input, ParseOptions::lenient()).unwrap().0.len());
+    for tolerance in [0, 1] {
+        best_of(&format!("count in memory, tolerance {tolerance}"), reports, || {
+            puzzle
+            .iter()
+            .map(Report::levels)
+            .count_safe_with(&rules, tolerance)
+        });
+        best_of(&format!("count from reader, tolerance {tolerance}"), reports, || {
+            count_safe_from_reader(input.as_slice(), &rules, tolerance).unwrap()
+        });
+    }
+}
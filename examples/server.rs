@@ -0,0 +1,14 @@
+//! `cargo run --example server --features server [address]`, then
+//! `curl --data-binary @input.txt 'localhost:8080/safe-reports?tolerance=1'`.
+
+use std::{env, net::TcpListener};
+
+use day_02::server::serve;
+
+fn main() -> std::io::Result<()> {
+    let address = env::args().nth(1).unwrap_or_else(|| "127.0.0.1:8080".to_string());
+    let listener = TcpListener::bind(&address)?;
+    println!("Listening on {address}");
+
+    serve(listener)
+}
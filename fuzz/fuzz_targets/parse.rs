@@ -0,0 +1,23 @@
+//! `cargo +nightly fuzz run parse`: parsing must never panic, whatever the
+//! bytes. Lenient parsing always succeeds; strict parsing may only fail with
+//! an error.
+
+#![no_main]
+
+use day_02::{ParseOptions, Puzzle, SafetyRules};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &[u8]| {
+    let normalizing = ParseOptions {
+        normalize_tokens: true,
+        ..ParseOptions::lenient()
+    };
+    for options in [ParseOptions::lenient(), normalizing] {
+        let (puzzle, _) = Puzzle::from_bytes(input, options).expect("lenient parsing never fails");
+        puzzle.count_safe(&SafetyRules::default(), 1);
+    }
+    let _ = Puzzle::from_bytes(input, ParseOptions::strict());
+    if let Ok(input) = std::str::from_utf8(input) {
+        let _ = Puzzle::parse(input, ParseOptions::strict());
+    }
+});
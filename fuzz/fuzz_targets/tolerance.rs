@@ -0,0 +1,33 @@
+//! `cargo +nightly fuzz run tolerance`: the dampener must agree with
+//! removing levels by hand. Reports and tolerances are kept small, the search
+//! being exponential in the tolerance.
+
+#![no_main]
+
+use day_02::{is_safe_slice, is_safe_slice_with_tolerance, SafetyRules};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &[u8]| {
+    let Some((&settings, levels)) = input.split_first() else {
+        return;
+    };
+    let levels: Vec<u32> = levels.iter().take(16).map(|&level| u32::from(level)).collect();
+    let rules = SafetyRules {
+        min_levels: usize::from(settings >> 4),
+        ..SafetyRules::default()
+    };
+    let tolerance = u32::from(settings & 0x3);
+
+    let safe = is_safe_slice_with_tolerance(&levels, &rules, tolerance);
+
+    if is_safe_slice(&levels, &rules) {
+        assert!(safe);
+    }
+    if tolerance > 0 && !safe {
+        assert!((0..levels.len()).all(|index| {
+            let mut rest = levels.clone();
+            rest.remove(index);
+            !is_safe_slice_with_tolerance(&rest, &rules, tolerance - 1)
+        }));
+    }
+});
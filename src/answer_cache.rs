@@ -0,0 +1,178 @@
+//! Answers kept on disk between runs, keyed by what decides them: the
+//! puzzle's [`fingerprint`](Puzzle::fingerprint), the tolerance and the
+//! rules.
+//!
+//! The cache is a text file with one entry per line,
+//! `FINGERPRINT TOLERANCE MIN_LEVELS ANSWER CHECKSUM`, the checksum covering
+//! the rest of the line. Lines that do not check out are dropped, so a
+//! damaged cache only costs a recomputation.
+
+use std::{
+    fs,
+    io,
+    path::Path
+};
+
+use crate::{fingerprint::Fnv1a, Day02Error, ParseOptions, Puzzle, SafetyRules};
+
+const CACHE_FILE: &str = "day_02_answers";
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+struct Entry {
+    fingerprint: u64,
+    tolerance: u32,
+    min_levels: usize,
+    answer: usize
+}
+
+impl Entry {
+    fn key(&self) -> (u64, u32, usize) {
+        (self.fingerprint, self.tolerance, self.min_levels)
+    }
+
+    fn fields(&self) -> String {
+        format!("{:016x} {} {} {}", self.fingerprint, self.tolerance, self.min_levels, self.answer)
+    }
+
+    fn line(&self) -> String {
+        let fields = self.fields();
+        format!("{fields} {:016x}", checksum(&fields))
+    }
+
+    fn from_line(line: &str) -> Option<Entry> {
+        let (fields, line_checksum) = line.rsplit_once(' ')?;
+        if u64::from_str_radix(line_checksum, 16).ok()? != checksum(fields) {
+            return None;
+        }
+        let mut fields = fields.split(' ');
+        let entry = Entry {
+            fingerprint: u64::from_str_radix(fields.next()?, 16).ok()?,
+            tolerance: fields.next()?.parse().ok()?,
+            min_levels: fields.next()?.parse().ok()?,
+            answer: fields.next()?.parse().ok()?
+        };
+        fields.next().is_none().then_some(entry)
+    }
+}
+
+fn checksum(fields: &str) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.write(fields.as_bytes());
+    hasher.finish()
+}
+
+/// The reports of the puzzle at `input_path` safe with up to `tolerance`
+/// removals, like [`find_safe_reports`](crate::find_safe_reports), reusing
+/// the answer stored in `cache_dir` when the same reports were already
+/// counted. Failing to store the answer is not an error.
+pub fn find_safe_reports_cached(input_path: impl AsRef<Path>, tolerance: u32, cache_dir: impl AsRef<Path>) -> Result<usize, Day02Error> {
+    let rules = SafetyRules::default();
+
+    cached_answer(input_path.as_ref(), tolerance, cache_dir.as_ref(), |puzzle| puzzle.count_safe(&rules, tolerance))
+}
+
+fn cached_answer(input_path: &Path, tolerance: u32, cache_dir: &Path, evaluate: impl FnOnce(&Puzzle) -> usize) -> Result<usize, Day02Error> {
+    let (puzzle, _) = Puzzle::from_path(input_path, ParseOptions::lenient())?;
+    let cache_path = cache_dir.join(CACHE_FILE);
+    let mut entries = read_entries(&cache_path);
+    let key = (puzzle.fingerprint(), tolerance, SafetyRules::default().min_levels);
+    if let Some(entry) = entries.iter().find(|entry| entry.key() == key) {
+        return Ok(entry.answer);
+    }
+    let entry = Entry {
+        fingerprint: key.0,
+        tolerance: key.1,
+        min_levels: key.2,
+        answer: evaluate(&puzzle)
+    };
+    entries.push(entry);
+    let _ = write_entries(cache_dir, &cache_path, &entries);
+
+    Ok(entry.answer)
+}
+
+/// The entries that check out; a missing or unreadable cache has none.
+fn read_entries(cache_path: &Path) -> Vec<Entry> {
+    fs::read_to_string(cache_path)
+    .map(|text| text.lines().filter_map(Entry::from_line).collect())
+    .unwrap_or_default()
+}
+
+/// Writes a new file and renames it over the cache, so a run that stops
+/// halfway leaves the previous cache whole.
+fn write_entries(cache_dir: &Path, cache_path: &Path, entries: &[Entry]) -> io::Result<()> {
+    fs::create_dir_all(cache_dir)?;
+    let text: String = entries.iter().map(|entry| entry.line() + "\n").collect();
+    let temporary_path = cache_path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&temporary_path, text)?;
+    fs::rename(&temporary_path, cache_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, env, path::PathBuf};
+
+    use super::*;
+
+    /// A fresh directory holding a copy of the sample puzzle.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("day_02_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::copy("tests/resources/puzzle.txt", dir.join("puzzle.txt")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn should_second_call_hit_cache_until_input_changes() {
+        let dir = scratch_dir("cache_hit");
+        let input = dir.join("puzzle.txt");
+        let evaluations = Cell::new(0);
+        let answer = |puzzle: &Puzzle| {
+            evaluations.set(evaluations.get() + 1);
+            puzzle.count_safe(&SafetyRules::default(), 1)
+        };
+
+        assert_eq!(cached_answer(&input, 1, &dir, answer).unwrap(), 4);
+        assert_eq!(cached_answer(&input, 1, &dir, answer).unwrap(), 4);
+        assert_eq!(evaluations.get(), 1);
+
+        fs::write(&input, fs::read_to_string(&input).unwrap().replace("1 3 6 7 9", "1 3 6 7 19")).unwrap();
+        assert_eq!(cached_answer(&input, 1, &dir, answer).unwrap(), 4);
+        assert_eq!(evaluations.get(), 2);
+        assert_eq!(cached_answer(&input, 0, &dir, answer).unwrap(), 4);
+        assert_eq!(evaluations.get(), 3);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn should_damaged_cache_fall_back_to_recomputing() {
+        let dir = scratch_dir("cache_damaged");
+        let input = dir.join("puzzle.txt");
+        assert_eq!(find_safe_reports_cached(&input, 1, &dir).unwrap(), 4);
+        let cache = fs::read_to_string(dir.join(CACHE_FILE)).unwrap();
+
+        // A changed answer no longer matches its checksum.
+        fs::write(dir.join(CACHE_FILE), cache.replacen(" 4 ", " 5 ", 1)).unwrap();
+        assert_eq!(find_safe_reports_cached(&input, 1, &dir).unwrap(), 4);
+        fs::write(dir.join(CACHE_FILE), "\u{0}garbage\n").unwrap();
+        assert_eq!(find_safe_reports_cached(&input, 1, &dir).unwrap(), 4);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn should_entry_round_trip_through_line() {
+        let entry = Entry {
+            fingerprint: u64::MAX,
+            tolerance: 1,
+            min_levels: 2,
+            answer: 311
+        };
+
+        assert_eq!(Entry::from_line(&entry.line()), Some(entry));
+        assert_eq!(Entry::from_line(&entry.line().replace("311", "312")), None);
+    }
+}
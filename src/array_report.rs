@@ -0,0 +1,173 @@
+//! Reports of at most `N` levels kept inline, for targets without an
+//! allocator such as microcontrollers reading sensor levels.
+
+use core::{error::Error, fmt};
+
+use crate::{direction_of, first_violation, is_safe_slice, removals::min_removals_to_safe_in, Direction, Level, ReportStatus, SafetyRules, Violation};
+
+/// A level pushed onto an [`ArrayReport`] already holding `capacity` levels.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub struct CapacityError {
+    pub capacity: usize
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "report is full: {} levels", self.capacity)
+    }
+}
+
+impl Error for CapacityError {}
+
+/// Like [`Report`](crate::Report), with its levels in a fixed array instead
+/// of a `Vec`: checking it, dampener included, never allocates.
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub struct ArrayReport<const N: usize, L: Level = u32> {
+    values: [L; N],
+    len: usize
+}
+
+impl<const N: usize, L: Level> ArrayReport<N, L> {
+    pub fn new() -> ArrayReport<N, L> {
+        ArrayReport {
+            values: [L::default(); N],
+            len: 0
+        }
+    }
+
+    /// The report of `levels`, unless there are more than `N`.
+    pub fn from_levels(levels: &[L]) -> Result<ArrayReport<N, L>, CapacityError> {
+        let mut report = ArrayReport::new();
+        levels.iter().try_for_each(|&level| report.push(level))?;
+
+        Ok(report)
+    }
+
+    /// Adds a level at the end, unless the report already holds `N`.
+    pub fn push(&mut self, level: L) -> Result<(), CapacityError> {
+        let slot = self.values.get_mut(self.len).ok_or(CapacityError {
+            capacity: N
+        })?;
+        *slot = level;
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Empties the report, to read the next one into it.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn levels(&self) -> &[L] {
+        &self.values[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Safe under the default rules once up to `tolerance` levels are
+    /// removed: 0 for part 1, 1 for part 2.
+    pub fn is_safe(&self, tolerance: u32) -> bool {
+        self.is_safe_with_tolerance(&SafetyRules::default(), tolerance)
+    }
+
+    /// Like [`Report::is_safe_with_tolerance`](crate::Report::is_safe_with_tolerance),
+    /// working on the stack.
+    pub fn is_safe_with_tolerance(&self, rules: &SafetyRules, tolerance: u32) -> bool {
+        min_removals_to_safe_in(self.levels(), rules, tolerance, &mut [0; N]).is_some()
+    }
+
+    /// Safe as-is under `rules`.
+    pub fn is_safe_with(&self, rules: &SafetyRules) -> bool {
+        is_safe_slice(self.levels(), rules)
+    }
+
+    /// The first rule broken before any removal.
+    pub fn violation(&self, rules: &SafetyRules) -> Option<Violation> {
+        first_violation(self.levels(), rules)
+    }
+
+    pub fn direction(&self) -> Direction {
+        direction_of(self.levels())
+    }
+
+    pub fn classify(&self, rules: &SafetyRules, tolerance: u32) -> ReportStatus {
+        if self.is_safe_with(rules) {
+            return ReportStatus::Safe;
+        }
+        if self.is_safe_with_tolerance(rules, tolerance) {
+            return ReportStatus::SafeWithDampener;
+        }
+        ReportStatus::Unsafe
+    }
+}
+
+impl<const N: usize, L: Level> Default for ArrayReport<N, L> {
+    fn default() -> ArrayReport<N, L> {
+        ArrayReport::new()
+    }
+}
+
+/// Reports are equal when their levels are, whatever the unused slots hold.
+impl<const N: usize, L: Level> PartialEq for ArrayReport<N, L> {
+    fn eq(&self, other: &ArrayReport<N, L>) -> bool {
+        self.levels() == other.levels()
+    }
+}
+
+impl<const N: usize, L: Level> Eq for ArrayReport<N, L> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_array_report_classify_like_report() {
+        let statuses = [
+            ([7, 6, 4, 2, 1], ReportStatus::Safe),
+            ([1, 2, 7, 8, 9], ReportStatus::Unsafe),
+            ([1, 3, 2, 4, 5], ReportStatus::SafeWithDampener),
+            ([8, 6, 4, 4, 1], ReportStatus::SafeWithDampener)
+        ];
+        for (levels, status) in statuses {
+            let report = ArrayReport::<8>::from_levels(&levels).unwrap();
+
+            assert_eq!(report.classify(&SafetyRules::default(), 1), status, "{levels:?}");
+        }
+        assert!(ArrayReport::<8, i32>::from_levels(&[-3, -1, 2]).unwrap().is_safe(0));
+        assert!(!ArrayReport::<4>::new().is_safe(1));
+    }
+
+    #[test]
+    fn should_push_fail_once_full() {
+        let mut report = ArrayReport::<2>::new();
+
+        assert_eq!(report.push(1), Ok(()));
+        assert_eq!(report.push(2), Ok(()));
+        assert_eq!(report.push(3), Err(CapacityError {
+            capacity: 2
+        }));
+        assert_eq!(report.levels(), &[1, 2]);
+        assert_eq!(ArrayReport::<2>::from_levels(&[1, 2, 3]), Err(CapacityError {
+            capacity: 2
+        }));
+    }
+
+    #[test]
+    fn should_clear_ignore_stale_levels() {
+        let mut report = ArrayReport::<4>::from_levels(&[9, 9, 9]).unwrap();
+        report.clear();
+        report.push(1).unwrap();
+
+        assert_eq!(report, ArrayReport::from_levels(&[1]).unwrap());
+        assert_eq!(report.len(), 1);
+    }
+}
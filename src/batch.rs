@@ -0,0 +1,121 @@
+//! Solving many puzzle files at once, each failing on its own.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf}
+};
+
+use crate::{try_find_safe_reports, Day02Error};
+
+/// The safe reports of one file, or why it could not be counted.
+#[derive(Debug)]
+pub struct FileResult {
+    pub path: PathBuf,
+    pub safe_reports: Result<usize, Day02Error>
+}
+
+#[derive(Debug)]
+pub struct BatchResult {
+    /// One result per path, in the order given.
+    pub files: Vec<FileResult>,
+    /// The safe reports of every file that could be counted.
+    pub total: usize
+}
+
+impl BatchResult {
+    /// How many files could not be counted.
+    pub fn failed(&self) -> usize {
+        self.files.iter().filter(|file| file.safe_reports.is_err()).count()
+    }
+}
+
+/// Counts each file like [`try_find_safe_reports`]. A file that cannot be
+/// read or parsed is reported in its result and left out of the total, and
+/// the others are counted all the same.
+pub fn find_safe_reports_batch(paths: &[PathBuf], tolerance: u32) -> BatchResult {
+    batch(paths, |path| try_find_safe_reports(path, tolerance))
+}
+
+pub(crate) fn batch(paths: &[PathBuf], mut count: impl FnMut(&Path) -> Result<usize, Day02Error>) -> BatchResult {
+    let files: Vec<FileResult> = paths
+    .iter()
+    .map(|path| FileResult {
+        path: path.clone(),
+        safe_reports: count(path)
+    })
+    .collect();
+    let total = files.iter().filter_map(|file| file.safe_reports.as_ref().ok()).sum();
+
+    BatchResult {
+        files,
+        total
+    }
+}
+
+/// The files in `dir`, and in its subdirectories when `recursive`, sorted
+/// by path. Hidden entries, whose names start with `.`, are skipped.
+pub fn puzzle_files(dir: &Path, recursive: bool) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_files(dir, recursive, &mut files)?;
+    files.sort();
+
+    Ok(files)
+}
+
+fn collect_files(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            if recursive {
+                collect_files(&path, recursive, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    #[test]
+    fn should_batch_isolate_unreadable_files() {
+        let paths = [
+            PathBuf::from("tests/resources/puzzle.txt"),
+            PathBuf::from("tests/resources/missing.txt"),
+            PathBuf::from("tests/resources/puzzle_trailing_blank_lines.txt")
+        ];
+
+        let result = find_safe_reports_batch(&paths, 1);
+
+        assert_eq!(result.total, 8);
+        assert_eq!(result.failed(), 1);
+        assert!(matches!(result.files[0].safe_reports, Ok(4)));
+        assert!(matches!(result.files[1].safe_reports, Err(Day02Error::Io(_))));
+        assert_eq!(result.files[2].path, paths[2]);
+    }
+
+    #[test]
+    fn should_puzzle_files_recurse_only_when_asked() {
+        let dir = env::temp_dir().join(format!("day_02_batch_{}", process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        for path in ["b.txt", "a.txt", ".hidden", "nested/c.txt"] {
+            fs::write(dir.join(path), "1 2 3\n").unwrap();
+        }
+
+        let flat = puzzle_files(&dir, false).unwrap();
+        let recursive = puzzle_files(&dir, true).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(flat, vec![dir.join("a.txt"), dir.join("b.txt")]);
+        assert_eq!(recursive, vec![dir.join("a.txt"), dir.join("b.txt"), dir.join("nested/c.txt")]);
+    }
+}
@@ -0,0 +1,112 @@
+//! Writes a generated puzzle for benchmarks, then prints its answers:
+//!
+//! `gen_input --reports N [--min-len 5] [--max-len 8] [--safe-ratio 0.25]
+//! [--dampener-ratio 0.25] [--seed 0] [--tolerance 1] --out PATH [--force]`
+//!
+//! Reports are streamed to the file as they are generated. An existing file
+//! is only overwritten with `--force`. Part 2 counts the reports safe once
+//! up to `--tolerance` levels are removed.
+
+use std::{
+    env,
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    process::ExitCode,
+    str::FromStr
+};
+
+use day_02::{
+    generate::{generate_reports, GeneratorConfig},
+    min_removals_to_safe, SafetyRules
+};
+
+struct Args {
+    config: GeneratorConfig,
+    tolerance: u32,
+    out: PathBuf,
+    force: bool
+}
+
+fn value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or(format!("`{flag}` needs a value"))?;
+    value.parse().map_err(|_| format!("invalid value `{value}` for `{flag}`"))
+}
+
+fn parse_args(arguments: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut config = GeneratorConfig::default();
+    let (mut min_len, mut max_len) = (config.levels.start, config.levels.end - 1);
+    let mut out = None;
+    let mut tolerance = 1;
+    let mut force = false;
+    let mut arguments = arguments.into_iter();
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--reports" => config.reports = value(&argument, arguments.next())?,
+            "--min-len" => min_len = value(&argument, arguments.next())?,
+            "--max-len" => max_len = value(&argument, arguments.next())?,
+            "--safe-ratio" => config.safe = value(&argument, arguments.next())?,
+            "--dampener-ratio" => config.dampener_safe = value(&argument, arguments.next())?,
+            "--seed" => config.seed = value(&argument, arguments.next())?,
+            "--tolerance" => tolerance = value(&argument, arguments.next())?,
+            "--out" => out = Some(value(&argument, arguments.next())?),
+            "--force" => force = true,
+            _ => return Err(format!("unexpected argument `{argument}`"))
+        }
+    }
+    if min_len < 4 || max_len < min_len {
+        return Err("lengths must satisfy 4 <= --min-len <= --max-len".to_string());
+    }
+    if config.safe < 0.0 || config.dampener_safe < 0.0 || config.safe + config.dampener_safe > 1.0 {
+        return Err("--safe-ratio and --dampener-ratio must add up to at most 1".to_string());
+    }
+    config.levels = min_len..max_len + 1;
+
+    Ok(Args {
+        config,
+        tolerance,
+        out: out.ok_or("`--out` is required")?,
+        force
+    })
+}
+
+fn generate(args: &Args) -> Result<(), String> {
+    let file = OpenOptions::new()
+    .write(true)
+    .create(true)
+    .truncate(true)
+    .create_new(!args.force)
+    .open(&args.out)
+    .map_err(|error| format!("cannot create {}: {error} (use --force to overwrite)", args.out.display()))?;
+    let mut writer = BufWriter::new(file);
+    let rules = SafetyRules::default();
+    let (mut part1, mut part2) = (0usize, 0usize);
+    for levels in generate_reports(&args.config) {
+        match min_removals_to_safe(&levels, &rules, args.tolerance) {
+            Some(0) => {
+                part1 += 1;
+                part2 += 1;
+            }
+            Some(_) => part2 += 1,
+            None => {}
+        }
+        let line: Vec<String> = levels.iter().map(u32::to_string).collect();
+        writeln!(writer, "{}", line.join(" ")).map_err(|error| error.to_string())?;
+    }
+    writer.flush().map_err(|error| error.to_string())?;
+
+    println!("Wrote {} reports to {}", args.config.reports, args.out.display());
+    println!("Part 1 (safe): {part1}");
+    println!("Part 2 (safe once up to {} levels are removed): {part2}", args.tolerance);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match parse_args(env::args().skip(1)).and_then(|args| generate(&args)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("gen_input: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
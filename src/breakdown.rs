@@ -0,0 +1,50 @@
+//! Reports grouped by the way they go.
+
+use crate::Direction;
+
+/// The reports of one direction and how many of them are safe.
+#[derive(PartialEq, Eq, Clone, Default)]
+#[derive(Debug)]
+pub struct DirectionGroup {
+    /// 0-based indices of the reports, in puzzle order.
+    pub indices: Vec<usize>,
+    /// Reports safe as-is.
+    pub safe: usize,
+    /// Reports safe once the Problem Dampener may remove one level.
+    pub safe_with_dampener: usize
+}
+
+impl DirectionGroup {
+    pub fn count(&self) -> usize {
+        self.indices.len()
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Default)]
+#[derive(Debug)]
+pub struct DirectionBreakdown {
+    pub ascending: DirectionGroup,
+    pub descending: DirectionGroup,
+    pub mixed: DirectionGroup,
+    pub flat: DirectionGroup
+}
+
+impl DirectionBreakdown {
+    pub fn group(&self, direction: Direction) -> &DirectionGroup {
+        match direction {
+            Direction::Ascending => &self.ascending,
+            Direction::Descending => &self.descending,
+            Direction::Mixed => &self.mixed,
+            Direction::Flat => &self.flat
+        }
+    }
+
+    pub(crate) fn group_mut(&mut self, direction: Direction) -> &mut DirectionGroup {
+        match direction {
+            Direction::Ascending => &mut self.ascending,
+            Direction::Descending => &mut self.descending,
+            Direction::Mixed => &mut self.mixed,
+            Direction::Flat => &mut self.flat
+        }
+    }
+}
@@ -0,0 +1,1671 @@
+//! The `day_02` binary, kept here so its behaviour can be tested.
+//!
+//! `day_02 [PATH | --input PATH] [--verify P1,P2] [--export-fixes CSV]
+//! [--top N] [--format text|markdown|json|csv] [--strict] [--watch]
+//! [--sample N [--seed S]] [--sections] [--delimiter D] [--skip-header]
+//! [--config PATH] [--time]`
+//! solves the puzzle at `PATH` (`src/resources/puzzle.txt` by default).
+//!
+//! `--time` prints both parts instead, with how long parsing and each part
+//! took, through [`run_solver`](crate::run_solver). It reads the puzzle's
+//! own format, so it cannot be combined with `--delimiter`, `--skip-header`
+//! or `--strict`.
+//!
+//! `day_02 count PATH [--tolerance N | --part 1|2] [--format text|json]
+//! [--recursive] [--progress]` prints the reports of `PATH`, or of the standard input
+//! when `PATH` is `-`, that are safe once up to `N` levels may be removed: 0
+//! by default, which `--part 1` also gives and `--part 2` is 1. Unlike
+//! solving, any bad line fails it. When `PATH` is a directory each of its
+//! files is counted on its own, and those of its subdirectories with
+//! `--recursive`, along with the total; a file that fails is reported and
+//! the others are still counted. `--progress` tells on the standard error
+//! how many lines and reports have been read, how many were safe and how
+//! fast, every million reports, for inputs that take minutes.
+//!
+//! `day_02 generate [--reports N] [--len A..B] [--safe-ratio R]
+//! [--dampener-ratio R] [--seed S] [--out PATH]` writes a random puzzle,
+//! the same for the same settings, with the given shares of reports safe
+//! as-is and safe only with the Problem Dampener, the rest staying unsafe;
+//! see [`generate`](crate::generate). It goes to the standard output unless
+//! `--out` is given.
+//!
+//! `day_02 fetch --session COOKIE` downloads the input with the `session`
+//! cookie of a logged-in browser, unless an earlier fetch cached it, then
+//! solves it like `PATH`; see [`fetch`](crate::fetch). It needs the `fetch`
+//! feature.
+//!
+//! `day_02 explain PATH [--line N] [--tolerance N | --part 1|2]
+//! [--color auto|always|never]` prints every report of `PATH`, or only the
+//! one on line `N`, with the pair breaking the rules highlighted, which way
+//! the report goes and, when levels may be removed, the level the Problem
+//! Dampener would remove; see [`explain`](crate::explain). Colors are used
+//! on terminals unless `NO_COLOR` is set, or as `--color` says.
+//!
+//! `day_02 diff OLD NEW [--tolerance N | --part 1|2] [--format text|json]`
+//! lists the reports whose status changed from `OLD` to `NEW`, by their
+//! position, along with those only `NEW` has and those it lost. Statuses are
+//! those of part 2 unless a tolerance is given.
+//!
+//! `day_02 repl` classifies reports typed one per line instead, see
+//! [`repl`](crate::repl), and `day_02 config init` prints a commented config
+//! file for `--config PATH`, see [`config`](crate::config).
+//!
+//! `--format json` prints the answers as JSON, along with the settings they
+//! were computed with and the classification of every report, and
+//! `--format csv` prints that classification as CSV, see
+//! [`Puzzle::write_classification_csv`].
+//!
+//! `--sections` reads the input as several puzzles separated by blank lines
+//! and prints the safe reports of each, see
+//! [`parse_sections`](crate::parse_sections). Like `--verify`, it prints
+//! nothing else, so neither can be combined with `--export-fixes`, `--top`
+//! or `--format`.
+//!
+//! `--strict` refuses an input with any bad line, listing all of them.
+//!
+//! `--delimiter whitespace|comma|tab|C` splits levels on any whitespace, the
+//! default, or on a comma, a tab or the ASCII character `C`, and
+//! `--skip-header` ignores the first line, for CSV exports. Sections and
+//! `--verify` always read the puzzle's own format.
+//!
+//! `--sample N` estimates both parts from `N` reports picked at random while
+//! the input is streamed, the pick depending only on `--seed` (0 by
+//! default). It cannot be combined with the flags whose output is exact.
+//!
+//! `--format markdown` prints the answers as Markdown tables, see
+//! [`Puzzle::to_markdown`].
+//!
+//! `--top N` also lists the `N` unsafe reports closest to safe, with how many
+//! levels each has to lose, except with `--format csv`.
+//!
+//! With `--watch`, or as `day_02 watch PATH`, both parts are printed again
+//! whenever the file changes, until the process is interrupted. Each run
+//! shows how far the parts moved since the previous one and which reports
+//! changed status, bursts of writes being folded into a single run. It
+//! cannot be combined with `--verify`, `--top` or `--format`.
+
+use std::{
+    env,
+    error::Error,
+    fmt,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, IsTerminal, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::mpsc::channel,
+    time::{Duration, SystemTime}
+};
+
+#[cfg(feature = "fetch")]
+use crate::fetch::{cache_path, fetch_input, FetchError, Session};
+use crate::{
+    batch::batch,
+    explain::explain_input,
+    config::{CliConfig, ConfigError, DEFAULT_CONFIG},
+    generate::{generate_reports, GeneratorConfig},
+    json, parse_sections, puzzle_files,
+    repl::repl,
+    verify,
+    watch::{format_timestamp, poll_changes, watch_loop},
+    compare_puzzles, count_safe_from_reader_with_progress, run_solver, Day02, Day02Error, Delimiter, MarkdownOptions, ParseError, ParseOptions,
+    Progress, Puzzle, PuzzleDiff, ReportStatus, SafetyReport, SafetyRules, SampledCounter, Solver, VerifyError
+};
+
+pub const DEFAULT_INPUT: &str = "src/resources/puzzle.txt";
+
+#[derive(PartialEq, Clone)]
+#[derive(Debug)]
+pub struct Args {
+    pub command: Command,
+    pub input: PathBuf,
+    /// Expected part 1 and part 2 answers to check instead of printing.
+    pub verify: Option<(u32, u32)>,
+    /// Where to write the dampener fixes as CSV.
+    pub export_fixes: Option<PathBuf>,
+    /// How many of the most fixable unsafe reports to list.
+    pub top: Option<usize>,
+    pub format: Format,
+    /// Refuse inputs with bad lines instead of skipping them.
+    pub strict: bool,
+    pub watch: bool,
+    /// Print both parts with how long each took.
+    pub time: bool,
+    /// How many reports to estimate the answers from.
+    pub sample: Option<usize>,
+    pub seed: u64,
+    /// Solve each blank-line separated section on its own.
+    pub sections: bool,
+    /// How many levels `count` may remove from each report.
+    pub tolerance: u32,
+    pub delimiter: Delimiter,
+    pub skip_header: bool,
+    /// Also count the files in subdirectories when `count` is given one.
+    pub recursive: bool,
+    /// Tell how `count` is going on the standard error.
+    pub progress: bool,
+    /// What `generate` writes, its seed being `seed`.
+    pub generator: GeneratorConfig,
+    /// Where `generate` writes, the standard output if `None`.
+    pub out: Option<PathBuf>,
+    /// What `diff` compares `input` with.
+    pub new_input: Option<PathBuf>,
+    /// The only line `explain` explains, 1-based.
+    pub line: Option<usize>,
+    pub color: ColorChoice,
+    /// What `fetch` logs in with.
+    #[cfg(feature = "fetch")]
+    pub session: Option<Session>
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub enum Command {
+    /// Solve the puzzle at `input`.
+    Solve,
+    /// Count the safe reports at `input`, the standard input being `-`.
+    Count,
+    Generate,
+    /// Explain every report at `input`.
+    Explain,
+    /// Compare the reports at `input` with those at `new_input`.
+    Diff,
+    /// Fetch the input, then solve it.
+    #[cfg(feature = "fetch")]
+    Fetch,
+    Repl,
+    ConfigInit
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Default)]
+#[derive(Debug)]
+pub enum Format {
+    #[default]
+    Text,
+    Markdown,
+    Json,
+    Csv
+}
+
+impl Format {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Format::Text => "text",
+            Format::Markdown => "markdown",
+            Format::Json => "json",
+            Format::Csv => "csv"
+        }
+    }
+}
+
+/// Whether `explain` colors its output.
+#[derive(PartialEq, Eq, Clone, Copy, Default)]
+#[derive(Debug)]
+pub enum ColorChoice {
+    /// On terminals, unless `NO_COLOR` is set.
+    #[default]
+    Auto,
+    Always,
+    Never
+}
+
+impl ColorChoice {
+    fn enabled(&self) -> bool {
+        match self {
+            ColorChoice::Auto => io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false
+        }
+    }
+}
+
+impl Default for Args {
+    fn default() -> Args {
+        Args {
+            command: Command::Solve,
+            input: PathBuf::from(DEFAULT_INPUT),
+            verify: None,
+            export_fixes: None,
+            top: None,
+            format: Format::Text,
+            strict: false,
+            watch: false,
+            time: false,
+            sample: None,
+            seed: 0,
+            sections: false,
+            tolerance: 0,
+            delimiter: Delimiter::Whitespace,
+            skip_header: false,
+            recursive: false,
+            progress: false,
+            generator: GeneratorConfig::default(),
+            out: None,
+            new_input: None,
+            line: None,
+            color: ColorChoice::Auto,
+            #[cfg(feature = "fetch")]
+            session: None
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone)]
+#[derive(Debug)]
+pub enum ArgsError {
+    UnknownFlag(String),
+    MissingValue(&'static str),
+    InvalidValue { flag: &'static str, value: String },
+    UnexpectedArgument(String),
+    Conflict { flag: &'static str, with: &'static str },
+    Config(ConfigError)
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgsError::UnknownFlag(flag) => write!(f, "unknown flag `{flag}`"),
+            ArgsError::MissingValue(flag) => write!(f, "`{flag}` needs a value"),
+            ArgsError::InvalidValue { flag, value } => write!(f, "invalid value `{value}` for `{flag}`"),
+            ArgsError::UnexpectedArgument(argument) => write!(f, "unexpected argument `{argument}`"),
+            ArgsError::Conflict { flag, with } => write!(f, "`{flag}` cannot be used with `{with}`"),
+            ArgsError::Config(error) => error.fmt(f)
+        }
+    }
+}
+
+impl Error for ArgsError {}
+
+#[derive(Debug)]
+pub enum CliError {
+    Args(ArgsError),
+    Puzzle(Day02Error),
+    /// Every bad line of an input read with `--strict`.
+    Invalid(Vec<ParseError>),
+    Verify(VerifyError),
+    /// Files of a directory given to `count` that could not be counted.
+    Batch { failed: usize },
+    #[cfg(feature = "fetch")]
+    Fetch(FetchError),
+    Io(io::Error)
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Args(error) => error.fmt(f),
+            CliError::Puzzle(error) => error.fmt(f),
+            CliError::Invalid(errors) => {
+                write!(f, "{} bad line(s) in puzzle:", errors.len())?;
+                errors.iter().try_for_each(|error| write!(f, "\n{error}"))
+            }
+            CliError::Verify(error) => write!(f, "verification failed: {error}"),
+            CliError::Batch { failed } => write!(f, "{failed} file(s) could not be counted"),
+            #[cfg(feature = "fetch")]
+            CliError::Fetch(error) => error.fmt(f),
+            CliError::Io(error) => write!(f, "cannot write output: {error}")
+        }
+    }
+}
+
+impl Error for CliError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CliError::Args(error) => Some(error),
+            CliError::Puzzle(error) => Some(error),
+            CliError::Invalid(errors) => errors.first().map(|error| error as &(dyn Error + 'static)),
+            CliError::Verify(error) => Some(error),
+            CliError::Batch { .. } => None,
+            #[cfg(feature = "fetch")]
+            CliError::Fetch(error) => Some(error),
+            CliError::Io(error) => Some(error)
+        }
+    }
+}
+
+impl From<ArgsError> for CliError {
+    fn from(error: ArgsError) -> CliError {
+        CliError::Args(error)
+    }
+}
+
+impl From<Day02Error> for CliError {
+    fn from(error: Day02Error) -> CliError {
+        CliError::Puzzle(error)
+    }
+}
+
+impl From<VerifyError> for CliError {
+    fn from(error: VerifyError) -> CliError {
+        CliError::Verify(error)
+    }
+}
+
+#[cfg(feature = "fetch")]
+impl From<FetchError> for CliError {
+    fn from(error: FetchError) -> CliError {
+        CliError::Fetch(error)
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(error: io::Error) -> CliError {
+        CliError::Io(error)
+    }
+}
+
+impl Args {
+    /// Parses the arguments following the program name, starting from the
+    /// settings of the `--config` file if one is given.
+    pub fn parse<I: IntoIterator<Item = String>>(arguments: I) -> Result<Args, ArgsError> {
+        let arguments: Vec<String> = arguments.into_iter().collect();
+        let mut args = Args::default();
+        let mut config_tolerance = false;
+        if let Some(position) = arguments.iter().position(|argument| argument == "--config") {
+            let path = arguments.get(position + 1).ok_or(ArgsError::MissingValue("--config"))?;
+            let config = CliConfig::load(Path::new(path)).map_err(ArgsError::Config)?;
+            config_tolerance = config.tolerance.is_some();
+            config.apply(&mut args);
+        }
+        let mut input = None;
+        let mut tolerance_flag = None;
+        let mut watch_command = false;
+        let mut arguments = arguments.into_iter().peekable();
+        if arguments.next_if(|argument| argument == "count").is_some() {
+            args.command = Command::Count;
+        } else if arguments.next_if(|argument| argument == "generate").is_some() {
+            args.command = Command::Generate;
+        } else if arguments.next_if(|argument| argument == "diff").is_some() {
+            args.command = Command::Diff;
+        } else if arguments.next_if(|argument| argument == "explain").is_some() {
+            args.command = Command::Explain;
+        } else if arguments.next_if(|argument| argument == "watch").is_some() {
+            args.watch = true;
+            watch_command = true;
+        } else if arguments.next_if(|argument| argument == "repl").is_some() {
+            args.command = Command::Repl;
+        } else if arguments.next_if(|argument| argument == "config").is_some() {
+            match arguments.next() {
+                Some(action) if action == "init" => args.command = Command::ConfigInit,
+                Some(action) => return Err(ArgsError::UnexpectedArgument(action)),
+                None => return Err(ArgsError::MissingValue("config"))
+            }
+        }
+        #[cfg(feature = "fetch")]
+        if args.command == Command::Solve && arguments.next_if(|argument| argument == "fetch").is_some() {
+            args.command = Command::Fetch;
+        }
+        while let Some(argument) = arguments.next() {
+            match argument.as_str() {
+                "--verify" => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--verify"))?;
+                    args.verify = Some(parse_answers(&value).ok_or(ArgsError::InvalidValue {
+                        flag: "--verify",
+                        value
+                    })?);
+                }
+                "--export-fixes" => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--export-fixes"))?;
+                    args.export_fixes = Some(PathBuf::from(value));
+                }
+                "--top" => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--top"))?;
+                    args.top = Some(value.parse().map_err(|_| ArgsError::InvalidValue {
+                        flag: "--top",
+                        value
+                    })?);
+                }
+                "--format" => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--format"))?;
+                    args.format = match value.as_str() {
+                        "text" => Format::Text,
+                        "markdown" => Format::Markdown,
+                        "json" => Format::Json,
+                        "csv" => Format::Csv,
+                        _ => return Err(ArgsError::InvalidValue {
+                            flag: "--format",
+                            value
+                        })
+                    };
+                }
+                "--input" => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--input"))?;
+                    if input.replace(PathBuf::from(value)).is_some() {
+                        return Err(ArgsError::UnexpectedArgument(argument));
+                    }
+                }
+                "--config" => {
+                    // Already applied.
+                    arguments.next();
+                }
+                "--tolerance" => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--tolerance"))?;
+                    args.tolerance = value.parse().map_err(|_| ArgsError::InvalidValue {
+                        flag: "--tolerance",
+                        value
+                    })?;
+                    set_tolerance_flag(&mut tolerance_flag, "--tolerance")?;
+                }
+                "--part" => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--part"))?;
+                    args.tolerance = match value.as_str() {
+                        "1" => 0,
+                        "2" => 1,
+                        _ => return Err(ArgsError::InvalidValue {
+                            flag: "--part",
+                            value
+                        })
+                    };
+                    set_tolerance_flag(&mut tolerance_flag, "--part")?;
+                }
+                "--delimiter" => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--delimiter"))?;
+                    args.delimiter = parse_delimiter(&value).ok_or(ArgsError::InvalidValue {
+                        flag: "--delimiter",
+                        value
+                    })?;
+                }
+                "--skip-header" => args.skip_header = true,
+                "--recursive" if args.command == Command::Count => args.recursive = true,
+                "--progress" if args.command == Command::Count => args.progress = true,
+                "--line" if args.command == Command::Explain => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--line"))?;
+                    args.line = Some(value.parse().ok().filter(|&line| line > 0).ok_or(ArgsError::InvalidValue {
+                        flag: "--line",
+                        value
+                    })?);
+                }
+                "--color" if args.command == Command::Explain => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--color"))?;
+                    args.color = match value.as_str() {
+                        "auto" => ColorChoice::Auto,
+                        "always" => ColorChoice::Always,
+                        "never" => ColorChoice::Never,
+                        _ => return Err(ArgsError::InvalidValue {
+                            flag: "--color",
+                            value
+                        })
+                    };
+                }
+                "--reports" if args.command == Command::Generate => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--reports"))?;
+                    args.generator.reports = value.parse().map_err(|_| ArgsError::InvalidValue {
+                        flag: "--reports",
+                        value
+                    })?;
+                }
+                "--len" if args.command == Command::Generate => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--len"))?;
+                    args.generator.levels = parse_lengths(&value).ok_or(ArgsError::InvalidValue {
+                        flag: "--len",
+                        value
+                    })?;
+                }
+                "--safe-ratio" if args.command == Command::Generate => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--safe-ratio"))?;
+                    args.generator.safe = parse_ratio(&value).ok_or(ArgsError::InvalidValue {
+                        flag: "--safe-ratio",
+                        value
+                    })?;
+                }
+                "--dampener-ratio" if args.command == Command::Generate => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--dampener-ratio"))?;
+                    args.generator.dampener_safe = parse_ratio(&value).ok_or(ArgsError::InvalidValue {
+                        flag: "--dampener-ratio",
+                        value
+                    })?;
+                }
+                "--out" if args.command == Command::Generate => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--out"))?;
+                    args.out = Some(PathBuf::from(value));
+                }
+                #[cfg(feature = "fetch")]
+                "--session" if args.command == Command::Fetch => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--session"))?;
+                    args.session = Some(Session::new(value));
+                }
+                "--sections" => args.sections = true,
+                "--strict" => args.strict = true,
+                "--watch" => args.watch = true,
+                "--time" if args.command == Command::Solve => args.time = true,
+                "--sample" => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--sample"))?;
+                    args.sample = Some(value.parse().map_err(|_| ArgsError::InvalidValue {
+                        flag: "--sample",
+                        value
+                    })?);
+                }
+                "--seed" => {
+                    let value = arguments.next().ok_or(ArgsError::MissingValue("--seed"))?;
+                    args.seed = value.parse().map_err(|_| ArgsError::InvalidValue {
+                        flag: "--seed",
+                        value
+                    })?;
+                }
+                flag if flag.starts_with("--") => return Err(ArgsError::UnknownFlag(argument)),
+                _ if input.is_none() => input = Some(PathBuf::from(argument)),
+                _ if args.command == Command::Diff && args.new_input.is_none() => args.new_input = Some(PathBuf::from(argument)),
+                _ => return Err(ArgsError::UnexpectedArgument(argument))
+            }
+        }
+        if args.command == Command::Generate {
+            if let Some(input) = input {
+                return Err(ArgsError::UnexpectedArgument(input.display().to_string()));
+            }
+            if args.generator.safe + args.generator.dampener_safe > 1.0 {
+                return Err(ArgsError::Conflict {
+                    flag: "--safe-ratio",
+                    with: "--dampener-ratio"
+                });
+            }
+            args.generator.seed = args.seed;
+        }
+        #[cfg(feature = "fetch")]
+        if args.command == Command::Fetch {
+            if let Some(input) = input.take() {
+                return Err(ArgsError::UnexpectedArgument(input.display().to_string()));
+            }
+            if args.session.is_none() {
+                return Err(ArgsError::MissingValue("--session"));
+            }
+        }
+        if let Some(input) = input {
+            args.input = input;
+        } else if args.command == Command::Count {
+            return Err(ArgsError::MissingValue("count"));
+        } else if args.command == Command::Explain {
+            return Err(ArgsError::MissingValue("explain"));
+        } else if args.command == Command::Diff {
+            return Err(ArgsError::MissingValue("diff"));
+        } else if watch_command {
+            return Err(ArgsError::MissingValue("watch"));
+        }
+        if let Some(flag) = tolerance_flag.filter(|_| !matches!(args.command, Command::Count | Command::Explain | Command::Diff)) {
+            return Err(ArgsError::UnexpectedArgument(flag.to_string()));
+        }
+        if args.command == Command::Diff {
+            if args.new_input.is_none() {
+                return Err(ArgsError::MissingValue("diff"));
+            }
+            if tolerance_flag.is_none() && !config_tolerance {
+                args.tolerance = 1;
+            }
+            let with = match args.format {
+                Format::Markdown => Some("--format markdown"),
+                Format::Csv => Some("--format csv"),
+                Format::Text | Format::Json => None
+            };
+            if let Some(with) = with {
+                return Err(ArgsError::Conflict {
+                    flag: "diff",
+                    with
+                });
+            }
+        }
+        if args.command == Command::Count {
+            let with = match args.format {
+                Format::Markdown => Some("--format markdown"),
+                Format::Csv => Some("--format csv"),
+                Format::Text | Format::Json => None
+            };
+            if let Some(with) = with {
+                return Err(ArgsError::Conflict {
+                    flag: "count",
+                    with
+                });
+            }
+        }
+        if args.command == Command::Explain {
+            let with = match args.format {
+                Format::Markdown => Some("--format markdown"),
+                Format::Json => Some("--format json"),
+                Format::Csv => Some("--format csv"),
+                Format::Text => None
+            };
+            if let Some(with) = with {
+                return Err(ArgsError::Conflict {
+                    flag: "explain",
+                    with
+                });
+            }
+        }
+        if args.time {
+            let output = [
+                (args.verify.is_some(), "--verify"),
+                (args.export_fixes.is_some(), "--export-fixes"),
+                (args.top.is_some(), "--top"),
+                (args.format != Format::Text, "--format"),
+                (args.watch, "--watch"),
+                (args.sample.is_some(), "--sample"),
+                (args.sections, "--sections"),
+                (args.delimiter != Delimiter::Whitespace, "--delimiter"),
+                (args.skip_header, "--skip-header"),
+                (args.strict, "--strict")
+            ];
+            if let Some(&(_, with)) = output.iter().find(|(given, _)| *given) {
+                return Err(ArgsError::Conflict {
+                    flag: "--time",
+                    with
+                });
+            }
+        }
+        if args.watch {
+            let once = [
+                (args.verify.is_some(), "--verify"),
+                (args.top.is_some(), "--top"),
+                (args.format != Format::Text, "--format")
+            ];
+            if let Some(&(_, with)) = once.iter().find(|(given, _)| *given) {
+                return Err(ArgsError::Conflict {
+                    flag: "--watch",
+                    with
+                });
+            }
+        }
+        if args.top.is_some() && args.format == Format::Csv {
+            return Err(ArgsError::Conflict {
+                flag: "--top",
+                with: "--format csv"
+            });
+        }
+        if args.sample.is_some() {
+            let exact = [
+                (args.verify.is_some(), "--verify"),
+                (args.export_fixes.is_some(), "--export-fixes"),
+                (args.top.is_some(), "--top"),
+                (args.format == Format::Markdown, "--format markdown"),
+                (args.format == Format::Json, "--format json"),
+                (args.format == Format::Csv, "--format csv"),
+                (args.watch, "--watch"),
+                (args.sections, "--sections")
+            ];
+            if let Some(&(_, with)) = exact.iter().find(|(given, _)| *given) {
+                return Err(ArgsError::Conflict {
+                    flag: "--sample",
+                    with
+                });
+            }
+        }
+        if args.sections {
+            let totals = [
+                (args.verify.is_some(), "--verify"),
+                (args.export_fixes.is_some(), "--export-fixes"),
+                (args.top.is_some(), "--top"),
+                (args.format != Format::Text, "--format")
+            ];
+            if let Some(&(_, with)) = totals.iter().find(|(given, _)| *given) {
+                return Err(ArgsError::Conflict {
+                    flag: "--sections",
+                    with
+                });
+            }
+        }
+        if args.verify.is_some() {
+            let answers = [
+                (args.export_fixes.is_some(), "--export-fixes"),
+                (args.top.is_some(), "--top"),
+                (args.format != Format::Text, "--format")
+            ];
+            if let Some(&(_, with)) = answers.iter().find(|(given, _)| *given) {
+                return Err(ArgsError::Conflict {
+                    flag: "--verify",
+                    with
+                });
+            }
+        }
+        Ok(args)
+    }
+
+    /// Lenient or strict parsing with the input format of the arguments.
+    fn parse_options(&self, strict: bool) -> ParseOptions {
+        ParseOptions {
+            strict,
+            delimiter: self.delimiter,
+            skip_header: self.skip_header,
+            ..ParseOptions::default()
+        }
+    }
+}
+
+/// `whitespace`, `comma`, `tab` or a single visible ASCII character, for
+/// `--delimiter` and the config file.
+pub(crate) fn parse_delimiter(value: &str) -> Option<Delimiter> {
+    match value {
+        "whitespace" => Some(Delimiter::Whitespace),
+        "comma" => Some(Delimiter::Comma),
+        "tab" => Some(Delimiter::Tab),
+        byte if byte.len() == 1 && byte.as_bytes()[0].is_ascii_graphic() => Some(Delimiter::Byte(byte.as_bytes()[0])),
+        _ => None
+    }
+}
+
+/// `--tolerance` and `--part` both set the tolerance, so only one may be given.
+fn set_tolerance_flag(given: &mut Option<&'static str>, flag: &'static str) -> Result<(), ArgsError> {
+    match given.replace(flag) {
+        Some(with) => Err(ArgsError::Conflict {
+            flag,
+            with
+        }),
+        None => Ok(())
+    }
+}
+
+/// `A..B`, `A..=B` or `N`, starting at 4 or more, which unsafe reports
+/// need to be built.
+fn parse_lengths(value: &str) -> Option<Range<usize>> {
+    let lengths = match value.split_once("..") {
+        Some((start, end)) => match end.strip_prefix('=') {
+            Some(end) => start.parse().ok()?..end.parse::<usize>().ok()?.checked_add(1)?,
+            None => start.parse().ok()?..end.parse().ok()?
+        },
+        None => {
+            let len: usize = value.parse().ok()?;
+            len..len.checked_add(1)?
+        }
+    };
+    Some(lengths).filter(|lengths| lengths.start >= 4 && !lengths.is_empty())
+}
+
+/// A fraction from 0 to 1.
+fn parse_ratio(value: &str) -> Option<f64> {
+    value.parse().ok().filter(|ratio| (0.0..=1.0).contains(ratio))
+}
+
+/// `P1,P2`, e.g. `2,4`.
+fn parse_answers(value: &str) -> Option<(u32, u32)> {
+    let (part1, part2) = value.split_once(',')?;
+
+    Some((part1.trim().parse().ok()?, part2.trim().parse().ok()?))
+}
+
+pub fn run(args: &Args, out: &mut impl Write) -> Result<(), CliError> {
+    match args.command {
+        Command::Solve => {}
+        Command::Count if args.input == Path::new("-") => return count(args, io::stdin().lock(), out),
+        Command::Count if args.input.is_dir() => return count_files(args, out),
+        Command::Count => return count(args, BufReader::new(File::open(&args.input).map_err(Day02Error::from)?), out),
+        Command::Diff => {
+            let new_input = args.new_input.as_deref().ok_or(ArgsError::MissingValue("diff"))?;
+            return diff(args, new_input, out);
+        }
+        Command::Explain => {
+            let input = fs::read(&args.input).map_err(Day02Error::from)?;
+            explain_input(&input, args.line, &args.parse_options(true), args.tolerance, args.color.enabled(), out)?;
+            return Ok(());
+        }
+        Command::Generate => {
+            match &args.out {
+                Some(path) => write_generated(&args.generator, &mut BufWriter::new(File::create(path)?))?,
+                None => write_generated(&args.generator, out)?
+            }
+            return Ok(());
+        }
+        #[cfg(feature = "fetch")]
+        Command::Fetch => {
+            let session = args.session.as_ref().ok_or(ArgsError::MissingValue("--session"))?;
+            let solving = Args {
+                command: Command::Solve,
+                input: fetch_input(session, &cache_path()?)?,
+                ..args.clone()
+            };
+            return run(&solving, out);
+        }
+        Command::Repl => {
+            repl(io::stdin().lock(), out)?;
+            return Ok(());
+        }
+        Command::ConfigInit => {
+            write!(out, "{DEFAULT_CONFIG}")?;
+            return Ok(());
+        }
+    }
+    let strict_puzzle = if args.strict {
+        let input = fs::read(&args.input).map_err(Day02Error::from)?;
+        Some(Puzzle::from_bytes_strict_all_with(&input, args.parse_options(true)).map_err(CliError::Invalid)?)
+    } else {
+        None
+    };
+    if args.watch {
+        return watch(args, out);
+    }
+    if args.time {
+        let input = fs::read_to_string(&args.input).map_err(Day02Error::from)?;
+        write!(out, "{}", run_solver(&Day02, &input)?)?;
+        return Ok(());
+    }
+    if let Some(size) = args.sample {
+        return sample(args, size, out);
+    }
+    if args.sections {
+        return sections(args, out);
+    }
+    if let Some((part1, part2)) = args.verify {
+        verify(&args.input, part1, part2)?;
+        writeln!(out, "Verified part 1 ({part1}) and part 2 ({part2})")?;
+        return Ok(());
+    }
+    // With `--strict` the input was read and parsed already.
+    let puzzle = match strict_puzzle {
+        Some(puzzle) => puzzle,
+        None => Puzzle::from_path(&args.input, args.parse_options(false))?.0
+    };
+    if let Some(path) = &args.export_fixes {
+        puzzle.export_dampener_fixes_csv(File::create(path)?)?;
+    }
+    if args.format == Format::Json {
+        writeln!(out, "{}", to_json(args, &puzzle))?;
+        return Ok(());
+    }
+    if args.format == Format::Csv {
+        puzzle.write_classification_csv(out)?;
+        return Ok(());
+    }
+    if let Some(top) = args.top {
+        write_most_fixable(&puzzle, top, out)?;
+    }
+    if args.format == Format::Markdown {
+        write!(out, "{}", puzzle.to_markdown(&MarkdownOptions::default()))?;
+        return Ok(());
+    }
+    let safe_reports = Day02.part1(&puzzle);
+    writeln!(out, "Total safe reports: {safe_reports}")?;
+
+    Ok(())
+}
+
+/// Reports needing more removals than this are listed as hopeless rather
+/// than searched further.
+const FIXABILITY_CAP: u32 = 3;
+
+fn write_most_fixable(puzzle: &Puzzle, top: usize, out: &mut impl Write) -> io::Result<()> {
+    for (index, removals) in puzzle.rank_by_fixability(FIXABILITY_CAP).into_iter().take(top) {
+        let levels = puzzle.reports[index].levels_text();
+        match removals {
+            1 => writeln!(out, "Report {}: {levels} (1 removal)", index + 1)?,
+            removals if removals <= FIXABILITY_CAP => writeln!(out, "Report {}: {levels} ({removals} removals)", index + 1)?,
+            _ => writeln!(out, "Report {}: {levels} (more than {FIXABILITY_CAP} removals)", index + 1)?
+        }
+    }
+    Ok(())
+}
+
+fn count(args: &Args, input: impl BufRead, out: &mut impl Write) -> Result<(), CliError> {
+    let counted = count_reader(args, input)?;
+    if args.format == Format::Json {
+        writeln!(out, "{}", json::object(&[
+            ("input", json::string(&args.input.display().to_string())),
+            ("tolerance", args.tolerance.to_string()),
+            ("total", counted.reports.to_string()),
+            ("safe", counted.safe.to_string())
+        ]))?;
+        return Ok(());
+    }
+    writeln!(out, "Total safe reports: {}", counted.safe)?;
+
+    Ok(())
+}
+
+/// How many reports `--progress` waits for between updates.
+const PROGRESS_EVERY: usize = 1_000_000;
+
+/// Counts `input` strictly, one line at a time, telling how it goes on the
+/// standard error with `--progress`: over the same line on terminals, one
+/// line per update otherwise.
+fn count_reader(args: &Args, input: impl BufRead) -> Result<Progress, Day02Error> {
+    let rules = SafetyRules::default();
+    if !args.progress {
+        return count_safe_from_reader_with_progress(input, &args.parse_options(true), &rules, args.tolerance, 0, &mut |_: &Progress| {});
+    }
+    let terminal = io::stderr().is_terminal();
+    let mut sink = |progress: &Progress| {
+        let line = progress_line(progress);
+        // Progress is only a courtesy, it must not fail the count.
+        let _ = if terminal {
+            write!(io::stderr(), "\r{line}")
+        } else {
+            writeln!(io::stderr(), "{line}")
+        };
+    };
+    let counted = count_safe_from_reader_with_progress(input, &args.parse_options(true), &rules, args.tolerance, PROGRESS_EVERY, &mut sink);
+    if terminal {
+        let _ = writeln!(io::stderr());
+    }
+    counted
+}
+
+/// E.g. `3000000 lines, 2000000 reports, 1200000 safe, 950000 reports/s`.
+fn progress_line(progress: &Progress) -> String {
+    format!(
+        "{} lines, {} reports, {} safe, {:.0} reports/s",
+        progress.lines,
+        progress.reports,
+        progress.safe,
+        progress.reports_per_second()
+    )
+}
+
+fn count_files(args: &Args, out: &mut impl Write) -> Result<(), CliError> {
+    let paths = puzzle_files(&args.input, args.recursive).map_err(Day02Error::from)?;
+    let result = batch(&paths, |path| Ok(count_reader(args, BufReader::new(File::open(path)?))?.safe));
+    if args.format == Format::Json {
+        let files = result.files.iter().map(|file| {
+            let path = ("path", json::string(&file.path.display().to_string()));
+            match &file.safe_reports {
+                Ok(safe_reports) => json::object(&[path, ("safe", safe_reports.to_string())]),
+                Err(error) => json::object(&[path, ("error", json::string(&error.to_string()))])
+            }
+        });
+        writeln!(out, "{}", json::object(&[
+            ("tolerance", args.tolerance.to_string()),
+            ("files", json::array(files)),
+            ("total", result.total.to_string())
+        ]))?;
+    } else {
+        for file in &result.files {
+            match &file.safe_reports {
+                Ok(safe_reports) => writeln!(out, "{}: {safe_reports}", file.path.display())?,
+                Err(error) => writeln!(out, "{}: {error}", file.path.display())?
+            }
+        }
+        writeln!(out, "Total safe reports: {}", result.total)?;
+    }
+    match result.failed() {
+        0 => Ok(()),
+        failed => Err(CliError::Batch {
+            failed
+        })
+    }
+}
+
+fn write_generated(config: &GeneratorConfig, out: &mut impl Write) -> io::Result<()> {
+    for levels in generate_reports(config) {
+        let levels: Vec<String> = levels.iter().map(u32::to_string).collect();
+        writeln!(out, "{}", levels.join(" "))?;
+    }
+    out.flush()
+}
+
+fn sections(args: &Args, out: &mut impl Write) -> Result<(), CliError> {
+    let input = fs::read_to_string(&args.input).map_err(Day02Error::from)?;
+    let sections = parse_sections(&input).map_err(Day02Error::from)?;
+    let mut total = 0;
+    for (index, section) in sections.iter().enumerate() {
+        let safe_reports = section.count_safe(&SafetyRules::default(), 0);
+        total += safe_reports;
+        writeln!(out, "Section {}: {safe_reports}", index + 1)?;
+    }
+    writeln!(out, "Total safe reports: {total}")?;
+
+    Ok(())
+}
+
+/// The answers under `settings`, the flags they were computed with, every
+/// report under `reports`, and the most fixable reports with `--top`.
+fn to_json(args: &Args, puzzle: &Puzzle) -> String {
+    let settings = json::object(&[
+        ("input", json::string(&args.input.display().to_string())),
+        ("format", json::string(args.format.as_str())),
+        ("strict", args.strict.to_string()),
+        ("top", json::option(args.top)),
+        ("tolerance", "1".to_string())
+    ]);
+    let mut fields = vec![
+        ("settings", settings),
+        ("summary", puzzle.summary(&SafetyRules::default(), 1).to_json()),
+        ("reports", json::array(puzzle.safety_reports(&SafetyRules::default()).iter().map(SafetyReport::to_json)))
+    ];
+    if let Some(top) = args.top {
+        let most_fixable = puzzle.rank_by_fixability(FIXABILITY_CAP)
+        .into_iter()
+        .take(top)
+        .map(|(index, removals)| json::object(&[("index", index.to_string()), ("removals", removals.to_string())]));
+        fields.push(("most_fixable", json::array(most_fixable)));
+    }
+    json::object(&fields)
+}
+
+fn sample(args: &Args, size: usize, out: &mut impl Write) -> Result<(), CliError> {
+    let mut counter = SampledCounter::with_options(SafetyRules::default(), 1, size, args.seed, args.parse_options(false));
+    let mut input = BufReader::new(File::open(&args.input).map_err(Day02Error::from)?);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if input.read_until(b'\n', &mut line).map_err(Day02Error::from)? == 0 {
+            break;
+        }
+        counter.push_line_bytes(line.strip_suffix(b"\n").unwrap_or(&line)).map_err(Day02Error::from)?;
+    }
+    let estimate = counter.estimate();
+    writeln!(out, "Sampled {} of {} reports (seed {})", estimate.sample_size, estimate.total, args.seed)?;
+    for (part, count) in [(1, estimate.part1), (2, estimate.part2)] {
+        writeln!(out, "Part {part}: ~{} ({:.1}%, 95% interval {}..={})", count.count, count.fraction * 100.0, count.low, count.high)?;
+    }
+    Ok(())
+}
+
+/// How long to wait for more changes before re-running, and how often the
+/// file is checked.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// How many reports that changed status [`render_parts`] lists at most.
+const LISTED_CHANGES: usize = 10;
+
+fn watch(args: &Args, out: &mut impl Write) -> Result<(), CliError> {
+    let (sender, changes) = channel();
+    poll_changes(args.input.clone(), DEBOUNCE / 2, sender);
+    let mut previous = None;
+    render_parts(args, &mut previous, out)?;
+    watch_loop(&changes, DEBOUNCE, || render_parts(args, &mut previous, out))?;
+
+    Ok(())
+}
+
+/// Both parts under a timestamped separator. After the first run, the parts
+/// show how much they moved and the reports whose part 2 status changed are
+/// listed, against `previous`, the last puzzle that could be read. A puzzle
+/// that cannot be read is reported in their place, as the file may be
+/// halfway through being saved.
+fn render_parts(args: &Args, previous: &mut Option<Puzzle>, out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "=== {} ===", format_timestamp(SystemTime::now()))?;
+    let puzzle = match Puzzle::from_path(&args.input, args.parse_options(false)) {
+        Ok((puzzle, _)) => puzzle,
+        Err(error) => return writeln!(out, "{error}")
+    };
+    let rules = SafetyRules::default();
+    for (part, tolerance) in [(1, 0), (2, 1)] {
+        let safe = puzzle.count_safe(&rules, tolerance);
+        match previous.as_ref().map(|previous| safe as i64 - previous.count_safe(&rules, tolerance) as i64) {
+            Some(moved) if moved != 0 => writeln!(out, "Part {part}: {safe} ({moved:+})")?,
+            _ => writeln!(out, "Part {part}: {safe}")?
+        }
+    }
+    if let Some(previous) = previous.as_ref() {
+        let (_, changes) = describe_changes(previous, &puzzle, 1);
+        for change in changes.iter().take(LISTED_CHANGES) {
+            writeln!(out, "  {change}")?;
+        }
+        if changes.len() > LISTED_CHANGES {
+            writeln!(out, "  ... and {} more", changes.len() - LISTED_CHANGES)?;
+        }
+    }
+    *previous = Some(puzzle);
+
+    Ok(())
+}
+
+/// How the reports differ from `old` to `new` with up to `tolerance`
+/// removals, and one line per report that changed status, was added or was
+/// removed, reports being numbered from 1.
+fn describe_changes(old: &Puzzle, new: &Puzzle, tolerance: u32) -> (PuzzleDiff, Vec<String>) {
+    let rules = SafetyRules::default();
+    let diff = compare_puzzles(old, new, tolerance);
+    let (old, new) = (old.statuses(&rules, tolerance), new.statuses(&rules, tolerance));
+    let changes = diff
+    .changed
+    .iter()
+    .map(|change| format!("report {}: {} -> {}", change.index + 1, change.old.as_str(), change.new.as_str()))
+    .chain(diff.only_in_b.iter().map(|&index| format!("report {}: new, {}", index + 1, new[index].as_str())))
+    .chain(diff.only_in_a.iter().map(|&index| format!("report {}: removed, was {}", index + 1, old[index].as_str())))
+    .collect();
+
+    (diff, changes)
+}
+
+fn diff(args: &Args, new_input: &Path, out: &mut impl Write) -> Result<(), CliError> {
+    let (old, _) = Puzzle::from_path(&args.input, args.parse_options(args.strict))?;
+    let (new, _) = Puzzle::from_path(new_input, args.parse_options(args.strict))?;
+    let (diff, changes) = describe_changes(&old, &new, args.tolerance);
+    if args.format == Format::Json {
+        let (old_statuses, new_statuses) = (old.statuses(&SafetyRules::default(), args.tolerance), new.statuses(&SafetyRules::default(), args.tolerance));
+        let changed = diff.changed.iter().map(|change| json::object(&[
+            ("index", change.index.to_string()),
+            ("old", json::string(change.old.as_str())),
+            ("new", json::string(change.new.as_str()))
+        ]));
+        let status_of = |statuses: &[ReportStatus], index: usize| json::object(&[
+            ("index", index.to_string()),
+            ("status", json::string(statuses[index].as_str()))
+        ]);
+        writeln!(out, "{}", json::object(&[
+            ("old", json::string(&args.input.display().to_string())),
+            ("new", json::string(&new_input.display().to_string())),
+            ("tolerance", args.tolerance.to_string()),
+            ("changed", json::array(changed)),
+            ("added", json::array(diff.only_in_b.iter().map(|&index| status_of(&new_statuses, index)))),
+            ("removed", json::array(diff.only_in_a.iter().map(|&index| status_of(&old_statuses, index)))),
+            ("unchanged", diff.unchanged.to_string())
+        ]))?;
+        return Ok(());
+    }
+    for change in &changes {
+        writeln!(out, "{change}")?;
+    }
+    writeln!(
+        out,
+        "{} changed, {} added, {} removed, {} unchanged",
+        diff.changed.len(),
+        diff.only_in_b.len(),
+        diff.only_in_a.len(),
+        diff.unchanged
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    fn parse(arguments: &[&str]) -> Result<Args, ArgsError> {
+        Args::parse(arguments.iter().map(|argument| argument.to_string()))
+    }
+
+    fn run_with(arguments: &[&str]) -> Result<String, CliError> {
+        let mut out = Vec::new();
+        run(&parse(arguments)?, &mut out)?;
+
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn should_parse_default_to_resources_puzzle() {
+        assert_eq!(parse(&[]), Ok(Args::default()));
+    }
+
+    #[test]
+    fn should_parse_input_and_verify() {
+        assert_eq!(parse(&["--verify", "2,4", "input.txt"]), Ok(Args {
+            input: PathBuf::from("input.txt"),
+            verify: Some((2, 4)),
+            ..Args::default()
+        }));
+    }
+
+    #[test]
+    fn should_parse_reject_bad_arguments() {
+        assert_eq!(parse(&["--verify"]), Err(ArgsError::MissingValue("--verify")));
+        assert_eq!(parse(&["--verify", "2"]), Err(ArgsError::InvalidValue {
+            flag: "--verify",
+            value: "2".to_string()
+        }));
+        assert_eq!(parse(&["--nope"]), Err(ArgsError::UnknownFlag("--nope".to_string())));
+        assert_eq!(parse(&["a.txt", "b.txt"]), Err(ArgsError::UnexpectedArgument("b.txt".to_string())));
+    }
+
+    #[test]
+    fn should_parse_repl_only_as_first_argument() {
+        assert_eq!(parse(&["repl"]), Ok(Args {
+            command: Command::Repl,
+            ..Args::default()
+        }));
+        assert_eq!(parse(&["input.txt", "repl"]), Err(ArgsError::UnexpectedArgument("repl".to_string())));
+    }
+
+    #[test]
+    fn should_parse_input_flag_and_watch() {
+        assert_eq!(parse(&["--input", "input.txt", "--watch"]), Ok(Args {
+            input: PathBuf::from("input.txt"),
+            watch: true,
+            ..Args::default()
+        }));
+        assert_eq!(parse(&["a.txt", "--input", "b.txt"]), Err(ArgsError::UnexpectedArgument("--input".to_string())));
+        assert_eq!(parse(&["watch", "input.txt"]), parse(&["input.txt", "--watch"]));
+        assert_eq!(parse(&["watch"]), Err(ArgsError::MissingValue("watch")));
+    }
+
+    #[test]
+    fn should_render_parts_under_timestamp() {
+        let mut out = Vec::new();
+
+        render_parts(&parse(&["tests/resources/puzzle.txt"]).unwrap(), &mut None, &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("=== ") && out.contains(" UTC ===\n"), "{out}");
+        assert!(out.ends_with("Part 1: 2\nPart 2: 4\n"), "{out}");
+    }
+
+    #[test]
+    fn should_render_parts_highlight_changes_since_previous_run() {
+        let path = env::temp_dir().join(format!("day_02_watch_{}.txt", process::id()));
+        let input = fs::read_to_string("tests/resources/puzzle.txt").unwrap();
+        let args = parse(&["watch", path.to_str().unwrap()]).unwrap();
+        let mut previous = None;
+        let mut renders = Vec::new();
+        for input in [input.clone(), format!("{}\n1 5 9\n", input.trim_end().replace("1 2 7 8 9", "1 2 4 5 6")), input] {
+            fs::write(&path, input).unwrap();
+            let mut out = Vec::new();
+            render_parts(&args, &mut previous, &mut out).unwrap();
+            let out = String::from_utf8(out).unwrap();
+            renders.push(out.split_once(" UTC ===\n").unwrap().1.to_string());
+        }
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(renders, [
+            "Part 1: 2\nPart 2: 4\n",
+            "Part 1: 3 (+1)\nPart 2: 5 (+1)\n  report 2: unsafe -> safe\n  report 7: new, unsafe\n",
+            "Part 1: 2 (-1)\nPart 2: 4 (-1)\n  report 2: safe -> unsafe\n  report 7: removed, was unsafe\n"
+        ]);
+    }
+
+    #[test]
+    fn should_run_print_safe_reports() {
+        assert_eq!(run_with(&["tests/resources/puzzle.txt"]).unwrap(), "Total safe reports: 2\n");
+    }
+
+    #[test]
+    fn should_run_list_most_fixable_reports() {
+        assert_eq!(run_with(&["tests/resources/puzzle.txt", "--top", "3"]).unwrap(), "\
+            Report 4: 1 3 2 4 5 (1 removal)\n\
+            Report 5: 8 6 4 4 1 (1 removal)\n\
+            Report 2: 1 2 7 8 9 (2 removals)\n\
+            Total safe reports: 2\n");
+        assert_eq!(parse(&["--top", "x"]), Err(ArgsError::InvalidValue {
+            flag: "--top",
+            value: "x".to_string()
+        }));
+    }
+
+    #[test]
+    fn should_run_print_markdown() {
+        assert_eq!(
+            run_with(&["tests/resources/puzzle.txt", "--format", "markdown"]).unwrap(),
+            fs::read_to_string("tests/resources/puzzle_markdown.md").unwrap()
+        );
+        assert_eq!(parse(&["--format", "html"]), Err(ArgsError::InvalidValue {
+            flag: "--format",
+            value: "html".to_string()
+        }));
+    }
+
+    #[test]
+    fn should_run_sample_whole_fixture_exactly() {
+        assert_eq!(run_with(&["tests/resources/puzzle.txt", "--sample", "100", "--seed", "9"]).unwrap(), "\
+            Sampled 6 of 6 reports (seed 9)\n\
+            Part 1: ~2 (33.3%, 95% interval 2..=2)\n\
+            Part 2: ~4 (66.7%, 95% interval 4..=4)\n");
+    }
+
+    #[test]
+    fn should_run_read_delimited_input_with_header() {
+        assert_eq!(run_with(&["tests/resources/puzzle_header.csv", "--delimiter", "comma", "--skip-header"]).unwrap(), "Total safe reports: 2\n");
+        assert_eq!(run_with(&["count", "tests/resources/puzzle_header.csv", "--delimiter", ",", "--skip-header", "--part", "2"]).unwrap(), "Total safe reports: 4\n");
+        assert!(matches!(
+            run_with(&["tests/resources/puzzle_header.csv", "--delimiter", "comma", "--strict"]),
+            Err(CliError::Invalid(errors)) if errors.len() == 1
+        ));
+        assert_eq!(parse(&["--delimiter", "::"]), Err(ArgsError::InvalidValue {
+            flag: "--delimiter",
+            value: "::".to_string()
+        }));
+    }
+
+    #[test]
+    fn should_run_print_classification_csv() {
+        let out = run_with(&["tests/resources/puzzle.txt", "--format", "csv"]).unwrap();
+
+        assert_eq!(out.lines().count(), 7);
+        assert!(out.contains("4,\"1 3 2 4 5\",safe_with_dampener,direction,1,1,3\r\n"), "{out}");
+        assert_eq!(parse(&["count", "a.txt", "--format", "csv"]), Err(ArgsError::Conflict {
+            flag: "count",
+            with: "--format csv"
+        }));
+    }
+
+    #[test]
+    fn should_parse_reject_flags_ignored_by_watch_or_csv() {
+        assert_eq!(parse(&["--watch", "--verify", "2,4"]), Err(ArgsError::Conflict {
+            flag: "--watch",
+            with: "--verify"
+        }));
+        assert_eq!(parse(&["--top", "3", "--format", "csv"]), Err(ArgsError::Conflict {
+            flag: "--top",
+            with: "--format csv"
+        }));
+    }
+
+    #[test]
+    fn should_parse_reject_flags_ignored_by_sections() {
+        for (flags, with) in [
+            (&["--export-fixes", "fixes.csv"][..], "--export-fixes"),
+            (&["--top", "3"], "--top"),
+            (&["--format", "json"], "--format")
+        ] {
+            assert_eq!(parse(&[&["--sections"], flags].concat()), Err(ArgsError::Conflict {
+                flag: "--sections",
+                with
+            }));
+        }
+    }
+
+    #[test]
+    fn should_parse_reject_flags_ignored_by_verify() {
+        for (flags, with) in [
+            (&["--export-fixes", "fixes.csv"][..], "--export-fixes"),
+            (&["--top", "3"], "--top"),
+            (&["--format", "json"], "--format")
+        ] {
+            assert_eq!(parse(&[&["--verify", "2,4"], flags].concat()), Err(ArgsError::Conflict {
+                flag: "--verify",
+                with
+            }));
+        }
+    }
+
+    #[test]
+    fn should_parse_reject_input_format_flags_with_time() {
+        for (flags, with) in [
+            (&["--delimiter", "comma"][..], "--delimiter"),
+            (&["--skip-header"], "--skip-header"),
+            (&["--strict"], "--strict")
+        ] {
+            assert_eq!(parse(&[&["--time"], flags].concat()), Err(ArgsError::Conflict {
+                flag: "--time",
+                with
+            }));
+        }
+    }
+
+    #[test]
+    fn should_parse_reject_sample_with_exact_output() {
+        assert_eq!(parse(&["--sample", "10", "--verify", "2,4"]), Err(ArgsError::Conflict {
+            flag: "--sample",
+            with: "--verify"
+        }));
+        assert_eq!(parse(&["--format", "markdown", "--sample", "10"]), Err(ArgsError::Conflict {
+            flag: "--sample",
+            with: "--format markdown"
+        }));
+    }
+
+    #[test]
+    fn should_run_strict_list_every_bad_line() {
+        let error = run_with(&["tests/resources/puzzle_three_errors.txt", "--strict"]).unwrap_err();
+
+        assert_eq!(error.to_string(), "\
+            3 bad line(s) in puzzle:\n\
+            line 2: `x` is not a level\n\
+            line 4: level `99999999999` is out of range\n\
+            line 7: `3.5` is not a level");
+        assert_eq!(run_with(&["tests/resources/puzzle.txt", "--strict"]).unwrap(), "Total safe reports: 2\n");
+    }
+
+    #[test]
+    fn should_command_line_override_config_file() {
+        let out = run_with(&["--config", "tests/resources/day02.toml", "--top", "1"]).unwrap();
+
+        assert_eq!(out, concat!(
+            r#"{"settings":{"input":"tests/resources/puzzle.txt","format":"json","strict":true,"top":1,"tolerance":1},"#,
+            r#""summary":{"total":6,"part1":2,"part2":4,"tolerance":1},"reports":["#,
+            r#"{"levels":[7,6,4,2,1],"status":"safe","violation":null,"violation_index":null,"fix":null},"#,
+            r#"{"levels":[1,2,7,8,9],"status":"unsafe","violation":"step","violation_index":1,"fix":null},"#,
+            r#"{"levels":[9,7,6,2,1],"status":"unsafe","violation":"step","violation_index":2,"fix":null},"#,
+            r#"{"levels":[1,3,2,4,5],"status":"safe_with_dampener","violation":"direction","violation_index":1,"fix":{"removed_index":1,"removed_level":3}},"#,
+            r#"{"levels":[8,6,4,4,1],"status":"safe_with_dampener","violation":"step","violation_index":2,"fix":{"removed_index":2,"removed_level":4}},"#,
+            r#"{"levels":[1,3,6,7,9],"status":"safe","violation":null,"violation_index":null,"fix":null}],"#,
+            r#""most_fixable":[{"index":3,"removals":1}]}"#,
+            "\n"
+        ));
+    }
+
+    #[test]
+    fn should_tolerance_flag_override_config_file() {
+        let config = ["--config", "tests/resources/day02_count.toml"];
+
+        assert_eq!(run_with(&[&["count", "tests/resources/puzzle_header.csv"][..], &config].concat()).unwrap(), "Total safe reports: 2\n");
+        assert_eq!(
+            run_with(&[&["count", "tests/resources/puzzle_header.csv"][..], &config, &["--part", "2"]].concat()).unwrap(),
+            "Total safe reports: 4\n"
+        );
+        assert_eq!(parse(&[&["diff", "old.txt", "new.txt"][..], &config].concat()).map(|args| args.tolerance), Ok(0));
+    }
+
+    #[test]
+    fn should_parse_report_bad_config() {
+        assert!(matches!(
+            parse(&["--config", "tests/resources/missing.toml"]),
+            Err(ArgsError::Config(ConfigError::Unreadable { .. }))
+        ));
+        assert_eq!(parse(&["--config"]), Err(ArgsError::MissingValue("--config")));
+    }
+
+    #[test]
+    fn should_config_init_print_default_config() {
+        assert_eq!(run_with(&["config", "init"]).unwrap(), DEFAULT_CONFIG);
+        assert_eq!(parse(&["config", "show"]), Err(ArgsError::UnexpectedArgument("show".to_string())));
+    }
+
+    #[test]
+    fn should_run_print_each_section() {
+        assert_eq!(
+            run_with(&["tests/resources/puzzle_sections.txt", "--sections"]).unwrap(),
+            "Section 1: 2\nSection 2: 2\nSection 3: 1\nTotal safe reports: 5\n"
+        );
+    }
+
+    #[test]
+    fn should_run_export_fixes_as_csv() {
+        let path = env::temp_dir().join(format!("day_02_fixes_{}.csv", process::id()));
+
+        run_with(&["tests/resources/puzzle.txt", "--export-fixes", path.to_str().unwrap()]).unwrap();
+
+        let csv = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+    }
+
+    fn count_with(arguments: &[&str], input: &str) -> Result<String, CliError> {
+        let mut out = Vec::new();
+        count(&parse(arguments)?, input.as_bytes(), &mut out)?;
+
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn should_parse_count_tolerance_and_part() {
+        assert_eq!(parse(&["count", "-", "--part", "2"]), Ok(Args {
+            command: Command::Count,
+            input: PathBuf::from("-"),
+            tolerance: 1,
+            ..Args::default()
+        }));
+        assert_eq!(parse(&["count", "a.txt", "--tolerance", "3"]).map(|args| args.tolerance), Ok(3));
+        assert_eq!(parse(&["count", "a.txt", "--part", "2", "--tolerance", "1"]), Err(ArgsError::Conflict {
+            flag: "--tolerance",
+            with: "--part"
+        }));
+        assert_eq!(parse(&["count", "a.txt", "--part", "3"]), Err(ArgsError::InvalidValue {
+            flag: "--part",
+            value: "3".to_string()
+        }));
+        assert_eq!(parse(&["count"]), Err(ArgsError::MissingValue("count")));
+        assert_eq!(parse(&["a.txt", "--tolerance", "1"]), Err(ArgsError::UnexpectedArgument("--tolerance".to_string())));
+    }
+
+    #[test]
+    fn should_run_count_both_parts_of_file() {
+        assert_eq!(run_with(&["count", "tests/resources/puzzle.txt"]).unwrap(), "Total safe reports: 2\n");
+        assert_eq!(run_with(&["count", "tests/resources/puzzle.txt", "--part", "2"]).unwrap(), "Total safe reports: 4\n");
+        assert_eq!(
+            run_with(&["count", "tests/resources/puzzle.txt", "--tolerance", "1", "--format", "json"]).unwrap(),
+            "{\"input\":\"tests/resources/puzzle.txt\",\"tolerance\":1,\"total\":6,\"safe\":4}\n"
+        );
+    }
+
+    #[test]
+    fn should_count_read_input_and_fail_on_bad_line() {
+        assert_eq!(count_with(&["count", "-"], "7 6 4 2 1\n1 3 2 4 5\n").unwrap(), "Total safe reports: 1\n");
+        assert!(matches!(
+            count_with(&["count", "-"], "7 6 4 2 1\n1 x 2\n"),
+            Err(CliError::Puzzle(Day02Error::Parse(_)))
+        ));
+    }
+
+    #[test]
+    fn should_count_with_progress_give_same_answer_and_describe_progress() {
+        assert_eq!(parse(&["count", "a.txt", "--progress"]).map(|args| args.progress), Ok(true));
+        assert_eq!(parse(&["a.txt", "--progress"]), Err(ArgsError::UnknownFlag("--progress".to_string())));
+        assert_eq!(run_with(&["count", "tests/resources/puzzle.txt", "--part", "2", "--progress"]).unwrap(), "Total safe reports: 4\n");
+        assert_eq!(progress_line(&Progress {
+            lines: 7,
+            reports: 6,
+            safe: 4,
+            elapsed: Duration::from_secs(2)
+        }), "7 lines, 6 reports, 4 safe, 3 reports/s");
+    }
+
+    #[test]
+    fn should_time_print_both_parts() {
+        let output = run_with(&["tests/resources/puzzle.txt", "--time"]).unwrap();
+
+        assert!(output.starts_with("Parse: "), "{output}");
+        assert!(output.contains("\nPart 1: 2 (") && output.contains("\nPart 2: 4 ("), "{output}");
+        assert_eq!(parse(&["--time", "--format", "json"]), Err(ArgsError::Conflict {
+            flag: "--time",
+            with: "--format"
+        }));
+        assert_eq!(parse(&["count", "a.txt", "--time"]), Err(ArgsError::UnknownFlag("--time".to_string())));
+    }
+
+    #[test]
+    fn should_count_every_file_of_directory() {
+        let dir = env::temp_dir().join(format!("day_02_count_{}", process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::copy("tests/resources/puzzle.txt", dir.join("a.txt")).unwrap();
+        fs::write(dir.join("b.txt"), "1 x 2\n").unwrap();
+        fs::copy("tests/resources/puzzle.txt", dir.join("nested/c.txt")).unwrap();
+        let mut out = Vec::new();
+
+        let flat = run(&parse(&["count", dir.to_str().unwrap(), "--part", "2"]).unwrap(), &mut out);
+        let mut json = Vec::new();
+        let recursive = run(&parse(&["count", dir.to_str().unwrap(), "--recursive", "--format", "json"]).unwrap(), &mut json);
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(flat, Err(CliError::Batch { failed: 1 })));
+        assert_eq!(String::from_utf8(out).unwrap(), format!(
+            "{}: 4\n{}: cannot parse puzzle: line 1: `x` is not a level\nTotal safe reports: 4\n",
+            dir.join("a.txt").display(),
+            dir.join("b.txt").display()
+        ));
+        assert!(matches!(recursive, Err(CliError::Batch { failed: 1 })));
+        assert!(String::from_utf8(json).unwrap().ends_with(r#"c.txt","safe":2}],"total":4}
+"#));
+        assert_eq!(parse(&["a.txt", "--recursive"]), Err(ArgsError::UnknownFlag("--recursive".to_string())));
+    }
+
+    #[cfg(feature = "fetch")]
+    #[test]
+    fn should_parse_fetch_session() {
+        assert_eq!(parse(&["fetch", "--session", "cookie", "--format", "json"]), Ok(Args {
+            command: Command::Fetch,
+            format: Format::Json,
+            session: Some(Session::new("cookie")),
+            ..Args::default()
+        }));
+        assert_eq!(parse(&["fetch"]), Err(ArgsError::MissingValue("--session")));
+        assert_eq!(parse(&["fetch", "a.txt", "--session", "cookie"]), Err(ArgsError::UnexpectedArgument("a.txt".to_string())));
+        assert_eq!(parse(&["--session", "cookie"]), Err(ArgsError::UnknownFlag("--session".to_string())));
+    }
+
+    #[test]
+    fn should_parse_explain_line_and_color() {
+        assert_eq!(parse(&["explain", "a.txt", "--line", "4", "--part", "2", "--color", "never"]), Ok(Args {
+            command: Command::Explain,
+            input: PathBuf::from("a.txt"),
+            line: Some(4),
+            tolerance: 1,
+            color: ColorChoice::Never,
+            ..Args::default()
+        }));
+        assert_eq!(parse(&["explain", "a.txt", "--line", "0"]), Err(ArgsError::InvalidValue {
+            flag: "--line",
+            value: "0".to_string()
+        }));
+        assert_eq!(parse(&["explain"]), Err(ArgsError::MissingValue("explain")));
+        assert_eq!(parse(&["explain", "a.txt", "--format", "json"]), Err(ArgsError::Conflict {
+            flag: "explain",
+            with: "--format json"
+        }));
+        assert_eq!(parse(&["a.txt", "--line", "4"]), Err(ArgsError::UnknownFlag("--line".to_string())));
+    }
+
+    #[test]
+    fn should_run_explain_one_line() {
+        assert_eq!(run_with(&["explain", "tests/resources/puzzle.txt", "--line", "5", "--color", "never"]).unwrap(), "line 5: 8 6 [4 4] 1\n  descending, unsafe: diff 0 at pair 3\n");
+    }
+
+    #[test]
+    fn should_parse_diff_inputs() {
+        assert_eq!(parse(&["diff", "old.txt", "new.txt"]), Ok(Args {
+            command: Command::Diff,
+            input: PathBuf::from("old.txt"),
+            new_input: Some(PathBuf::from("new.txt")),
+            tolerance: 1,
+            ..Args::default()
+        }));
+        assert_eq!(parse(&["diff", "old.txt", "new.txt", "--part", "1"]).map(|args| args.tolerance), Ok(0));
+        assert_eq!(parse(&["diff", "old.txt"]), Err(ArgsError::MissingValue("diff")));
+        assert_eq!(parse(&["diff", "a", "b", "c"]), Err(ArgsError::UnexpectedArgument("c".to_string())));
+        assert_eq!(parse(&["a.txt", "b.txt"]), Err(ArgsError::UnexpectedArgument("b.txt".to_string())));
+    }
+
+    #[test]
+    fn should_run_diff_list_status_changes() {
+        let path = env::temp_dir().join(format!("day_02_diff_{}.txt", process::id()));
+        let input = fs::read_to_string("tests/resources/puzzle.txt").unwrap();
+        fs::write(&path, input.replace("1 2 7 8 9", "1 2 4 5 6").replace("1 3 6 7 9", "1 3 6 7 19")).unwrap();
+        let path = path.to_str().unwrap();
+
+        let text = run_with(&["diff", "tests/resources/puzzle.txt", path]);
+        let json = run_with(&["diff", path, "tests/resources/puzzle.txt", "--part", "1", "--format", "json"]);
+
+        fs::remove_file(path).unwrap();
+        assert_eq!(text.unwrap(), "report 2: unsafe -> safe\nreport 6: safe -> safe_with_dampener\n2 changed, 0 added, 0 removed, 4 unchanged\n");
+        assert_eq!(json.unwrap(), format!("{{\"old\":\"{path}\",\"new\":\"tests/resources/puzzle.txt\",\"tolerance\":0,\"changed\":[\
+{{\"index\":1,\"old\":\"safe\",\"new\":\"unsafe\"}},{{\"index\":5,\"old\":\"unsafe\",\"new\":\"safe\"}}],\"added\":[],\"removed\":[],\"unchanged\":4}}\n"));
+    }
+
+    #[test]
+    fn should_parse_generate_settings() {
+        assert_eq!(parse(&["generate", "--reports", "10", "--len", "5..=9", "--safe-ratio", "0.4", "--seed", "3"]).map(|args| args.generator), Ok(GeneratorConfig {
+            reports: 10,
+            levels: 5..10,
+            safe: 0.4,
+            seed: 3,
+            ..GeneratorConfig::default()
+        }));
+        assert_eq!(parse(&["generate", "--len", "6"]).map(|args| args.generator.levels), Ok(6..7));
+        for (flag, value) in [("--len", "3..8"), ("--len", "8..8"), ("--safe-ratio", "1.5")] {
+            assert_eq!(parse(&["generate", flag, value]), Err(ArgsError::InvalidValue {
+                flag,
+                value: value.to_string()
+            }));
+        }
+        assert_eq!(parse(&["generate", "--safe-ratio", "0.6", "--dampener-ratio", "0.6"]), Err(ArgsError::Conflict {
+            flag: "--safe-ratio",
+            with: "--dampener-ratio"
+        }));
+        assert_eq!(parse(&["--reports", "10"]), Err(ArgsError::UnknownFlag("--reports".to_string())));
+    }
+
+    #[test]
+    fn should_run_generate_puzzle_with_known_answers() {
+        let arguments = ["generate", "--reports", "200", "--len", "5..10", "--safe-ratio", "0.4", "--dampener-ratio", "0.3", "--seed", "7"];
+        let out = run_with(&arguments).unwrap();
+
+        let (puzzle, _) = Puzzle::parse(&out, ParseOptions::strict()).unwrap();
+        let summary = puzzle.summary(&SafetyRules::default(), 1);
+        assert_eq!((summary.total, summary.part1, summary.part2), (200, 80, 140));
+        assert!(puzzle.iter().all(|report| (5..10).contains(&report.levels().len())));
+        assert_eq!(run_with(&arguments).unwrap(), out);
+    }
+
+    #[test]
+    fn should_run_verify_answers() {
+        assert_eq!(run_with(&["tests/resources/puzzle.txt", "--verify", "2,4"]).unwrap(), "Verified part 1 (2) and part 2 (4)\n");
+        assert!(matches!(
+            run_with(&["tests/resources/puzzle.txt", "--verify", "2,5"]),
+            Err(CliError::Verify(VerifyError::Part2 { expected: 5, actual: 4 }))
+        ));
+    }
+}
@@ -0,0 +1,323 @@
+//! `--config PATH`: the `day_02` flags kept in a TOML file.
+//!
+//! Only the part of TOML the flags need is read: `key = value` lines with
+//! strings, integers, booleans or, for `verify`, a pair of integers, and `#`
+//! comments. Flags given on the command line win over the file.
+
+use std::{error::Error, fmt, fs, path::{Path, PathBuf}};
+
+use crate::{
+    cli::{parse_delimiter, Args, Format},
+    Delimiter
+};
+
+/// What `day_02 config init` prints: every key, commented out at its
+/// default.
+pub const DEFAULT_CONFIG: &str = "\
+# day_02 settings. Flags given on the command line win over these.
+
+# The puzzle to solve.
+# input = \"src/resources/puzzle.txt\"
+
+# Expected part 1 and part 2 answers to check instead of printing.
+# verify = [242, 311]
+
+# Where to write the dampener fixes as CSV.
+# export_fixes = \"fixes.csv\"
+
+# How many of the most fixable unsafe reports to list.
+# top = 5
+
+# \"text\", \"markdown\", \"json\" or \"csv\".
+# format = \"text\"
+
+# Refuse inputs with bad lines instead of skipping them.
+# strict = false
+
+# Print both parts again whenever the input changes.
+# watch = false
+
+# Estimate both parts from this many reports picked with `seed`.
+# sample = 1000
+# seed = 0
+
+# Solve each blank-line separated section on its own.
+# sections = false
+
+# How many levels `count`, `explain` and `diff` may remove from each report.
+# tolerance = 0
+
+# \"whitespace\", \"comma\", \"tab\" or any other single character between levels.
+# delimiter = \"whitespace\"
+
+# Ignore the first line of the input, such as a CSV header.
+# skip_header = false
+";
+
+/// The settings of a config file; the keys left out keep their defaults.
+#[derive(PartialEq, Eq, Clone, Default)]
+#[derive(Debug)]
+pub struct CliConfig {
+    pub input: Option<PathBuf>,
+    pub verify: Option<(u32, u32)>,
+    pub export_fixes: Option<PathBuf>,
+    pub top: Option<usize>,
+    pub format: Option<Format>,
+    pub strict: Option<bool>,
+    pub watch: Option<bool>,
+    pub sample: Option<usize>,
+    pub seed: Option<u64>,
+    pub sections: Option<bool>,
+    pub tolerance: Option<u32>,
+    pub delimiter: Option<Delimiter>,
+    pub skip_header: Option<bool>
+}
+
+/// A config file that cannot be used. Lines are 1-based.
+#[derive(PartialEq, Eq, Clone)]
+#[derive(Debug)]
+pub enum ConfigError {
+    Unreadable { path: PathBuf, message: String },
+    Syntax { line: usize },
+    UnknownKey { line: usize, key: String },
+    InvalidValue { line: usize, key: String, value: String },
+    DuplicateKey { line: usize, key: String }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Unreadable { path, message } => write!(f, "cannot read config `{}`: {message}", path.display()),
+            ConfigError::Syntax { line } => write!(f, "config line {line}: expected `key = value`"),
+            ConfigError::UnknownKey { line, key } => write!(f, "config line {line}: unknown key `{key}`"),
+            ConfigError::InvalidValue { line, key, value } => write!(f, "config line {line}: invalid value `{value}` for `{key}`"),
+            ConfigError::DuplicateKey { line, key } => write!(f, "config line {line}: `{key}` is already set")
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+enum Value {
+    String(String),
+    Integer(u64),
+    Boolean(bool),
+    Pair(u64, u64)
+}
+
+impl CliConfig {
+    pub fn load(path: &Path) -> Result<CliConfig, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|error| ConfigError::Unreadable {
+            path: path.to_path_buf(),
+            message: error.to_string()
+        })?;
+
+        CliConfig::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<CliConfig, ConfigError> {
+        let mut config = CliConfig::default();
+        let mut seen = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, raw) = line.split_once('=').ok_or(ConfigError::Syntax {
+                line: line_number
+            })?;
+            let (key, raw) = (key.trim(), raw.trim());
+            if seen.contains(&key) {
+                return Err(ConfigError::DuplicateKey {
+                    line: line_number,
+                    key: key.to_string()
+                });
+            }
+            seen.push(key);
+            let invalid = || ConfigError::InvalidValue {
+                line: line_number,
+                key: key.to_string(),
+                value: raw.to_string()
+            };
+            let value = parse_value(raw).ok_or_else(invalid)?;
+            match (key, value) {
+                ("input", Value::String(path)) => config.input = Some(PathBuf::from(path)),
+                ("verify", Value::Pair(part1, part2)) => {
+                    config.verify = Some((u32::try_from(part1).map_err(|_| invalid())?, u32::try_from(part2).map_err(|_| invalid())?));
+                }
+                ("export_fixes", Value::String(path)) => config.export_fixes = Some(PathBuf::from(path)),
+                ("top", Value::Integer(top)) => config.top = Some(usize::try_from(top).map_err(|_| invalid())?),
+                ("format", Value::String(format)) => config.format = Some(match format.as_str() {
+                    "text" => Format::Text,
+                    "markdown" => Format::Markdown,
+                    "json" => Format::Json,
+                    "csv" => Format::Csv,
+                    _ => return Err(invalid())
+                }),
+                ("strict", Value::Boolean(strict)) => config.strict = Some(strict),
+                ("watch", Value::Boolean(watch)) => config.watch = Some(watch),
+                ("sample", Value::Integer(sample)) => config.sample = Some(usize::try_from(sample).map_err(|_| invalid())?),
+                ("seed", Value::Integer(seed)) => config.seed = Some(seed),
+                ("sections", Value::Boolean(sections)) => config.sections = Some(sections),
+                ("tolerance", Value::Integer(tolerance)) => config.tolerance = Some(u32::try_from(tolerance).map_err(|_| invalid())?),
+                ("delimiter", Value::String(delimiter)) => config.delimiter = Some(parse_delimiter(&delimiter).ok_or_else(invalid)?),
+                ("skip_header", Value::Boolean(skip_header)) => config.skip_header = Some(skip_header),
+                (
+                    "input" | "verify" | "export_fixes" | "top" | "format" | "strict" | "watch" | "sample" | "seed" | "sections" | "tolerance" | "delimiter"
+                    | "skip_header",
+                    _
+                ) => return Err(invalid()),
+                _ => return Err(ConfigError::UnknownKey {
+                    line: line_number,
+                    key: key.to_string()
+                })
+            }
+        }
+        Ok(config)
+    }
+
+    /// Overwrites the settings of `args` that the file sets.
+    pub fn apply(&self, args: &mut Args) {
+        if let Some(input) = &self.input {
+            args.input = input.clone();
+        }
+        args.verify = self.verify.or(args.verify);
+        if let Some(export_fixes) = &self.export_fixes {
+            args.export_fixes = Some(export_fixes.clone());
+        }
+        args.top = self.top.or(args.top);
+        args.format = self.format.unwrap_or(args.format);
+        args.strict = self.strict.unwrap_or(args.strict);
+        args.watch = self.watch.unwrap_or(args.watch);
+        args.sample = self.sample.or(args.sample);
+        args.seed = self.seed.unwrap_or(args.seed);
+        args.sections = self.sections.unwrap_or(args.sections);
+        args.tolerance = self.tolerance.unwrap_or(args.tolerance);
+        args.delimiter = self.delimiter.unwrap_or(args.delimiter);
+        args.skip_header = self.skip_header.unwrap_or(args.skip_header);
+    }
+}
+
+/// The line up to a `#` that is not inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, character) in line.char_indices() {
+        match character {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_value(raw: &str) -> Option<Value> {
+    if let Some(string) = raw.strip_prefix('"').and_then(|rest| rest.strip_suffix('"')) {
+        return parse_string(string).map(Value::String);
+    }
+    if let Some(items) = raw.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+        let (first, second) = items.split_once(',')?;
+        return Some(Value::Pair(parse_integer(first.trim())?, parse_integer(second.trim())?));
+    }
+    match raw {
+        "true" => Some(Value::Boolean(true)),
+        "false" => Some(Value::Boolean(false)),
+        _ => parse_integer(raw).map(Value::Integer)
+    }
+}
+
+/// TOML allows `_` between digits.
+fn parse_integer(raw: &str) -> Option<u64> {
+    if raw.starts_with('_') || raw.ends_with('_') || raw.contains("__") {
+        return None;
+    }
+    raw.replace('_', "").parse().ok()
+}
+
+/// The inside of a basic string, with `\"` and `\\` escapes.
+fn parse_string(inside: &str) -> Option<String> {
+    let mut string = String::with_capacity(inside.len());
+    let mut characters = inside.chars();
+    while let Some(character) = characters.next() {
+        match character {
+            '\\' => match characters.next()? {
+                '"' => string.push('"'),
+                '\\' => string.push('\\'),
+                'n' => string.push('\n'),
+                't' => string.push('\t'),
+                _ => return None
+            },
+            '"' => return None,
+            character => string.push(character)
+        }
+    }
+    Some(string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_every_key() {
+        let config = CliConfig::parse("\
+            input = \"a # b.txt\" # the puzzle\n\
+            verify = [2, 4]\n\
+            export_fixes = \"fixes.csv\"\n\
+            top = 1_000\n\
+            format = \"markdown\"\n\
+            strict = true\n\
+            watch = false\n\
+            sample = 10\n\
+            seed = 7\n\
+            sections = true\n\
+            tolerance = 2\n\
+            delimiter = \";\"\n\
+            skip_header = true\n");
+
+        assert_eq!(config, Ok(CliConfig {
+            input: Some(PathBuf::from("a # b.txt")),
+            verify: Some((2, 4)),
+            export_fixes: Some(PathBuf::from("fixes.csv")),
+            top: Some(1000),
+            format: Some(Format::Markdown),
+            strict: Some(true),
+            watch: Some(false),
+            sample: Some(10),
+            seed: Some(7),
+            sections: Some(true),
+            tolerance: Some(2),
+            delimiter: Some(Delimiter::Byte(b';')),
+            skip_header: Some(true)
+        }));
+    }
+
+    #[test]
+    fn should_default_config_set_nothing() {
+        assert_eq!(CliConfig::parse(DEFAULT_CONFIG), Ok(CliConfig::default()));
+    }
+
+    #[test]
+    fn should_parse_name_offending_line_and_key() {
+        assert_eq!(CliConfig::parse("top = 1\n\ntolerence = 1"), Err(ConfigError::UnknownKey {
+            line: 3,
+            key: "tolerence".to_string()
+        }));
+        assert_eq!(CliConfig::parse("strict = \"yes\""), Err(ConfigError::InvalidValue {
+            line: 1,
+            key: "strict".to_string(),
+            value: "\"yes\"".to_string()
+        }));
+        assert_eq!(CliConfig::parse("# settings\nstrict"), Err(ConfigError::Syntax {
+            line: 2
+        }));
+        assert_eq!(CliConfig::parse("top = 1\ntop = 2"), Err(ConfigError::DuplicateKey {
+            line: 2,
+            key: "top".to_string()
+        }));
+    }
+}
@@ -0,0 +1,104 @@
+//! Which reports changed status between two versions of an input.
+
+use crate::{Puzzle, ReportStatus, SafetyRules};
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub struct StatusChange {
+    /// 0-based index of the report in both puzzles.
+    pub index: usize,
+    pub old: ReportStatus,
+    pub new: ReportStatus
+}
+
+/// Reports are paired by index; the longer puzzle's extra reports are listed
+/// on their own.
+#[derive(PartialEq, Eq, Clone, Default)]
+#[derive(Debug)]
+pub struct PuzzleDiff {
+    pub only_in_a: Vec<usize>,
+    pub only_in_b: Vec<usize>,
+    pub changed: Vec<StatusChange>,
+    pub unchanged: usize
+}
+
+impl PuzzleDiff {
+    pub fn is_empty(&self) -> bool {
+        self.only_in_a.is_empty() && self.only_in_b.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares the status of every report of `a` and `b` under the default
+/// rules, with up to `tolerance` removals.
+pub fn compare_puzzles(a: &Puzzle, b: &Puzzle, tolerance: u32) -> PuzzleDiff {
+    let rules = SafetyRules::default();
+    let old = a.statuses(&rules, tolerance);
+    let new = b.statuses(&rules, tolerance);
+    let mut diff = PuzzleDiff {
+        only_in_a: (new.len()..old.len()).collect(),
+        only_in_b: (old.len()..new.len()).collect(),
+        ..PuzzleDiff::default()
+    };
+    for (index, (&old, &new)) in old.iter().zip(&new).enumerate() {
+        if old == new {
+            diff.unchanged += 1;
+        } else {
+            diff.changed.push(StatusChange {
+                index,
+                old,
+                new
+            });
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+    use crate::ParseOptions;
+
+    fn parse(input: &str) -> Puzzle {
+        Puzzle::parse(input, ParseOptions::strict()).unwrap().0
+    }
+
+    #[test]
+    fn should_compare_puzzles_pinpoint_mutated_line() {
+        let input = read_to_string("tests/resources/puzzle.txt").unwrap();
+        let mutated = input.replace("1 2 7 8 9", "1 2 4 5 6");
+
+        let diff = compare_puzzles(&parse(&input), &parse(&mutated), 1);
+
+        assert_eq!(diff, PuzzleDiff {
+            changed: vec![StatusChange {
+                index: 1,
+                old: ReportStatus::Unsafe,
+                new: ReportStatus::Safe
+            }],
+            unchanged: 5,
+            ..PuzzleDiff::default()
+        });
+    }
+
+    #[test]
+    fn should_compare_puzzles_list_extra_trailing_reports() {
+        let a = parse("1 2 3\n3 2 1\n1 5 9");
+        let b = parse("1 2 3");
+
+        let diff = compare_puzzles(&a, &b, 0);
+
+        assert_eq!(diff.only_in_a, vec![1, 2]);
+        assert!(diff.only_in_b.is_empty());
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(compare_puzzles(&b, &a, 0).only_in_b, vec![1, 2]);
+    }
+
+    #[test]
+    fn should_compare_puzzles_be_empty_for_same_input() {
+        let input = read_to_string("tests/resources/puzzle.txt").unwrap();
+
+        assert!(compare_puzzles(&parse(&input), &parse(&input), 1).is_empty());
+    }
+}
@@ -0,0 +1,40 @@
+use std::{error::Error, fmt, io};
+
+use crate::ParseError;
+
+/// Everything that can go wrong when solving a puzzle read from a file.
+#[derive(Debug)]
+pub enum Day02Error {
+    Io(io::Error),
+    Parse(ParseError)
+}
+
+impl fmt::Display for Day02Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Day02Error::Io(error) => write!(f, "cannot read puzzle: {error}"),
+            Day02Error::Parse(error) => write!(f, "cannot parse puzzle: {error}")
+        }
+    }
+}
+
+impl Error for Day02Error {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Day02Error::Io(error) => Some(error),
+            Day02Error::Parse(error) => Some(error)
+        }
+    }
+}
+
+impl From<io::Error> for Day02Error {
+    fn from(error: io::Error) -> Day02Error {
+        Day02Error::Io(error)
+    }
+}
+
+impl From<ParseError> for Day02Error {
+    fn from(error: ParseError) -> Day02Error {
+        Day02Error::Parse(error)
+    }
+}
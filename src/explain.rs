@@ -0,0 +1,166 @@
+//! `day_02 explain`: each report with the pair that breaks the rules
+//! highlighted, which way it goes and what the Problem Dampener would
+//! remove, e.g.
+//!
+//! ```text
+//! line 4: 1 [{3} 2] 4 5
+//!   mixed, safe with dampener: direction change at pair 2, fixed by removing level 2 (3)
+//! ```
+//!
+//! Without colors the offending pair is bracketed and the removed level
+//! braced; with them the pair is red and the removed level yellow.
+
+use std::io::{self, Write};
+
+use crate::{
+    min_removals_to_safe,
+    parse::{lines, parse_line},
+    puzzle::violation_text,
+    report::Report,
+    Direction, ParseOptions, ParseSummary, ReportStatus, SafetyRules
+};
+
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// Explains every line of `input` that holds a report, or only line `only`
+/// (1-based), parsing each with `options`. Lines that do not parse are
+/// explained by their error.
+pub(crate) fn explain_input(input: &[u8], only: Option<usize>, options: &ParseOptions, tolerance: u32, color: bool, mut out: impl Write) -> io::Result<()> {
+    let rules = SafetyRules::default();
+    let mut found = false;
+    for (location, line) in lines(input) {
+        if only.is_some_and(|only| only != location.line) {
+            continue;
+        }
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        match parse_line(line, location, options, &mut ParseSummary::default()) {
+            Ok(Some(levels)) => {
+                found = true;
+                write!(out, "{}", explain_report(location.line, &Report::new(levels), &rules, tolerance, color))?;
+            }
+            Ok(None) => {}
+            Err(error) => {
+                found = true;
+                writeln!(out, "line {}: cannot parse: {error}", location.line)?;
+            }
+        }
+    }
+    match only {
+        Some(only) if !found => writeln!(out, "line {only}: no report"),
+        _ => Ok(())
+    }
+}
+
+/// The report on `line` and why it is safe or not once up to `tolerance`
+/// levels may be removed, over two lines.
+pub(crate) fn explain_report(line: usize, report: &Report, rules: &SafetyRules, tolerance: u32, color: bool) -> String {
+    let violation = report.violation(rules);
+    let pair = violation.and_then(|violation| violation.index());
+    let status = report.classify(rules, tolerance);
+    let fix = match status {
+        ReportStatus::SafeWithDampener => report.dampener_fix(rules),
+        ReportStatus::Safe | ReportStatus::Unsafe => None
+    };
+    let removed = fix.as_ref().map(|fix| fix.removed_index);
+    let levels: Vec<String> = report
+    .levels()
+    .iter()
+    .enumerate()
+    .map(|(index, level)| {
+        let in_pair = pair.is_some_and(|pair| index == pair || index == pair + 1);
+        let level = match (removed == Some(index), color) {
+            (true, true) => format!("{YELLOW}{level}{}", if in_pair { RED } else { RESET }),
+            (true, false) => format!("{{{level}}}"),
+            (false, _) => level.to_string()
+        };
+        match (pair, color) {
+            (Some(pair), true) if index == pair => format!("{RED}{level}"),
+            (Some(pair), true) if index == pair + 1 => format!("{level}{RESET}"),
+            (Some(pair), false) if index == pair => format!("[{level}"),
+            (Some(pair), false) if index == pair + 1 => format!("{level}]"),
+            _ => level
+        }
+    })
+    .collect();
+    let reason = violation.map(violation_text).unwrap_or_default();
+    let verdict = match (status, fix) {
+        (ReportStatus::Safe, _) => "safe".to_string(),
+        (ReportStatus::SafeWithDampener, Some(fix)) => {
+            format!("safe with dampener: {reason}, fixed by removing level {} ({})", fix.removed_index + 1, fix.removed_level)
+        }
+        (ReportStatus::SafeWithDampener, None) => {
+            let removals = min_removals_to_safe(report.levels(), rules, tolerance).unwrap_or(tolerance);
+            format!("safe with dampener: {reason}, fixed by removing {removals} levels")
+        }
+        (ReportStatus::Unsafe, _) => format!("unsafe: {reason}")
+    };
+
+    format!("line {line}: {}\n  {}, {verdict}\n", levels.join(" "), direction_text(report.direction()))
+}
+
+fn direction_text(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Ascending => "ascending",
+        Direction::Descending => "descending",
+        Direction::Mixed => "mixed",
+        Direction::Flat => "flat"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn explained(only: Option<usize>, tolerance: u32, color: bool) -> String {
+        let input = fs::read("tests/resources/puzzle.txt").unwrap();
+        let mut out = Vec::new();
+        explain_input(&input, only, &ParseOptions::strict(), tolerance, color, &mut out).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn should_explain_input_highlight_violating_pairs() {
+        assert_eq!(explained(None, 0, false), "\
+line 1: 7 6 4 2 1
+  descending, safe
+line 2: 1 [2 7] 8 9
+  ascending, unsafe: diff 5 at pair 2
+line 3: 9 7 [6 2] 1
+  descending, unsafe: diff 4 at pair 3
+line 4: 1 [3 2] 4 5
+  mixed, unsafe: direction change at pair 2
+line 5: 8 6 [4 4] 1
+  descending, unsafe: diff 0 at pair 3
+line 6: 1 3 6 7 9
+  ascending, safe
+");
+    }
+
+    #[test]
+    fn should_explain_input_show_dampener_removal_when_tolerance_1() {
+        assert_eq!(explained(Some(4), 1, false), "line 4: 1 [{3} 2] 4 5\n  mixed, safe with dampener: direction change at pair 2, fixed by removing level 2 (3)\n");
+        assert_eq!(explained(Some(4), 1, true), "line 4: 1 \x1b[31m\x1b[33m3\x1b[31m 2\x1b[0m 4 5\n  mixed, safe with dampener: direction change at pair 2, fixed by removing level 2 (3)\n");
+        assert_eq!(explained(Some(2), 1, true), "line 2: 1 \x1b[31m2 7\x1b[0m 8 9\n  ascending, unsafe: diff 5 at pair 2\n");
+    }
+
+    #[test]
+    fn should_explain_input_report_missing_and_bad_lines() {
+        assert_eq!(explained(Some(99), 0, false), "line 99: no report\n");
+
+        let mut out = Vec::new();
+        explain_input(b"1 2 3\n1 x 3\n", None, &ParseOptions::strict(), 0, false, &mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with("line 1: 1 2 3\n  ascending, safe\nline 2: cannot parse: "));
+    }
+
+    #[test]
+    fn should_explain_report_count_removals_beyond_one() {
+        let report = Report::new(vec![1, 9, 2, 8, 3]);
+
+        assert_eq!(explain_report(1, &report, &SafetyRules::default(), 2, false), "line 1: [1 9] 2 8 3\n  mixed, safe with dampener: diff 8 at pair 1, fixed by removing 2 levels\n");
+    }
+}
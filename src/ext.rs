@@ -0,0 +1,68 @@
+//! Classifying reports that are already in memory, in whatever collection
+//! they come in, without building a [`Puzzle`](crate::Puzzle).
+
+use crate::{removals::min_removals_to_safe_reusing, SafetyPolicy, SafetyRules};
+
+/// Counting and filtering for any iterator of reports, such as
+/// `Vec<Vec<u32>>::iter()` or slices of levels.
+pub trait SafeReportsExt: Iterator + Sized {
+    /// The reports `policy` finds safe as-is.
+    fn filter_safe<P: SafetyPolicy>(self, policy: P) -> impl Iterator<Item = Self::Item>
+    where
+        Self::Item: AsRef<[u32]>
+    {
+        self.filter(move |levels| policy.is_safe(levels.as_ref()))
+    }
+
+    /// How many reports are safe under the default rules once up to
+    /// `tolerance` levels may be removed: 0 for part 1, 1 for part 2.
+    fn count_safe_with_tolerance(self, tolerance: u32) -> usize
+    where
+        Self::Item: AsRef<[u32]>
+    {
+        self.count_safe_with(&SafetyRules::default(), tolerance)
+    }
+
+    /// Like [`SafeReportsExt::count_safe_with_tolerance`], under `rules`.
+    fn count_safe_with(self, rules: &SafetyRules, tolerance: u32) -> usize
+    where
+        Self::Item: AsRef<[u32]>
+    {
+        let mut scratch = Vec::new();
+        self.filter(|levels| min_removals_to_safe_reusing(levels.as_ref(), rules, tolerance, &mut scratch).is_some()).count()
+    }
+}
+
+impl<I: Iterator> SafeReportsExt for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<Vec<u32>> {
+        vec![vec![7, 6, 4, 2, 1], vec![1, 2, 7, 8, 9], vec![9, 7, 6, 2, 1], vec![1, 3, 2, 4, 5], vec![8, 6, 4, 4, 1], vec![1, 3, 6, 7, 9]]
+    }
+
+    #[test]
+    fn should_count_safe_with_tolerance_on_any_collection() {
+        let reports = sample();
+
+        assert_eq!(reports.iter().count_safe_with_tolerance(0), 2);
+        assert_eq!(reports.iter().map(Vec::as_slice).count_safe_with_tolerance(1), 4);
+        assert_eq!(reports.into_iter().count_safe_with(&SafetyRules {
+            max_step: 5,
+            ..SafetyRules::default()
+        }, 0), 4);
+    }
+
+    #[test]
+    fn should_filter_safe_keep_reports_policy_accepts() {
+        let reports = sample();
+
+        let safe: Vec<&Vec<u32>> = reports.iter().filter_safe(SafetyRules::default()).collect();
+        let short: Vec<&Vec<u32>> = reports.iter().filter_safe(|levels: &[u32]| levels[0] > 8).collect();
+
+        assert_eq!(safe, vec![&reports[0], &reports[5]]);
+        assert_eq!(short, vec![&reports[2]]);
+    }
+}
@@ -0,0 +1,151 @@
+//! Downloading the day 2 input from adventofcode.com, once.
+//!
+//! The input is fetched with the system `curl`, which does the TLS, and kept
+//! in `~/.cache/aoc/2024/02/input.txt` (under `$XDG_CACHE_HOME` when set), so
+//! later runs never hit the site again.
+
+use std::{
+    env,
+    error::Error,
+    ffi::OsString,
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio}
+};
+
+pub const INPUT_URL: &str = "https://adventofcode.com/2024/day/2/input";
+
+/// The value of the `session` cookie of a logged-in adventofcode.com
+/// browser. It is never printed, `Debug` included.
+#[derive(PartialEq, Eq, Clone)]
+pub struct Session(String);
+
+impl Session {
+    pub fn new(cookie: impl Into<String>) -> Session {
+        Session(cookie.into())
+    }
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Session(..)")
+    }
+}
+
+#[derive(Debug)]
+pub enum FetchError {
+    /// Neither `$XDG_CACHE_HOME` nor `$HOME` is set.
+    NoCacheDir,
+    /// `curl` could not be run, or the cache could not be written.
+    Io(io::Error),
+    /// `curl` ran but the download failed, with what it said on stderr.
+    Download(String)
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::NoCacheDir => write!(f, "cannot find a cache directory: set HOME or XDG_CACHE_HOME"),
+            FetchError::Io(error) => write!(f, "cannot fetch puzzle input: {error}"),
+            FetchError::Download(message) => write!(f, "cannot download {INPUT_URL}: {message}")
+        }
+    }
+}
+
+impl Error for FetchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FetchError::Io(error) => Some(error),
+            FetchError::NoCacheDir | FetchError::Download(_) => None
+        }
+    }
+}
+
+impl From<io::Error> for FetchError {
+    fn from(error: io::Error) -> FetchError {
+        FetchError::Io(error)
+    }
+}
+
+/// Where the input is cached, from the environment.
+pub fn cache_path() -> Result<PathBuf, FetchError> {
+    cache_path_in(env::var_os("XDG_CACHE_HOME"), env::var_os("HOME"))
+}
+
+fn cache_path_in(xdg_cache_home: Option<OsString>, home: Option<OsString>) -> Result<PathBuf, FetchError> {
+    let cache = xdg_cache_home
+    .filter(|dir| !dir.is_empty())
+    .map(PathBuf::from)
+    .or_else(|| home.filter(|dir| !dir.is_empty()).map(|home| Path::new(&home).join(".cache")))
+    .ok_or(FetchError::NoCacheDir)?;
+
+    Ok(cache.join("aoc/2024/02/input.txt"))
+}
+
+/// The path of the input at `cache`, downloading it there first unless a
+/// previous run already did. The download goes to a temporary file next to
+/// `cache`, so an interrupted one never leaves a partial input behind.
+pub fn fetch_input(session: &Session, cache: &Path) -> Result<PathBuf, FetchError> {
+    if fs::metadata(cache).is_ok_and(|metadata| metadata.len() > 0) {
+        return Ok(cache.to_path_buf());
+    }
+    if let Some(dir) = cache.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let partial = cache.with_extension("part");
+    let mut curl = Command::new("curl")
+    .args(["--fail", "--silent", "--show-error", "--location"])
+    .args(["--user-agent", concat!("day_02/", env!("CARGO_PKG_VERSION"), " (Advent of Code 2024 day 2 solver)")])
+    // From the standard input, so the cookie never shows in the process list.
+    .args(["--header", "@-", "--output"])
+    .arg(&partial)
+    .arg(INPUT_URL)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::null())
+    .stderr(Stdio::piped())
+    .spawn()?;
+    if let Some(mut stdin) = curl.stdin.take() {
+        writeln!(stdin, "Cookie: session={}", session.0)?;
+    }
+    let output = curl.wait_with_output()?;
+    if !output.status.success() {
+        let _ = fs::remove_file(&partial);
+        return Err(FetchError::Download(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    fs::rename(&partial, cache)?;
+
+    Ok(cache.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+
+    use super::*;
+
+    #[test]
+    fn should_cache_path_prefer_xdg_cache_home() {
+        assert_eq!(cache_path_in(Some("/xdg".into()), Some("/home/me".into())).unwrap(), PathBuf::from("/xdg/aoc/2024/02/input.txt"));
+        assert_eq!(cache_path_in(Some("".into()), Some("/home/me".into())).unwrap(), PathBuf::from("/home/me/.cache/aoc/2024/02/input.txt"));
+        assert!(matches!(cache_path_in(None, None), Err(FetchError::NoCacheDir)));
+    }
+
+    #[test]
+    fn should_fetch_input_reuse_cached_input() {
+        let dir = env::temp_dir().join(format!("day_02_fetch_{}", process::id()));
+        let cache = dir.join("aoc/2024/02/input.txt");
+        fs::create_dir_all(cache.parent().unwrap()).unwrap();
+        fs::write(&cache, "7 6 4 2 1\n").unwrap();
+
+        let fetched = fetch_input(&Session::new("unused"), &cache);
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(fetched.unwrap(), cache);
+    }
+
+    #[test]
+    fn should_session_never_be_printed() {
+        assert_eq!(format!("{:?}", Session::new("53616c7465645f5f")), "Session(..)");
+    }
+}
@@ -0,0 +1,219 @@
+//! C ABI for calling the solver from other languages. Build the shared
+//! library with
+//! `cargo rustc --release --lib --features ffi --crate-type cdylib`.
+//!
+//! Every function catches panics before they reach the caller. Failures
+//! return an error code and leave a message behind for
+//! `day02_last_error_message`, kept per thread.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr},
+    panic::{catch_unwind, UnwindSafe},
+    ptr, slice
+};
+
+use crate::{Day02Error, ParseOptions, Puzzle, SafetyRules};
+
+pub const DAY02_OK: i32 = 0;
+pub const DAY02_NULL_ARGUMENT: i32 = 1;
+pub const DAY02_INVALID_UTF8: i32 = 2;
+pub const DAY02_IO_ERROR: i32 = 3;
+pub const DAY02_PARSE_ERROR: i32 = 4;
+pub const DAY02_PANIC: i32 = 5;
+
+/// Returned by `day02_count_safe` when it could not count.
+pub const DAY02_COUNT_ERROR: u64 = u64::MAX;
+
+thread_local! {
+    static LAST_ERROR: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+struct FfiError {
+    code: i32,
+    message: String
+}
+
+impl FfiError {
+    fn new(code: i32, message: &str) -> FfiError {
+        FfiError {
+            code,
+            message: message.to_string()
+        }
+    }
+}
+
+impl From<Day02Error> for FfiError {
+    fn from(error: Day02Error) -> FfiError {
+        let code = match error {
+            Day02Error::Io(_) => DAY02_IO_ERROR,
+            Day02Error::Parse(_) => DAY02_PARSE_ERROR
+        };
+        FfiError::new(code, &error.to_string())
+    }
+}
+
+fn guarded<T>(call: impl FnOnce() -> Result<T, FfiError> + UnwindSafe) -> Result<T, i32> {
+    let result = catch_unwind(call).unwrap_or_else(|_| Err(FfiError::new(DAY02_PANIC, "day_02 panicked")));
+    LAST_ERROR.with(|last_error| match &result {
+        Ok(_) => last_error.borrow_mut().clear(),
+        Err(error) => *last_error.borrow_mut() = error.message.clone()
+    });
+
+    result.map_err(|error| error.code)
+}
+
+fn count(puzzle: &Puzzle, tolerance: u32) -> u64 {
+    puzzle.count_safe(&SafetyRules::default(), tolerance) as u64
+}
+
+/// Counts the safe reports of the `input_len` bytes at `input_ptr`, or
+/// returns `DAY02_COUNT_ERROR`.
+///
+/// # Safety
+///
+/// `input_ptr` must be null or point to `input_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn day02_count_safe(input_ptr: *const u8, input_len: usize, tolerance: u32) -> u64 {
+    guarded(|| {
+        if input_ptr.is_null() {
+            return Err(FfiError::new(DAY02_NULL_ARGUMENT, "input is null"));
+        }
+        let input = slice::from_raw_parts(input_ptr, input_len);
+        let (puzzle, _) = Puzzle::from_bytes(input, ParseOptions::lenient()).map_err(Day02Error::from)?;
+
+        Ok(count(&puzzle, tolerance))
+    })
+    .unwrap_or(DAY02_COUNT_ERROR)
+}
+
+/// Counts the safe reports of the file at `path_cstr` into `out_count`.
+///
+/// # Safety
+///
+/// `path_cstr` must be null or a NUL-terminated string, and `out_count` must
+/// be null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn day02_count_safe_file(path_cstr: *const c_char, tolerance: u32, out_count: *mut u64) -> i32 {
+    let result = guarded(|| {
+        if path_cstr.is_null() || out_count.is_null() {
+            return Err(FfiError::new(DAY02_NULL_ARGUMENT, "path or out_count is null"));
+        }
+        let path = CStr::from_ptr(path_cstr)
+        .to_str()
+        .map_err(|_| FfiError::new(DAY02_INVALID_UTF8, "path is not valid UTF-8"))?;
+        let (puzzle, _) = Puzzle::from_path(path, ParseOptions::lenient())?;
+        *out_count = count(&puzzle, tolerance);
+
+        Ok(())
+    });
+
+    result.err().unwrap_or(DAY02_OK)
+}
+
+/// Copies the message of the last failure on this thread, NUL-terminated and
+/// truncated to fit, into `buffer`. Returns the full length of the message
+/// without the NUL, so a caller can retry with a larger buffer.
+///
+/// # Safety
+///
+/// `buffer` must be null or point to `buffer_len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn day02_last_error_message(buffer: *mut c_char, buffer_len: usize) -> usize {
+    LAST_ERROR.with(|last_error| {
+        let message = last_error.borrow();
+        if !buffer.is_null() && buffer_len > 0 {
+            let copied = message.len().min(buffer_len - 1);
+            ptr::copy_nonoverlapping(message.as_ptr() as *const c_char, buffer, copied);
+            *buffer.add(copied) = 0;
+        }
+        message.len()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::CString, fs::read_to_string};
+
+    use super::*;
+
+    fn last_error_message() -> String {
+        let mut buffer = [0 as c_char; 256];
+        unsafe {
+            day02_last_error_message(buffer.as_mut_ptr(), buffer.len());
+
+            CStr::from_ptr(buffer.as_ptr()).to_str().unwrap().to_string()
+        }
+    }
+
+    #[test]
+    fn should_count_safe_sample_for_both_parts() {
+        let input = read_to_string("tests/resources/puzzle.txt").unwrap();
+
+        assert_eq!(unsafe { day02_count_safe(input.as_ptr(), input.len(), 0) }, 2);
+        assert_eq!(unsafe { day02_count_safe(input.as_ptr(), input.len(), 1) }, 4);
+    }
+
+    #[test]
+    fn should_count_safe_fail_on_null_input() {
+        assert_eq!(unsafe { day02_count_safe(ptr::null(), 3, 0) }, DAY02_COUNT_ERROR);
+        assert_eq!(last_error_message(), "input is null");
+    }
+
+    #[test]
+    fn should_count_safe_file_write_count() {
+        let path = CString::new("tests/resources/puzzle.txt").unwrap();
+        let mut count = 0;
+
+        assert_eq!(unsafe { day02_count_safe_file(path.as_ptr(), 1, &mut count) }, DAY02_OK);
+        assert_eq!(count, 4);
+        assert_eq!(last_error_message(), "");
+    }
+
+    #[test]
+    fn should_count_safe_file_fail_on_null_arguments() {
+        let path = CString::new("tests/resources/puzzle.txt").unwrap();
+        let mut count = 0;
+
+        assert_eq!(unsafe { day02_count_safe_file(ptr::null(), 0, &mut count) }, DAY02_NULL_ARGUMENT);
+        assert_eq!(unsafe { day02_count_safe_file(path.as_ptr(), 0, ptr::null_mut()) }, DAY02_NULL_ARGUMENT);
+    }
+
+    #[test]
+    fn should_count_safe_file_fail_on_invalid_utf8_path() {
+        let path = CString::new(vec![b't', 0xFF]).unwrap();
+        let mut count = 0;
+
+        assert_eq!(unsafe { day02_count_safe_file(path.as_ptr(), 0, &mut count) }, DAY02_INVALID_UTF8);
+        assert_eq!(last_error_message(), "path is not valid UTF-8");
+    }
+
+    #[test]
+    fn should_count_safe_file_fail_on_missing_file() {
+        let path = CString::new("tests/resources/missing.txt").unwrap();
+        let mut count = 7;
+
+        assert_eq!(unsafe { day02_count_safe_file(path.as_ptr(), 0, &mut count) }, DAY02_IO_ERROR);
+        assert_eq!(count, 7);
+        assert!(last_error_message().starts_with("cannot read puzzle"));
+    }
+
+    #[test]
+    fn should_last_error_message_truncate_and_return_full_length() {
+        unsafe { day02_count_safe(ptr::null(), 0, 0) };
+        let mut buffer = [0 as c_char; 6];
+
+        let length = unsafe { day02_last_error_message(buffer.as_mut_ptr(), buffer.len()) };
+
+        assert_eq!(length, "input is null".len());
+        assert_eq!(unsafe { CStr::from_ptr(buffer.as_ptr()) }.to_str().unwrap(), "input");
+    }
+
+    #[test]
+    fn should_guarded_turn_panic_into_error_code() {
+        let result: Result<(), i32> = guarded(|| panic!("boom"));
+
+        assert_eq!(result, Err(DAY02_PANIC));
+        assert_eq!(last_error_message(), "day_02 panicked");
+    }
+}
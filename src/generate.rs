@@ -0,0 +1,212 @@
+//! Seeded puzzles of any size, for benchmarks and tests. The same config,
+//! seed included, always gives the same puzzle.
+
+use std::{
+    io::{self, Write},
+    ops::Range
+};
+
+use crate::{report::Report, rng::Rng, Puzzle};
+
+#[derive(PartialEq, Clone)]
+#[derive(Debug)]
+pub struct GeneratorConfig {
+    pub reports: usize,
+    /// Levels per report. Must start at 4 or more, which unsafe reports need
+    /// to be built.
+    pub levels: Range<usize>,
+    /// Fraction of the reports safe as-is.
+    pub safe: f64,
+    /// Fraction of the reports that are unsafe but safe once the Problem
+    /// Dampener removes one level. The rest stay unsafe either way.
+    pub dampener_safe: f64,
+    pub seed: u64
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> GeneratorConfig {
+        GeneratorConfig {
+            reports: 1000,
+            levels: 5..9,
+            safe: 0.25,
+            dampener_safe: 0.25,
+            seed: 0
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Kind {
+    Safe,
+    DampenerSafe,
+    Unsafe
+}
+
+/// Each kind of report is unsafe or safe by construction, and their counts
+/// are the fractions of `config.reports`, rounded.
+pub fn generate_puzzle(config: &GeneratorConfig) -> Puzzle {
+    Puzzle::from_reports(generate_reports(config).map(Report::new).collect())
+}
+
+/// The reports of [`generate_puzzle`] one at a time, for inputs too large to
+/// hold in memory.
+pub fn generate_reports(config: &GeneratorConfig) -> Reports {
+    assert!(config.levels.start >= 4 && config.levels.start < config.levels.end, "levels must be a non-empty range starting at 4 or more");
+    assert!(config.safe >= 0.0 && config.dampener_safe >= 0.0 && config.safe + config.dampener_safe <= 1.0, "fractions must add up to at most 1");
+
+    let safe = (config.reports as f64 * config.safe).round() as usize;
+    let dampener_safe = ((config.reports as f64 * config.dampener_safe).round() as usize).min(config.reports - safe);
+    Reports {
+        rng: Rng::seeded(config.seed),
+        levels: config.levels.clone(),
+        remaining: [safe, dampener_safe, config.reports - safe - dampener_safe]
+    }
+}
+
+pub struct Reports {
+    rng: Rng,
+    levels: Range<usize>,
+    /// Safe, dampener-safe and unsafe reports still to generate.
+    remaining: [usize; 3]
+}
+
+impl Iterator for Reports {
+    type Item = Vec<u32>;
+
+    fn next(&mut self) -> Option<Vec<u32>> {
+        let total: usize = self.remaining.iter().sum();
+        if total == 0 {
+            return None;
+        }
+        // Each kind with the odds of what is left of it, which shuffles the
+        // exact counts without holding them.
+        let mut roll = self.rng.below(0..total as u64) as usize;
+        let mut kind = 0;
+        while roll >= self.remaining[kind] {
+            roll -= self.remaining[kind];
+            kind += 1;
+        }
+        self.remaining[kind] -= 1;
+        let kind = [Kind::Safe, Kind::DampenerSafe, Kind::Unsafe][kind];
+        let len = self.rng.below(self.levels.start as u64..self.levels.end as u64) as usize;
+
+        Some(generate_report(&mut self.rng, kind, len))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let total = self.remaining.iter().sum();
+        (total, Some(total))
+    }
+}
+
+/// Writes `puzzle` in the input format, one report per line.
+pub fn write_to(puzzle: &Puzzle, writer: &mut impl Write) -> io::Result<()> {
+    for report in &puzzle.reports {
+        writeln!(writer, "{}", report.levels_text())?;
+    }
+    Ok(())
+}
+
+pub(crate) fn generate_report(rng: &mut Rng, kind: Kind, len: usize) -> Vec<u32> {
+    match kind {
+        Kind::Safe => safe_levels(rng, len),
+        Kind::DampenerSafe => {
+            // One level breaking a step next to it: removing it gives back
+            // the safe report.
+            let mut levels = safe_levels(rng, len - 1);
+            let index = rng.below(0..levels.len() as u64 + 1) as usize;
+            let neighbour = levels[index.saturating_sub(1)];
+            let level = if rng.coin() { neighbour } else { neighbour + rng.below(4..20) as u32 };
+            levels.insert(index, level);
+            levels
+        }
+        Kind::Unsafe => {
+            // Two repeated levels far enough apart that no single removal
+            // gets rid of both equal pairs.
+            let mut levels = safe_levels(rng, len - 2);
+            let first = rng.below(0..levels.len() as u64 - 1) as usize;
+            let second = rng.below(first as u64 + 1..levels.len() as u64) as usize;
+            levels.insert(second, levels[second]);
+            levels.insert(first, levels[first]);
+            levels
+        }
+    }
+}
+
+/// One direction, steps of 1 to 3.
+pub(crate) fn safe_levels(rng: &mut Rng, len: usize) -> Vec<u32> {
+    let ascending = rng.coin();
+    // High enough that a descending report never goes below zero.
+    let mut level = rng.below(1..50) as u32 + 3 * len as u32;
+    let mut levels = Vec::with_capacity(len + 2);
+    levels.push(level);
+    for _ in 1..len {
+        let step = rng.below(1..4) as u32;
+        level = if ascending { level + step } else { level - step };
+        levels.push(level);
+    }
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseOptions, ReportStatus, SafetyRules};
+
+    #[test]
+    fn should_generate_reports_of_requested_kinds() {
+        let config = GeneratorConfig {
+            reports: 2000,
+            safe: 0.3,
+            dampener_safe: 0.2,
+            ..GeneratorConfig::default()
+        };
+
+        let statuses = generate_puzzle(&config).statuses(&SafetyRules::default(), 1);
+
+        let count = |status| statuses.iter().filter(|&&other| other == status).count();
+        assert_eq!(count(ReportStatus::Safe), 600);
+        assert_eq!(count(ReportStatus::SafeWithDampener), 400);
+        assert_eq!(count(ReportStatus::Unsafe), 1000);
+    }
+
+    #[test]
+    fn should_generate_reports_within_level_range() {
+        let config = GeneratorConfig {
+            levels: 4..6,
+            ..GeneratorConfig::default()
+        };
+
+        let puzzle = generate_puzzle(&config);
+
+        assert!(puzzle.reports.iter().all(|report| (4..6).contains(&report.values.len())));
+    }
+
+    #[test]
+    fn should_same_seed_reproduce_same_puzzle() {
+        let config = GeneratorConfig {
+            seed: 42,
+            ..GeneratorConfig::default()
+        };
+
+        assert_eq!(generate_puzzle(&config), generate_puzzle(&config));
+        assert_ne!(generate_puzzle(&config), generate_puzzle(&GeneratorConfig::default()));
+    }
+
+    #[test]
+    fn should_generate_reports_stream_same_reports_as_puzzle() {
+        let reports: Vec<Vec<u32>> = generate_reports(&GeneratorConfig::default()).collect();
+
+        assert_eq!(generate_puzzle(&GeneratorConfig::default()), Puzzle::from_reports(reports.into_iter().map(Report::new).collect()));
+    }
+
+    #[test]
+    fn should_write_to_give_back_same_puzzle_when_parsed() {
+        let puzzle = generate_puzzle(&GeneratorConfig::default());
+        let mut text = Vec::new();
+
+        write_to(&puzzle, &mut text).unwrap();
+
+        assert_eq!(Puzzle::from_bytes(&text, ParseOptions::strict()).unwrap().0, puzzle);
+    }
+}
@@ -0,0 +1,212 @@
+//! Counting reports as their lines arrive, for inputs that are never done,
+//! such as a log being tailed.
+
+use crate::{
+    parse::{parse_line, Location},
+    removals::classify_reusing,
+    ParseError, ParseOptions, ParseSummary, ReportStatus, SafetyRules, SafetySummary
+};
+
+/// The status of the report on a line, `None` when the line makes no
+/// report, or why it could not be parsed.
+pub type Verdict = Result<Option<ReportStatus>, ParseError>;
+
+/// Running totals over the lines pushed so far, always equal to what parsing
+/// the same lines as a whole and calling [`Puzzle::summary`](crate::Puzzle::summary)
+/// would give.
+#[derive(PartialEq, Eq, Clone)]
+#[derive(Debug)]
+pub struct IncrementalCounter {
+    rules: SafetyRules,
+    options: ParseOptions,
+    totals: SafetySummary,
+    next_location: Location,
+    /// The bytes pushed after the last line ending.
+    partial_line: Vec<u8>,
+    /// The removal search's, kept across lines.
+    scratch: Vec<usize>
+}
+
+impl IncrementalCounter {
+    /// Parses strictly, so that every bad line is reported.
+    pub fn new(rules: SafetyRules, tolerance: u32) -> IncrementalCounter {
+        IncrementalCounter::with_options(rules, tolerance, ParseOptions::strict())
+    }
+
+    pub fn with_options(rules: SafetyRules, tolerance: u32, options: ParseOptions) -> IncrementalCounter {
+        IncrementalCounter {
+            rules,
+            options,
+            totals: SafetySummary {
+                tolerance,
+                ..SafetySummary::default()
+            },
+            next_location: Location {
+                line: 1,
+                offset: 0
+            },
+            partial_line: Vec::new(),
+            scratch: Vec::new()
+        }
+    }
+
+    /// Parses and classifies one line, without its line ending. Returns
+    /// `None` for lines that do not make a report, such as blank lines.
+    pub fn push_line(&mut self, line: &str) -> Verdict {
+        self.push_line_bytes(line.as_bytes())
+    }
+
+    /// Pushes bytes as they are appended to the input, which may end halfway
+    /// through a line: every line they complete is pushed as by
+    /// [`IncrementalCounter::push_line`], in order, and the rest is kept
+    /// until the next call completes it or [`IncrementalCounter::finish`]
+    /// ends the input. A bad line only fails its own verdict.
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> Vec<Verdict> {
+        let mut verdicts = Vec::new();
+        let mut rest = bytes;
+        while let Some(end) = rest.iter().position(|&byte| byte == b'\n') {
+            let verdict = if self.partial_line.is_empty() {
+                self.push_line_bytes(&rest[..end])
+            } else {
+                let mut line = std::mem::take(&mut self.partial_line);
+                line.extend_from_slice(&rest[..end]);
+                self.push_line_bytes(&line)
+            };
+            verdicts.push(verdict);
+            rest = &rest[end + 1..];
+        }
+        self.partial_line.extend_from_slice(rest);
+        verdicts
+    }
+
+    /// Pushes the last line of an input not ending with a line ending, once
+    /// no more bytes will come. `Ok(None)` when there is none.
+    pub fn finish(&mut self) -> Verdict {
+        if self.partial_line.is_empty() {
+            return Ok(None);
+        }
+        let line = std::mem::take(&mut self.partial_line);
+        self.push_line_bytes(&line)
+    }
+
+    fn push_line_bytes(&mut self, line: &[u8]) -> Verdict {
+        let location = self.next_location;
+        self.next_location = Location {
+            line: location.line + 1,
+            offset: location.offset + line.len() + 1
+        };
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let Some(levels) = parse_line::<u32>(line, location, &self.options, &mut ParseSummary::default())? else {
+            return Ok(None);
+        };
+        let status = classify_reusing(&levels, &self.rules, self.totals.tolerance, &mut self.scratch);
+        self.totals.total += 1;
+        match status {
+            ReportStatus::Safe => {
+                self.totals.part1 += 1;
+                self.totals.part2 += 1;
+            }
+            ReportStatus::SafeWithDampener => self.totals.part2 += 1,
+            ReportStatus::Unsafe => {}
+        }
+        Ok(Some(status))
+    }
+
+    /// `part1` counts the reports safe as-is, `part2` those safe once up to
+    /// `tolerance` levels may be removed.
+    pub fn totals(&self) -> SafetySummary {
+        self.totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+    use crate::Puzzle;
+
+    #[test]
+    fn should_totals_match_batch_summary_line_by_line() {
+        let input = read_to_string("tests/resources/puzzle.txt").unwrap();
+        let (puzzle, _) = Puzzle::parse(&input, ParseOptions::strict()).unwrap();
+        let mut counter = IncrementalCounter::new(SafetyRules::default(), 1);
+
+        let statuses: Vec<ReportStatus> = input.lines().filter_map(|line| counter.push_line(line).unwrap()).collect();
+
+        assert_eq!(counter.totals(), puzzle.summary(&SafetyRules::default(), 1));
+        assert_eq!(statuses, puzzle.statuses(&SafetyRules::default(), 1));
+    }
+
+    #[test]
+    fn should_skip_lines_like_batch_parsing_when_lenient() {
+        let input = read_to_string("tests/resources/puzzle_trailing_blank_lines.txt").unwrap()
+            + &read_to_string("tests/resources/puzzle_overflow.txt").unwrap();
+        let (puzzle, _) = Puzzle::parse(&input, ParseOptions::lenient()).unwrap();
+        let mut counter = IncrementalCounter::with_options(SafetyRules::default(), 0, ParseOptions::lenient());
+
+        input.lines().for_each(|line| {
+            counter.push_line(line).unwrap();
+        });
+
+        assert_eq!(counter.totals(), puzzle.summary(&SafetyRules::default(), 0));
+    }
+
+    #[test]
+    fn should_push_bytes_count_lines_split_across_chunks() {
+        let input = read_to_string("tests/resources/puzzle.txt").unwrap();
+        let (puzzle, _) = Puzzle::parse(&input, ParseOptions::strict()).unwrap();
+        for chunk_size in [1, 3, 7, input.len()] {
+            let mut counter = IncrementalCounter::new(SafetyRules::default(), 1);
+
+            let mut statuses: Vec<ReportStatus> = input
+            .as_bytes()
+            .chunks(chunk_size)
+            .flat_map(|chunk| counter.push_bytes(chunk))
+            .filter_map(|verdict| verdict.unwrap())
+            .collect();
+            statuses.extend(counter.finish().unwrap());
+
+            assert_eq!(statuses, puzzle.statuses(&SafetyRules::default(), 1), "chunks of {chunk_size}");
+            assert_eq!(counter.totals(), puzzle.summary(&SafetyRules::default(), 1), "chunks of {chunk_size}");
+        }
+    }
+
+    #[test]
+    fn should_push_bytes_fail_only_bad_line() {
+        let mut counter = IncrementalCounter::new(SafetyRules::default(), 0);
+
+        let verdicts = counter.push_bytes(b"1 2 3\r\n1 x\n7 6 4");
+
+        assert_eq!(verdicts, vec![Ok(Some(ReportStatus::Safe)), Err(ParseError::InvalidToken {
+            line: 2,
+            token: "x".to_string()
+        })]);
+        assert_eq!(counter.totals().total, 1);
+        assert_eq!(counter.push_bytes(b" 2 1\n"), vec![Ok(Some(ReportStatus::Safe))]);
+        assert_eq!(counter.finish(), Ok(None));
+        assert_eq!(counter.totals().part1, 2);
+    }
+
+    #[test]
+    fn should_count_long_reports_at_large_tolerance() {
+        let mut counter = IncrementalCounter::new(SafetyRules::default(), 20);
+        let levels: Vec<String> = (0..200).map(|index| if index % 10 == 0 { 999 } else { index }).map(|level| level.to_string()).collect();
+
+        assert_eq!(counter.push_line(&levels.join(" ")), Ok(Some(ReportStatus::SafeWithDampener)));
+        assert_eq!(counter.totals().part2, 1);
+    }
+
+    #[test]
+    fn should_push_line_fail_with_line_number_when_strict() {
+        let mut counter = IncrementalCounter::new(SafetyRules::default(), 1);
+
+        assert_eq!(counter.push_line("7 6 4 2 1"), Ok(Some(ReportStatus::Safe)));
+        assert_eq!(counter.push_line(""), Ok(None));
+        assert_eq!(counter.push_line("1 x 2"), Err(ParseError::InvalidToken {
+            line: 3,
+            token: "x".to_string()
+        }));
+        assert_eq!(counter.totals().total, 1);
+    }
+}
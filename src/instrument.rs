@@ -0,0 +1,228 @@
+//! Spans and events describing what the library does, for embedders to
+//! forward to their own logging or tracing setup, such as a `tracing`
+//! subscriber through its macros of the same level. Install a
+//! [`Subscriber`] for the current thread with [`with_subscriber`].
+//!
+//! Spans are reported once they end, with every field known by then: the
+//! parsing of a file with its duration, and counts, at [`Severity::Info`].
+//! Events are notes along the way: lines lenient parsing skipped at
+//! [`Severity::Warn`], and the status of every report counted at
+//! [`Severity::Trace`], only built when [`Subscriber::enabled`] wants them.
+//! Without the `tracing` feature the hooks compile to nothing.
+
+use std::fmt::Display;
+#[cfg(feature = "tracing")]
+use std::{cell::RefCell, rc::Rc};
+
+/// The fields of a span or an event, as `(name, value)` pairs.
+pub(crate) type Fields<'a> = &'a [(&'static str, &'a dyn Display)];
+
+/// How much a record matters, from the most to the least verbose.
+#[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(Debug)]
+pub enum Severity {
+    Trace,
+    Debug,
+    Info,
+    Warn
+}
+
+#[cfg(feature = "tracing")]
+#[derive(PartialEq, Eq, Clone)]
+#[derive(Debug)]
+pub struct Record {
+    pub name: &'static str,
+    pub severity: Severity,
+    pub fields: Vec<(&'static str, String)>
+}
+
+#[cfg(feature = "tracing")]
+impl Record {
+    /// The value of the field called `name`, if the record has one.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+        .iter()
+        .find(|(field, _)| *field == name)
+        .map(|(_, value)| value.as_str())
+    }
+}
+
+#[cfg(feature = "tracing")]
+pub trait Subscriber {
+    /// Whether records of `severity` are wanted at all. Those that are not
+    /// are never built, which keeps per-report events cheap when unwanted.
+    fn enabled(&self, severity: Severity) -> bool {
+        let _ = severity;
+        true
+    }
+
+    fn span(&self, record: &Record);
+
+    fn event(&self, record: &Record);
+}
+
+#[cfg(feature = "tracing")]
+thread_local! {
+    static SUBSCRIBER: RefCell<Option<Rc<dyn Subscriber>>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with `subscriber` receiving the spans and events of the current
+/// thread, restoring the previous subscriber afterwards.
+#[cfg(feature = "tracing")]
+pub fn with_subscriber<R>(subscriber: Rc<dyn Subscriber>, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Rc<dyn Subscriber>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SUBSCRIBER.with(|current| *current.borrow_mut() = self.0.take());
+        }
+    }
+
+    let _restore = Restore(SUBSCRIBER.with(|current| current.borrow_mut().replace(subscriber)));
+    f()
+}
+
+#[cfg(feature = "tracing")]
+fn dispatch(name: &'static str, severity: Severity, fields: Fields, deliver: impl FnOnce(&dyn Subscriber, &Record)) {
+    // Cloned out so a subscriber may itself call into the library.
+    let subscriber = SUBSCRIBER.with(|current| current.borrow().clone());
+    if let Some(subscriber) = subscriber.filter(|subscriber| subscriber.enabled(severity)) {
+        let record = Record {
+            name,
+            severity,
+            fields: fields.iter().map(|(field, value)| (*field, value.to_string())).collect()
+        };
+        deliver(&*subscriber, &record);
+    }
+}
+
+/// Whether records of `severity` would reach a subscriber, to skip the work
+/// of computing their fields otherwise.
+#[cfg(feature = "tracing")]
+pub(crate) fn enabled(severity: Severity) -> bool {
+    SUBSCRIBER.with(|current| current.borrow().as_ref().is_some_and(|subscriber| subscriber.enabled(severity)))
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn span(name: &'static str, fields: Fields) {
+    dispatch(name, Severity::Info, fields, |subscriber, record| subscriber.span(record));
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn event(severity: Severity, name: &'static str, fields: Fields) {
+    dispatch(name, severity, fields, |subscriber, record| subscriber.event(record));
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn enabled(_severity: Severity) -> bool {
+    false
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn span(_name: &'static str, _fields: Fields) {}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn event(_severity: Severity, _name: &'static str, _fields: Fields) {}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::{ParseOptions, Puzzle, SafetyRules};
+
+    #[derive(Default)]
+    struct Capture {
+        spans: RefCell<Vec<Record>>,
+        events: RefCell<Vec<Record>>,
+        /// Records less severe are not wanted. Everything when `None`.
+        min_severity: Option<Severity>
+    }
+
+    impl Subscriber for Capture {
+        fn enabled(&self, severity: Severity) -> bool {
+            self.min_severity.is_none_or(|min_severity| severity >= min_severity)
+        }
+
+        fn span(&self, record: &Record) {
+            self.spans.borrow_mut().push(record.clone());
+        }
+
+        fn event(&self, record: &Record) {
+            self.events.borrow_mut().push(record.clone());
+        }
+    }
+
+    #[test]
+    fn should_report_parse_and_count_spans_with_fields() {
+        let capture = Rc::new(Capture::default());
+
+        with_subscriber(capture.clone(), || {
+            let (puzzle, _) = Puzzle::from_path("tests/resources/puzzle_overflow.txt", ParseOptions::lenient()).unwrap();
+            puzzle.count_safe(&SafetyRules::default(), 1);
+        });
+
+        let spans = capture.spans.borrow();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].name, "extract_puzzle");
+        assert_eq!(spans[0].field("path"), Some("tests/resources/puzzle_overflow.txt"));
+        assert_eq!(spans[0].field("bytes"), Some("72"));
+        assert_eq!(spans[0].field("reports"), Some("5"));
+        assert!(spans[0].field("duration_us").is_some_and(|duration| duration.parse::<u128>().is_ok()));
+        assert_eq!(spans[1].name, "count_safe");
+        assert_eq!(spans[1].severity, Severity::Info);
+        assert_eq!(spans[1].field("tolerance"), Some("1"));
+        assert_eq!(spans[1].field("result"), Some("3"));
+    }
+
+    #[test]
+    fn should_report_skipped_lines_as_events_in_lenient_mode() {
+        let capture = Rc::new(Capture::default());
+
+        with_subscriber(capture.clone(), || {
+            Puzzle::parse("1 2\n1 99999999999 3\n\u{7f} 5", ParseOptions::lenient()).unwrap();
+        });
+
+        let events = capture.events.borrow();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].severity, Severity::Warn);
+        assert_eq!(events[0].name, "skipped line");
+        assert_eq!(events[0].field("line"), Some("2"));
+        assert_eq!(events[0].field("reason"), Some("overflow"));
+        assert_eq!(events[1].name, "skipped token");
+        assert_eq!(events[1].field("line"), Some("3"));
+        assert_eq!(events[1].field("offset"), Some("20"));
+    }
+
+    #[test]
+    fn should_report_each_counted_report_at_trace_only_when_enabled() {
+        let (puzzle, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::lenient()).unwrap();
+        let tracing = Rc::new(Capture::default());
+        let informed = Rc::new(Capture {
+            min_severity: Some(Severity::Info),
+            ..Capture::default()
+        });
+
+        with_subscriber(tracing.clone(), || Puzzle::from_reports(puzzle.reports.clone()).count_safe(&SafetyRules::default(), 1));
+        with_subscriber(informed.clone(), || Puzzle::from_reports(puzzle.reports.clone()).count_safe(&SafetyRules::default(), 1));
+
+        let events = tracing.events.borrow();
+        assert_eq!(events.len(), 6);
+        assert!(events.iter().all(|event| event.name == "classified report" && event.severity == Severity::Trace));
+        assert_eq!(events[3].field("index"), Some("3"));
+        assert_eq!(events[3].field("status"), Some("safe_with_dampener"));
+        assert!(informed.events.borrow().is_empty());
+        assert_eq!(informed.spans.borrow().len(), 1);
+    }
+
+    #[test]
+    fn should_not_report_anything_once_subscriber_is_restored() {
+        let capture = Rc::new(Capture::default());
+
+        with_subscriber(capture.clone(), || {});
+        Puzzle::parse("1 99999999999 3", ParseOptions::lenient()).unwrap();
+
+        assert!(capture.events.borrow().is_empty());
+    }
+}
@@ -0,0 +1,63 @@
+//! Just enough JSON writing for the result types, keeping the crate free of
+//! dependencies.
+
+use std::fmt::{Display, Write};
+
+pub(crate) fn string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for character in value.chars() {
+        match character {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            character if character.is_control() => {
+                let _ = write!(json, "\\u{:04x}", character as u32);
+            }
+            character => json.push(character)
+        }
+    }
+    json.push('"');
+    json
+}
+
+pub(crate) fn option<T: Display>(value: Option<T>) -> String {
+    value.map_or("null".to_string(), |value| value.to_string())
+}
+
+pub(crate) fn array(items: impl IntoIterator<Item = String>) -> String {
+    format!("[{}]", items.into_iter().collect::<Vec<_>>().join(","))
+}
+
+/// Writes `fields` as an object, in the given order.
+pub(crate) fn object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields
+    .iter()
+    .map(|(name, value)| format!("{}:{value}", string(name)))
+    .collect();
+
+    format!("{{{}}}", fields.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_string_escape_quotes_and_control_characters() {
+        assert_eq!(string("a \"b\"\\\n\u{1}"), r#""a \"b\"\\\n\u0001""#);
+    }
+
+    #[test]
+    fn should_object_keep_field_order() {
+        assert_eq!(object(&[("b", "1".to_string()), ("a", option::<u32>(None))]), r#"{"b":1,"a":null}"#);
+    }
+
+    #[test]
+    fn should_array_join_items() {
+        assert_eq!(array(vec!["1".to_string(), "2".to_string()]), "[1,2]");
+        assert_eq!(array(Vec::new()), "[]");
+    }
+}
@@ -0,0 +1,48 @@
+//! The numbers a report can be made of.
+
+use core::{num::ParseIntError, str::FromStr};
+
+/// A level of a report. Steps between levels are measured as `u32`, those
+/// too large to fit counting as `u32::MAX`: they break any rules with a
+/// smaller `max_step`, and pass rules whose `max_step` is `u32::MAX` like a
+/// step of exactly `u32::MAX` would. The default level only fills unused
+/// slots of an [`ArrayReport`](crate::ArrayReport).
+pub trait Level: Copy + Ord + Default + FromStr<Err = ParseIntError> {
+    /// Whether levels may be negative, so that a `-` may start one.
+    const SIGNED: bool;
+
+    /// How far apart `self` and `other` are, whichever is larger.
+    fn distance(self, other: Self) -> u32;
+}
+
+impl Level for u32 {
+    const SIGNED: bool = false;
+
+    fn distance(self, other: u32) -> u32 {
+        self.abs_diff(other)
+    }
+}
+
+impl Level for i32 {
+    const SIGNED: bool = true;
+
+    fn distance(self, other: i32) -> u32 {
+        self.abs_diff(other)
+    }
+}
+
+impl Level for u64 {
+    const SIGNED: bool = false;
+
+    fn distance(self, other: u64) -> u32 {
+        u32::try_from(self.abs_diff(other)).unwrap_or(u32::MAX)
+    }
+}
+
+impl Level for i64 {
+    const SIGNED: bool = true;
+
+    fn distance(self, other: i64) -> u32 {
+        u32::try_from(self.abs_diff(other)).unwrap_or(u32::MAX)
+    }
+}
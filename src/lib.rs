@@ -1,194 +1,316 @@
-use std::{fs::read_to_string, usize};
+#![cfg_attr(not(feature = "std"), no_std)]
 
-pub fn find_safe_reports(input_path: &str) -> u32 {
-    let puzzle = extract_puzzle(input_path);
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
-    safe_reports(&puzzle)
+#[cfg(feature = "std")]
+mod answer_cache;
+mod array_report;
+#[cfg(feature = "std")]
+mod batch;
+#[cfg(feature = "std")]
+mod breakdown;
+#[cfg(feature = "std")]
+pub mod cli;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "std")]
+mod ext;
+#[cfg(feature = "std")]
+mod explain;
+#[cfg(feature = "fetch")]
+pub mod fetch;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+mod fingerprint;
+#[cfg(feature = "std")]
+pub mod generate;
+#[cfg(feature = "std")]
+mod incremental;
+#[cfg(feature = "std")]
+mod instrument;
+#[cfg(feature = "std")]
+mod json;
+mod level;
+#[cfg(feature = "std")]
+mod markdown;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "std")]
+mod parse;
+mod policy;
+#[cfg(feature = "std")]
+mod puzzle;
+#[cfg(all(feature = "std", any(test, feature = "reference")))]
+pub mod reference;
+#[cfg(feature = "std")]
+mod reload;
+mod removals;
+#[cfg(feature = "std")]
+pub mod repl;
+#[cfg(feature = "alloc")]
+mod report;
+#[cfg(feature = "std")]
+mod rng;
+#[cfg(feature = "alloc")]
+mod rule_set;
+mod rules;
+mod safety;
+#[cfg(feature = "std")]
+mod sample;
+#[cfg(feature = "std")]
+mod sections;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "std")]
+mod solver;
+#[cfg(feature = "strategies")]
+pub mod strategy;
+#[cfg(feature = "std")]
+mod sparkline;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "std")]
+mod summary;
+#[cfg(feature = "std")]
+mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "std")]
+pub mod watch;
+
+#[cfg(feature = "std")]
+use std::{fs::File, io::BufReader, path::Path};
+
+#[cfg(feature = "std")]
+pub use answer_cache::find_safe_reports_cached;
+pub use array_report::{ArrayReport, CapacityError};
+#[cfg(feature = "std")]
+pub use batch::{find_safe_reports_batch, puzzle_files, BatchResult, FileResult};
+#[cfg(feature = "std")]
+pub use breakdown::{DirectionBreakdown, DirectionGroup};
+#[cfg(feature = "std")]
+pub use diff::{compare_puzzles, PuzzleDiff, StatusChange};
+#[cfg(feature = "std")]
+pub use error::Day02Error;
+#[cfg(feature = "std")]
+pub use ext::SafeReportsExt;
+#[cfg(feature = "std")]
+pub use incremental::{IncrementalCounter, Verdict};
+#[cfg(feature = "tracing")]
+pub use instrument::{with_subscriber, Record, Severity, Subscriber};
+pub use level::Level;
+#[cfg(feature = "std")]
+pub use markdown::MarkdownOptions;
+#[cfg(feature = "std")]
+pub use parse::{Delimiter, ParseError, ParseOptions, ParseSummary};
+pub use policy::SafetyPolicy;
+#[cfg(feature = "std")]
+pub use puzzle::Puzzle;
+#[cfg(feature = "std")]
+pub use reload::ReloadStats;
+#[cfg(feature = "alloc")]
+pub use removals::min_removals_to_safe;
+#[cfg(feature = "alloc")]
+pub use report::Report;
+#[cfg(feature = "alloc")]
+pub use rule_set::{MinLevels, Monotonic, RuleOutcome, RuleSet, SafetyRule, StepRange};
+pub use rules::SafetyRules;
+pub use safety::{
+    classify_slice, direction_of, first_violation, is_safe_iter, is_safe_iter_trimming_edges, is_safe_slice,
+    is_safe_slice_with_tolerance, Direction, ReportStatus, Violation
+};
+#[cfg(feature = "std")]
+pub use sample::{Estimate, EstimatedCount, SampledCounter};
+#[cfg(feature = "std")]
+pub use sections::{find_safe_reports_per_section, parse_sections};
+#[cfg(feature = "std")]
+pub use solver::{run_solver, Day02, Solution, Solver, Timed};
+#[cfg(feature = "std")]
+pub use sparkline::sparkline;
+#[cfg(feature = "std")]
+pub use stats::{FailureReasons, SafetyStats};
+#[cfg(feature = "std")]
+pub use stream::{count_safe_from_reader, count_safe_from_reader_with_progress, Progress, ProgressSink};
+#[cfg(feature = "std")]
+pub use summary::{DampenerFix, SafetyReport, SafetySummary};
+#[cfg(feature = "std")]
+pub use verify::{verify, VerifyError};
+
+/// The reports of the puzzle at `input_path` that are safe once the Problem
+/// Dampener may remove up to `tolerance` levels from each: 0 for part 1, 1
+/// for part 2.
+///
+/// # Panics
+///
+/// If the file cannot be read; see [`try_find_safe_reports`].
+#[cfg(feature = "std")]
+pub fn find_safe_reports(input_path: &str, tolerance: u32) -> usize {
+    safe_reports(&extract_puzzle(input_path), tolerance)
+}
+
+/// Like [`find_safe_reports`], under `rules` rather than the puzzle's, for
+/// variants of it.
+///
+/// # Panics
+///
+/// If the file cannot be read.
+#[cfg(feature = "std")]
+pub fn find_safe_reports_with_rules(input_path: &str, rules: &SafetyRules, tolerance: u32) -> usize {
+    extract_puzzle(input_path).count_safe(rules, tolerance)
+}
+
+/// Like [`find_safe_reports`], failing instead of panicking when the file
+/// cannot be read. Unlike [`find_safe_reports`], it also fails on the first
+/// line that does not parse strictly, with [`Day02Error::Parse`] telling
+/// which.
+#[cfg(feature = "std")]
+pub fn try_find_safe_reports(input_path: impl AsRef<Path>, tolerance: u32) -> Result<usize, Day02Error> {
+    let reader = BufReader::new(File::open(input_path)?);
+    let counted = count_safe_from_reader_with_progress(reader, &ParseOptions::strict(), &SafetyRules::default(), tolerance, 0, &mut |_: &Progress| {})?;
+
+    Ok(counted.safe)
+}
+
+/// Like [`find_safe_reports`], classifying the reports on `threads` threads,
+/// or as many as the machine can run at once when 0.
+///
+/// # Panics
+///
+/// If the file cannot be read.
+#[cfg(feature = "parallel")]
+pub fn find_safe_reports_parallel(input_path: &str, tolerance: u32, threads: usize) -> usize {
+    extract_puzzle(input_path).count_safe_parallel(&SafetyRules::default(), tolerance, threads)
 }
 
+#[cfg(feature = "std")]
 fn extract_puzzle(input_path: &str) -> Puzzle {
-    let mut puzzle = Puzzle::new();
-    read_to_string(input_path)
-    .unwrap()
-    .lines()
-    .for_each(|line| puzzle.add_report(line));
+    let (puzzle, _) = Puzzle::from_reader(BufReader::new(File::open(input_path).unwrap()), ParseOptions::lenient()).unwrap();
 
     puzzle
 }
 
-fn safe_reports(puzzle: &Puzzle) -> u32 {
+#[cfg(feature = "std")]
+fn safe_reports(puzzle: &Puzzle, tolerance: u32) -> usize {
     if puzzle.reports.is_empty() {
         return 0;
     }
     let mut safe_count = 0;
     puzzle.reports.iter().for_each(|report| {
-        if report.is_safe() {
+        if report.is_safe(tolerance) {
             safe_count += 1;
         }
     });
     safe_count
 }
 
-#[derive(PartialEq)]
-#[derive(Debug)]
-struct Puzzle {
-    reports: Vec<Report>
-}
-
-#[derive(PartialEq)]
-#[derive(Debug)]
-struct Report {
-    values: Vec<u32>
-}
-
-impl Report {
-    fn is_safe_at_index(&self, index: usize) -> bool {
-        if index == self.values.len() - 1 {
-            return true;
-        }
-        let value = self.values[index];
-        let next_value = self.values[index + 1];
-
-        value.abs_diff(next_value) > 0 && value.abs_diff(next_value) < 4
-    }
-
-    fn is_wright_order(&self, index: usize) -> bool {
-        if index == self.values.len() - 1 {
-            return true;
-        }
-        let value = self.values[index];
-        let next_value = self.values[index + 1];
-
-        if self.is_ascending() {
-            return next_value > value;
-        }
-        next_value < value
-    }
-
-    fn is_ascending(&self) -> bool {
-        self.values.len() >= 2 && self.values[0] < self.values[1]
-    }
-
-    fn is_safe(&self) -> bool {
-        for i in 0..self.values.len()  {
-            if !self.is_safe_at_index(i) || !self.is_wright_order(i) {
-                return false;
-            }
-        }
-        true
-    }
-}
-
-impl Puzzle {
-    fn new() -> Puzzle {
-        Puzzle {
-            reports: Vec::new()
-        }
-    }
-
-    fn add_report(&mut self, line: &str) {
-        let numbers: Vec<u32> = line
-        .split_whitespace() 
-        .filter_map(|s| s.parse::<u32>().ok())
-        .collect();
-
-        self.reports.push(Report {
-            values: numbers
-        });
-    }
-}
-
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
+    use report::Report;
 
     #[test]
     fn should_safe_reports_0_when_safe_distance_but_not_always_increasing() {
-        assert_eq!(safe_reports(&Puzzle {
-            reports: vec![Report {
-                values: vec![1, 3, 1]
-            }]
-        }), 0);
+        assert_eq!(safe_reports(&Puzzle::from_reports(vec![Report {
+            values: vec![1, 3, 1]
+        }]), 0), 0);
     }
 
     #[test]
     fn should_safe_reports_2_when_two_reports_safe() {
-        assert_eq!(safe_reports(&Puzzle {
-            reports: vec![Report {
-                values: vec![1, 3]
-            }, Report {
-                values: vec![1, 2]
-            }]
-        }), 2);
+        assert_eq!(safe_reports(&Puzzle::from_reports(vec![Report {
+            values: vec![1, 3]
+        }, Report {
+            values: vec![1, 2]
+        }]), 0), 2);
     }
    
     #[test]
     fn should_safe_reports_1_when_one_reports_is_containing_1_5() {
-        assert_eq!(safe_reports(&Puzzle {
-            reports: vec![Report {
-                values: vec![1, 5]
-            }]
-        }), 0);
+        assert_eq!(safe_reports(&Puzzle::from_reports(vec![Report {
+            values: vec![1, 5]
+        }]), 0), 0);
     }
 
     #[test]
     fn should_safe_reports_1_when_one_reports_is_containing_1_1() {
-        assert_eq!(safe_reports(&Puzzle {
-            reports: vec![Report {
-                values: vec![1, 1]
-            }]
-        }), 0);
+        assert_eq!(safe_reports(&Puzzle::from_reports(vec![Report {
+            values: vec![1, 1]
+        }]), 0), 0);
     }
 
 
     #[test]
     fn should_safe_reports_1_when_one_reports_is_containing_1_2() {
-        assert_eq!(safe_reports(&Puzzle {
-            reports: vec![Report {
-                values: vec![1, 2]
-            }]
-        }), 1);
+        assert_eq!(safe_reports(&Puzzle::from_reports(vec![Report {
+            values: vec![1, 2]
+        }]), 0), 1);
     }
 
     #[test]
     fn should_safe_reports_0_when_empty_reports() {
-        assert_eq!(safe_reports(&Puzzle {
-            reports: vec![]
-        }), 0);
+        assert_eq!(safe_reports(&Puzzle::from_reports(vec![]), 0), 0);
+    }
+
+    #[test]
+    fn should_safe_reports_count_dampened_reports_when_tolerance_1() {
+        assert_eq!(safe_reports(&Puzzle::from_reports(vec![Report {
+            values: vec![1, 3, 2, 4, 5]
+        }, Report {
+            values: vec![1, 9, 2, 8, 3]
+        }]), 1), 1);
+    }
+
+    #[test]
+    fn should_try_find_safe_reports_fail_on_missing_file() {
+        assert_eq!(try_find_safe_reports("tests/resources/puzzle.txt", 1).unwrap(), 4);
+        assert!(matches!(try_find_safe_reports("tests/resources/missing.txt", 0), Err(Day02Error::Io(_))));
+    }
+
+    #[test]
+    fn should_try_find_safe_reports_fail_on_garbage_line() {
+        let error = try_find_safe_reports("tests/resources/puzzle_three_errors.txt", 1).unwrap_err();
+
+        assert!(matches!(error, Day02Error::Parse(ParseError::InvalidToken { line: 2, .. })), "{error:?}");
     }
 
     #[test]
     fn should_extract_puzzle() {
         assert_eq!(extract_puzzle("tests/resources/puzzle.txt"), 
-        Puzzle {
-            reports: vec![Report {
-                values: vec![7, 6, 4, 2, 1]
-            },
-            Report {
-                values: vec![1, 2, 7, 8, 9]
-            },
-            Report {
-                values: vec![9, 7, 6, 2, 1]
-            },
-            Report {
-                values: vec![1, 3, 2, 4, 5]
-            },
-            Report {
-                values: vec![8, 6, 4, 4, 1]
-            },
-            Report {
-                values: vec![1, 3, 6, 7, 9]
-            }]
-        })
+        Puzzle::from_reports(vec![Report {
+            values: vec![7, 6, 4, 2, 1]
+        },
+        Report {
+            values: vec![1, 2, 7, 8, 9]
+        },
+        Report {
+            values: vec![9, 7, 6, 2, 1]
+        },
+        Report {
+            values: vec![1, 3, 2, 4, 5]
+        },
+        Report {
+            values: vec![8, 6, 4, 4, 1]
+        },
+        Report {
+            values: vec![1, 3, 6, 7, 9]
+        }]))
         
     }
 
     #[test]
-    fn should_report_is_safe_return_true_when_one_element() {
-        let report = Report {
-            values: vec![1]
-        };
-        assert_eq!(report.is_safe_at_index(0), true)
+    fn should_extract_puzzle_ignore_trailing_blank_lines() {
+        assert_eq!(extract_puzzle("tests/resources/puzzle_trailing_blank_lines.txt"),
+        extract_puzzle("tests/resources/puzzle.txt"));
     }
 
     
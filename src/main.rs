@@ -1,7 +1,17 @@
-use day_02::find_safe_reports;
+use std::{env, io, process::ExitCode};
 
-fn main() {
-    let safe_reports = find_safe_reports("src/resources/puzzle.txt");
+use day_02::cli::{run, Args, CliError};
 
-    println!("Total safe reports: {safe_reports}")
+fn main() -> ExitCode {
+    let result = Args::parse(env::args().skip(1))
+    .map_err(CliError::from)
+    .and_then(|args| run(&args, &mut io::stdout()));
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("day_02: {error}");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,94 @@
+//! Results as GitHub-flavoured Markdown.
+
+use std::fmt::Write;
+
+use crate::{puzzle::violation_text, Puzzle, ReportStatus, SafetyRules};
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub struct MarkdownOptions {
+    /// How many unsafe reports to list, or `None` to leave the table out.
+    /// Those past the limit are only counted.
+    pub unsafe_rows: Option<usize>
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> MarkdownOptions {
+        MarkdownOptions {
+            unsafe_rows: Some(20)
+        }
+    }
+}
+
+pub(crate) fn render(puzzle: &Puzzle, options: &MarkdownOptions) -> String {
+    let rules = SafetyRules::default();
+    let summary = puzzle.summary(&rules, 1);
+    let mut markdown = String::new();
+    // Writing to a String cannot fail.
+    let _ = writeln!(markdown, "| | Reports |");
+    let _ = writeln!(markdown, "|---|---:|");
+    let _ = writeln!(markdown, "| Total | {} |", summary.total);
+    let _ = writeln!(markdown, "| Safe (part 1) | {} |", summary.part1);
+    let _ = writeln!(markdown, "| Safe with dampener (part 2) | {} |", summary.part2);
+    let _ = writeln!(markdown, "| Dampener-only | {} |", summary.part2 - summary.part1);
+
+    let Some(rows) = options.unsafe_rows else {
+        return markdown;
+    };
+    let unsafe_reports: Vec<_> = puzzle.reports
+    .iter()
+    .enumerate()
+    .filter(|(_, report)| report.classify(&rules, 1) == ReportStatus::Unsafe)
+    .collect();
+    if unsafe_reports.is_empty() {
+        return markdown;
+    }
+    let _ = writeln!(markdown);
+    let _ = writeln!(markdown, "| Line | Levels | Reason |");
+    let _ = writeln!(markdown, "|---:|---|---|");
+    for (index, report) in unsafe_reports.iter().take(rows) {
+        let reason = report.violation(&rules).map_or_else(String::new, violation_text);
+        // In a code span the levels are rendered as they are.
+        let _ = writeln!(markdown, "| {} | `{}` | {reason} |", index + 1, report.levels_text());
+    }
+    if unsafe_reports.len() > rows {
+        let _ = writeln!(markdown);
+        let _ = writeln!(markdown, "…and {} more", unsafe_reports.len() - rows);
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+    use crate::ParseOptions;
+
+    fn sample() -> Puzzle {
+        Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::strict()).unwrap().0
+    }
+
+    #[test]
+    fn should_to_markdown_match_golden_file() {
+        assert_eq!(sample().to_markdown(&MarkdownOptions::default()), read_to_string("tests/resources/puzzle_markdown.md").unwrap());
+    }
+
+    #[test]
+    fn should_to_markdown_count_rows_past_limit() {
+        let markdown = sample().to_markdown(&MarkdownOptions {
+            unsafe_rows: Some(1)
+        });
+
+        assert!(markdown.ends_with("| 2 | `1 2 7 8 9` | diff 5 at pair 2 |\n\n…and 1 more\n"), "{markdown}");
+    }
+
+    #[test]
+    fn should_to_markdown_leave_out_unsafe_table() {
+        let markdown = sample().to_markdown(&MarkdownOptions {
+            unsafe_rows: None
+        });
+
+        assert!(markdown.ends_with("| Dampener-only | 2 |\n"), "{markdown}");
+    }
+}
@@ -0,0 +1,129 @@
+//! Read-only memory maps of puzzle files, so that parsing reads the page
+//! cache directly instead of a copy of the whole file.
+//!
+//! Only 64-bit Unix maps files; elsewhere [`map`] reads the file into memory
+//! as [`Puzzle::from_path`](crate::Puzzle::from_path) does.
+
+use std::{fs::File, io, ops::Deref};
+
+#[cfg(all(unix, target_pointer_width = "64"))]
+mod sys {
+    use std::{
+        ffi::{c_int, c_void},
+        fs::File,
+        io,
+        os::fd::AsRawFd,
+        ptr, slice
+    };
+
+    const PROT_READ: c_int = 1;
+    const MAP_PRIVATE: c_int = 2;
+
+    extern "C" {
+        fn mmap(addr: *mut c_void, len: usize, prot: c_int, flags: c_int, fd: c_int, offset: i64) -> *mut c_void;
+        fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    }
+
+    pub(super) struct Mapping {
+        addr: *mut c_void,
+        len: usize
+    }
+
+    impl Mapping {
+        pub(super) fn new(file: &File, len: usize) -> io::Result<Mapping> {
+            // SAFETY: a fresh private read-only mapping of an open file
+            // aliases no Rust memory.
+            let addr = unsafe { mmap(ptr::null_mut(), len, PROT_READ, MAP_PRIVATE, file.as_raw_fd(), 0) };
+            // MAP_FAILED
+            if addr as usize == usize::MAX {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Mapping {
+                addr,
+                len
+            })
+        }
+
+        pub(super) fn bytes(&self) -> &[u8] {
+            // SAFETY: the mapping is `len` readable bytes until dropped.
+            unsafe { slice::from_raw_parts(self.addr.cast(), self.len) }
+        }
+    }
+
+    impl Drop for Mapping {
+        fn drop(&mut self) {
+            // SAFETY: `addr` and `len` are those of a live mapping, dropped
+            // once.
+            unsafe { munmap(self.addr, self.len) };
+        }
+    }
+}
+
+enum Backing {
+    #[cfg(all(unix, target_pointer_width = "64"))]
+    Mapped(sys::Mapping),
+    Read(Vec<u8>)
+}
+
+/// The bytes of a file, mapped or read.
+pub(crate) struct Map(Backing);
+
+impl Deref for Map {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.0 {
+            #[cfg(all(unix, target_pointer_width = "64"))]
+            Backing::Mapped(mapping) => mapping.bytes(),
+            Backing::Read(bytes) => bytes
+        }
+    }
+}
+
+/// Maps `file` read-only. Empty files, which cannot be mapped, give no bytes.
+///
+/// The file must not be truncated while mapped: reading a page past its new
+/// end kills the process with `SIGBUS`.
+pub(crate) fn map(file: &File) -> io::Result<Map> {
+    let len = usize::try_from(file.metadata()?.len()).map_err(|_| io::Error::new(io::ErrorKind::OutOfMemory, "file larger than the address space"))?;
+    if len == 0 {
+        return Ok(Map(Backing::Read(Vec::new())));
+    }
+    #[cfg(all(unix, target_pointer_width = "64"))]
+    {
+        sys::Mapping::new(file, len).map(|mapping| Map(Backing::Mapped(mapping)))
+    }
+    #[cfg(not(all(unix, target_pointer_width = "64")))]
+    {
+        use std::io::Read;
+
+        let mut bytes = Vec::with_capacity(len);
+        (&*file).read_to_end(&mut bytes)?;
+        Ok(Map(Backing::Read(bytes)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+
+    #[test]
+    fn should_map_give_file_bytes() {
+        let path = "tests/resources/puzzle.txt";
+
+        assert_eq!(&*map(&File::open(path).unwrap()).unwrap(), fs::read(path).unwrap().as_slice());
+    }
+
+    #[test]
+    fn should_map_give_no_bytes_when_file_empty() {
+        let path = env::temp_dir().join(format!("day_02_mmap_{}", process::id()));
+        fs::write(&path, "").unwrap();
+
+        let bytes = map(&File::open(&path).unwrap()).unwrap().len();
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(bytes, 0);
+    }
+}
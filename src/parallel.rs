@@ -0,0 +1,53 @@
+//! Classifying reports across threads, for puzzles of millions of reports.
+
+use std::{num::NonZeroUsize, thread};
+
+use crate::{puzzle::count_safe, report::Report, SafetyRules};
+
+/// Like [`count_safe`], splitting `reports` into one contiguous chunk per
+/// thread. `threads` 0 uses as many threads as the machine can run at once.
+pub(crate) fn count_safe_parallel(reports: &[Report], rules: &SafetyRules, tolerance: u32, threads: usize) -> usize {
+    let threads = match threads {
+        0 => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        threads => threads
+    };
+    if threads == 1 || reports.len() < 2 {
+        return count_safe(reports, rules, tolerance);
+    }
+    let chunk_size = reports.len().div_ceil(threads);
+    thread::scope(|scope| {
+        let counting: Vec<_> = reports
+        .chunks(chunk_size)
+        .map(|chunk| scope.spawn(move || count_safe(chunk, rules, tolerance)))
+        .collect();
+
+        counting.into_iter().map(|handle| handle.join().unwrap()).sum()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::{generate_puzzle, GeneratorConfig};
+
+    #[test]
+    fn should_count_safe_parallel_agree_with_count_safe_whatever_threads() {
+        let puzzle = generate_puzzle(&GeneratorConfig {
+            reports: 4999,
+            seed: 8,
+            ..GeneratorConfig::default()
+        });
+        for tolerance in 0..3 {
+            let expected = count_safe(&puzzle.reports, &SafetyRules::default(), tolerance);
+            for threads in [0, 1, 2, 3, 7, 64, 10_000] {
+                assert_eq!(count_safe_parallel(&puzzle.reports, &SafetyRules::default(), tolerance, threads), expected, "{threads} threads at tolerance {tolerance}");
+            }
+        }
+    }
+
+    #[test]
+    fn should_count_safe_parallel_handle_few_reports() {
+        assert_eq!(count_safe_parallel(&[], &SafetyRules::default(), 1, 4), 0);
+        assert_eq!(count_safe_parallel(&[Report::new(vec![1, 2, 3])], &SafetyRules::default(), 0, 4), 1);
+    }
+}
@@ -0,0 +1,592 @@
+use std::{borrow::Cow, error::Error, fmt, str::from_utf8};
+
+use crate::{
+    instrument::{self, Severity},
+    Level
+};
+
+/// How strictly puzzle lines are turned into reports.
+#[derive(PartialEq, Eq, Clone, Copy, Default)]
+#[derive(Debug)]
+pub struct ParseOptions {
+    /// Fail on the first line that cannot be parsed faithfully instead of
+    /// skipping it.
+    pub strict: bool,
+    /// Lines with fewer levels are rejected. `0` accepts every line.
+    pub min_levels: usize,
+    /// Accept `1_000`, `+3` and bracketed rows such as `[7, 6, 4, 2, 1]` in
+    /// lenient mode. Strict parsing keeps rejecting them.
+    pub normalize_tokens: bool,
+    pub delimiter: Delimiter,
+    /// Ignore the first line, such as a CSV header.
+    pub skip_header: bool,
+    /// Treat blank lines as reports with no levels, too short to be kept,
+    /// instead of skipping them.
+    pub reject_blank_lines: bool
+}
+
+/// What separates the levels of a line.
+#[derive(PartialEq, Eq, Clone, Copy, Default)]
+#[derive(Debug)]
+pub enum Delimiter {
+    /// Any run of spaces and tabs.
+    #[default]
+    Whitespace,
+    Comma,
+    Tab,
+    /// Any other ASCII byte. Whitespace around each level is ignored, and an
+    /// empty field is not a level.
+    Byte(u8)
+}
+
+impl Delimiter {
+    fn separates(&self, byte: u8) -> bool {
+        match self {
+            Delimiter::Whitespace => byte.is_ascii_whitespace(),
+            Delimiter::Comma => byte == b',',
+            Delimiter::Tab => byte == b'\t',
+            Delimiter::Byte(delimiter) => byte == *delimiter
+        }
+    }
+}
+
+impl ParseOptions {
+    pub fn strict() -> ParseOptions {
+        ParseOptions {
+            strict: true,
+            ..ParseOptions::default()
+        }
+    }
+
+    pub fn lenient() -> ParseOptions {
+        ParseOptions {
+            strict: false,
+            ..ParseOptions::default()
+        }
+    }
+}
+
+/// What lenient parsing had to do to the input to produce a puzzle.
+#[derive(PartialEq, Eq, Clone, Default)]
+#[derive(Debug)]
+pub struct ParseSummary {
+    pub lines: usize,
+    pub reports: usize,
+    pub blank_lines: usize,
+    /// 1-based numbers of the lines skipped because a level did not fit.
+    pub overflowed_lines: Vec<usize>,
+    /// 1-based numbers of the lines skipped for having fewer levels than
+    /// `ParseOptions::min_levels`.
+    pub short_lines: Vec<usize>,
+    /// Byte offsets of the bytes that are neither printable ASCII nor
+    /// whitespace. The tokens holding them are skipped.
+    pub invalid_bytes: Vec<usize>
+}
+
+#[derive(PartialEq, Eq, Clone)]
+#[derive(Debug)]
+pub enum ParseError {
+    /// A level on `line` (1-based) is too large to be stored.
+    Overflow { line: usize, token: String },
+    /// `line` (1-based) has fewer levels than `ParseOptions::min_levels`.
+    TooShort { line: usize, levels: usize },
+    /// `token` on `line` (1-based) is not a level.
+    InvalidToken { line: usize, token: String },
+    /// `byte` at `offset` in the input cannot be part of a puzzle.
+    InvalidByte { line: usize, offset: usize, byte: u8 }
+}
+
+impl ParseError {
+    /// The 1-based line the error is on.
+    pub fn line(&self) -> usize {
+        match self {
+            ParseError::Overflow { line, .. }
+            | ParseError::TooShort { line, .. }
+            | ParseError::InvalidToken { line, .. }
+            | ParseError::InvalidByte { line, .. } => *line
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Overflow { line, token } => {
+                write!(f, "line {line}: level `{token}` is out of range")
+            }
+            ParseError::TooShort { line, levels } => {
+                write!(f, "line {line}: report has only {levels} level(s)")
+            }
+            ParseError::InvalidToken { line, token } => {
+                write!(f, "line {line}: `{token}` is not a level")
+            }
+            ParseError::InvalidByte { line, offset, byte } => {
+                write!(f, "line {line}: invalid byte 0x{byte:02X} at offset {offset}")
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Where a line starts in the input.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub(crate) struct Location {
+    /// 1-based line number.
+    pub(crate) line: usize,
+    /// Byte offset of the first byte of the line.
+    pub(crate) offset: usize
+}
+
+/// Splits `input` into lines the way `str::lines` does, without requiring
+/// UTF-8: a trailing newline does not start an extra line and `\r\n` endings
+/// are accepted.
+pub(crate) fn lines(input: &[u8]) -> impl Iterator<Item = (Location, &[u8])> {
+    let mut offset = 0;
+    let mut line = 0;
+    std::iter::from_fn(move || {
+        if offset >= input.len() {
+            return None;
+        }
+        let rest = &input[offset..];
+        let end = rest.iter().position(|&byte| byte == b'\n').unwrap_or(rest.len());
+        let location = Location {
+            line: line + 1,
+            offset
+        };
+        line += 1;
+        offset += end + 1;
+
+        Some((location, rest[..end].strip_suffix(b"\r").unwrap_or(&rest[..end])))
+    })
+}
+
+fn is_digits(token: &str) -> bool {
+    !token.is_empty() && token.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+/// Digits, after a `-` when `L` can be negative.
+fn is_level<L: Level>(token: &str) -> bool {
+    match token.strip_prefix('-') {
+        Some(digits) => L::SIGNED && is_digits(digits),
+        None => is_digits(token)
+    }
+}
+
+/// Rewrites an exported token such as `[7,`, `+3` or `1_000` into plain
+/// digits, or `None` when it is not a level even once normalized.
+fn normalize_token(token: &str) -> Option<String> {
+    let token = token.strip_suffix(',').unwrap_or(token);
+    let token = token.strip_suffix(']').unwrap_or(token);
+    let token = token.strip_prefix('[').unwrap_or(token);
+    let token = token.strip_prefix('+').unwrap_or(token);
+    // Separators must sit between digits: no `1_`, `_1` or `1__0`.
+    if !token.split('_').all(is_digits) {
+        return None;
+    }
+
+    Some(token.replace('_', ""))
+}
+
+fn is_valid_byte(byte: u8) -> bool {
+    byte.is_ascii_graphic() || byte.is_ascii_whitespace()
+}
+
+/// Parses one line into its levels. Returns `None` when the line does not
+/// become a report, after recording why in `summary`.
+pub(crate) fn parse_line<L: Level>(line: &[u8], location: Location, options: &ParseOptions, summary: &mut ParseSummary) -> Result<Option<Vec<L>>, ParseError> {
+    let mut levels = Vec::new();
+
+    Ok(parse_line_into(line, location, options, summary, &mut levels)?.then_some(levels))
+}
+
+/// Like [`parse_line`], into `levels` so that its allocation can be reused.
+/// Returns whether the line made a report; `levels` is only meaningful then.
+pub(crate) fn parse_line_into<L: Level>(line: &[u8], location: Location, options: &ParseOptions, summary: &mut ParseSummary, levels: &mut Vec<L>) -> Result<bool, ParseError> {
+    levels.clear();
+    if options.skip_header && location.line == 1 {
+        return Ok(false);
+    }
+    if line.iter().all(u8::is_ascii_whitespace) {
+        if options.reject_blank_lines {
+            return too_short(location, levels, options, summary);
+        }
+        summary.blank_lines += 1;
+        return Ok(false);
+    }
+    let mut field_start = 0;
+    for field in line.split(|&byte| options.delimiter.separates(byte)) {
+        let leading = field.iter().take_while(|byte| byte.is_ascii_whitespace()).count();
+        let token_offset = location.offset + field_start + leading;
+        field_start += field.len() + 1;
+        let token = field.trim_ascii();
+        if token.is_empty() {
+            // Only whitespace can leave empty fields between levels.
+            if options.strict && options.delimiter != Delimiter::Whitespace {
+                return Err(ParseError::InvalidToken {
+                    line: location.line,
+                    token: String::new()
+                });
+            }
+            continue;
+        }
+        if let Some(index) = token.iter().position(|&byte| !is_valid_byte(byte)) {
+            if options.strict {
+                return Err(ParseError::InvalidByte {
+                    line: location.line,
+                    offset: token_offset + index,
+                    byte: token[index]
+                });
+            }
+            token.iter().enumerate()
+            .filter(|(_, &byte)| !is_valid_byte(byte))
+            .for_each(|(index, _)| summary.invalid_bytes.push(token_offset + index));
+            instrument::event(Severity::Warn, "skipped token", &[("line", &location.line), ("offset", &(token_offset + index))]);
+            continue;
+        }
+        // Only printable ASCII is left, so the token is valid UTF-8.
+        let token = from_utf8(token).unwrap_or_default();
+        // Borrowed unless normalized, so plain tokens are parsed in place.
+        let level = if options.normalize_tokens && !options.strict {
+            normalize_token(token).map(Cow::Owned)
+        } else {
+            Some(Cow::Borrowed(token))
+        };
+        match level.filter(|level| is_level::<L>(level)).map(|level| level.parse::<L>()) {
+            Some(Ok(level)) => levels.push(level),
+            // Digits that do not parse can only be out of range.
+            Some(Err(_)) => {
+                if options.strict {
+                    return Err(ParseError::Overflow {
+                        line: location.line,
+                        token: token.to_string()
+                    });
+                }
+                // The whole line is dropped rather than shortened, since a
+                // shortened report can look safe.
+                summary.overflowed_lines.push(location.line);
+                instrument::event(Severity::Warn, "skipped line", &[("line", &location.line), ("reason", &"overflow")]);
+                return Ok(false);
+            }
+            None if options.strict => {
+                return Err(ParseError::InvalidToken {
+                    line: location.line,
+                    token: token.to_string()
+                });
+            }
+            None => {}
+        }
+    }
+    // A line of nothing but dropped tokens would otherwise become an empty
+    // report.
+    if levels.is_empty() || levels.len() < options.min_levels {
+        return too_short(location, levels, options, summary);
+    }
+    Ok(true)
+}
+
+fn too_short<L>(location: Location, levels: &[L], options: &ParseOptions, summary: &mut ParseSummary) -> Result<bool, ParseError> {
+    if options.strict {
+        return Err(ParseError::TooShort {
+            line: location.line,
+            levels: levels.len()
+        });
+    }
+    summary.short_lines.push(location.line);
+    instrument::event(Severity::Warn, "skipped line", &[("line", &location.line), ("reason", &"too_short")]);
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at_line(line: usize) -> Location {
+        Location {
+            line,
+            offset: 0
+        }
+    }
+
+    #[test]
+    fn should_parse_line_skip_line_with_only_whitespace() {
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b" \t", at_line(1), &ParseOptions::strict(), &mut summary), Ok(None));
+        assert_eq!(summary.blank_lines, 1);
+    }
+
+    #[test]
+    fn should_parse_line_return_levels() {
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b"7 6 4", at_line(1), &ParseOptions::strict(), &mut summary), Ok(Some(vec![7, 6, 4])));
+        assert_eq!(summary, ParseSummary::default());
+    }
+
+    #[test]
+    fn should_parse_line_fail_on_overflow_when_strict() {
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b"1 99999999999 3", at_line(4), &ParseOptions::strict(), &mut summary), Err(ParseError::Overflow {
+            line: 4,
+            token: "99999999999".to_string()
+        }));
+    }
+
+    #[test]
+    fn should_parse_line_drop_whole_line_on_overflow_when_lenient() {
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b"1 99999999999 3", at_line(4), &ParseOptions::lenient(), &mut summary), Ok(None));
+        assert_eq!(summary.overflowed_lines, vec![4]);
+    }
+
+    #[test]
+    fn should_parse_line_accept_short_line_when_no_min_levels() {
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b"5", at_line(1), &ParseOptions::strict(), &mut summary), Ok(Some(vec![5])));
+    }
+
+    #[test]
+    fn should_parse_line_fail_on_short_line_when_strict_with_min_levels() {
+        let options = ParseOptions {
+            min_levels: 2,
+            ..ParseOptions::strict()
+        };
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b"5", at_line(3), &options, &mut summary), Err(ParseError::TooShort {
+            line: 3,
+            levels: 1
+        }));
+    }
+
+    #[test]
+    fn should_parse_line_skip_short_line_when_lenient_with_min_levels() {
+        let options = ParseOptions {
+            min_levels: 2,
+            ..ParseOptions::lenient()
+        };
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b"5", at_line(3), &options, &mut summary), Ok(None));
+        assert_eq!(parse_line::<u32>(b"5 6", at_line(4), &options, &mut summary), Ok(Some(vec![5, 6])));
+        assert_eq!(summary.short_lines, vec![3]);
+    }
+
+    #[test]
+    fn should_parse_line_fail_with_offset_on_invalid_byte_when_strict() {
+        let location = Location {
+            line: 2,
+            offset: 10
+        };
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b"1 2\xFF3", location, &ParseOptions::strict(), &mut summary), Err(ParseError::InvalidByte {
+            line: 2,
+            offset: 13,
+            byte: 0xFF
+        }));
+    }
+
+    #[test]
+    fn should_parse_line_skip_token_with_invalid_byte_when_lenient() {
+        let location = Location {
+            line: 2,
+            offset: 10
+        };
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b"1 2\xFF3 4", location, &ParseOptions::lenient(), &mut summary), Ok(Some(vec![1, 4])));
+        assert_eq!(summary.invalid_bytes, vec![13]);
+    }
+
+    #[test]
+    fn should_parse_line_skip_line_without_any_level_when_lenient() {
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b",,, ,,\t,", at_line(1), &ParseOptions::lenient(), &mut summary), Ok(None));
+        assert_eq!(parse_line::<u32>(b"\x00\xFF \x7F", at_line(2), &ParseOptions::lenient(), &mut summary), Ok(None));
+        assert_eq!(summary.short_lines, vec![1, 2]);
+    }
+
+    #[test]
+    fn should_parse_line_fail_on_garbage_when_strict() {
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b",,, ,,\t,", at_line(1), &ParseOptions::strict(), &mut summary), Err(ParseError::InvalidToken {
+            line: 1,
+            token: ",,,".to_string()
+        }));
+        assert_eq!(parse_line::<u32>(b"\x00\xFF \x7F", at_line(2), &ParseOptions::strict(), &mut summary), Err(ParseError::InvalidByte {
+            line: 2,
+            offset: 0,
+            byte: 0
+        }));
+    }
+
+    #[test]
+    fn should_parse_line_handle_very_long_line() {
+        let line = "1 2 ".repeat(100_000);
+        let mut summary = ParseSummary::default();
+
+        let levels = parse_line::<u32>(line.as_bytes(), at_line(1), &ParseOptions::strict(), &mut summary).unwrap().unwrap();
+
+        assert_eq!(levels.len(), 200_000);
+    }
+
+    #[test]
+    fn should_lines_split_like_str_lines() {
+        let input = b"1 2\r\n\n3 4\n";
+
+        let lines: Vec<_> = lines(input).map(|(location, line)| (location.line, location.offset, line)).collect();
+
+        assert_eq!(lines, vec![(1, 0, &b"1 2"[..]), (2, 5, &b""[..]), (3, 6, &b"3 4"[..])]);
+    }
+
+    fn normalizing() -> ParseOptions {
+        ParseOptions {
+            normalize_tokens: true,
+            ..ParseOptions::lenient()
+        }
+    }
+
+    #[test]
+    fn should_normalize_token_accept_exported_forms() {
+        assert_eq!(normalize_token("1_000"), Some("1000".to_string()));
+        assert_eq!(normalize_token("+3"), Some("3".to_string()));
+        assert_eq!(normalize_token("[7,"), Some("7".to_string()));
+        assert_eq!(normalize_token("6,"), Some("6".to_string()));
+        assert_eq!(normalize_token("1]"), Some("1".to_string()));
+        assert_eq!(normalize_token("[42]"), Some("42".to_string()));
+    }
+
+    #[test]
+    fn should_normalize_token_reject_garbage() {
+        assert_eq!(normalize_token("1_"), None);
+        assert_eq!(normalize_token("_1"), None);
+        assert_eq!(normalize_token("1__0"), None);
+        assert_eq!(normalize_token("++3"), None);
+        assert_eq!(normalize_token("[[3"), None);
+        assert_eq!(normalize_token("3,,"), None);
+        assert_eq!(normalize_token("[]"), None);
+        assert_eq!(normalize_token("x"), None);
+    }
+
+    #[test]
+    fn should_parse_line_parse_bracketed_row_like_plain_row_when_normalizing() {
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b"[7, 6, 4, 2, 1]", at_line(1), &normalizing(), &mut summary), Ok(Some(vec![7, 6, 4, 2, 1])));
+    }
+
+    #[test]
+    fn should_parse_line_accept_separators_and_plus_when_normalizing() {
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b"1_000 +3 1_001", at_line(1), &normalizing(), &mut summary), Ok(Some(vec![1000, 3, 1001])));
+    }
+
+    #[test]
+    fn should_parse_line_drop_exported_forms_when_not_normalizing() {
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b"1_000 +3 4", at_line(1), &ParseOptions::lenient(), &mut summary), Ok(Some(vec![4])));
+    }
+
+    #[test]
+    fn should_parse_line_reject_exported_forms_when_strict() {
+        let options = ParseOptions {
+            normalize_tokens: true,
+            ..ParseOptions::strict()
+        };
+        for (line, token) in [("+3 4", "+3"), ("1_000 4", "1_000"), ("[7, 6]", "[7,")] {
+            let mut summary = ParseSummary::default();
+
+            assert_eq!(parse_line::<u32>(line.as_bytes(), at_line(2), &options, &mut summary), Err(ParseError::InvalidToken {
+                line: 2,
+                token: token.to_string()
+            }));
+        }
+    }
+
+    #[test]
+    fn should_parse_line_split_on_delimiter() {
+        let comma = ParseOptions {
+            delimiter: Delimiter::Comma,
+            ..ParseOptions::strict()
+        };
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b"7, 6,4 ,2,1", at_line(1), &comma, &mut summary), Ok(Some(vec![7, 6, 4, 2, 1])));
+        assert_eq!(parse_line::<u32>(b"7\t6\t4", at_line(1), &ParseOptions {
+            delimiter: Delimiter::Tab,
+            ..ParseOptions::strict()
+        }, &mut summary), Ok(Some(vec![7, 6, 4])));
+        assert_eq!(parse_line::<u32>(b"7;6;4", at_line(1), &ParseOptions {
+            delimiter: Delimiter::Byte(b';'),
+            ..ParseOptions::strict()
+        }, &mut summary), Ok(Some(vec![7, 6, 4])));
+        assert_eq!(parse_line::<u32>(b"7 6,4", at_line(3), &comma, &mut summary), Err(ParseError::InvalidToken {
+            line: 3,
+            token: "7 6".to_string()
+        }));
+    }
+
+    #[test]
+    fn should_parse_line_reject_empty_field_only_when_strict() {
+        let comma = ParseOptions {
+            delimiter: Delimiter::Comma,
+            ..ParseOptions::strict()
+        };
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b"1,,2", at_line(2), &comma, &mut summary), Err(ParseError::InvalidToken {
+            line: 2,
+            token: String::new()
+        }));
+        assert_eq!(parse_line::<u32>(b"1,,2,", at_line(2), &ParseOptions {
+            strict: false,
+            ..comma
+        }, &mut summary), Ok(Some(vec![1, 2])));
+    }
+
+    #[test]
+    fn should_parse_line_report_invalid_byte_offset_after_delimiter() {
+        let options = ParseOptions {
+            delimiter: Delimiter::Comma,
+            ..ParseOptions::strict()
+        };
+
+        assert_eq!(parse_line::<u32>(b"1,  2\xFF", at_line(1), &options, &mut ParseSummary::default()), Err(ParseError::InvalidByte {
+            line: 1,
+            offset: 5,
+            byte: 0xFF
+        }));
+    }
+
+    #[test]
+    fn should_parse_line_skip_header_and_reject_blank_lines() {
+        let options = ParseOptions {
+            skip_header: true,
+            reject_blank_lines: true,
+            ..ParseOptions::strict()
+        };
+        let mut summary = ParseSummary::default();
+
+        assert_eq!(parse_line::<u32>(b"a,b,c", at_line(1), &options, &mut summary), Ok(None));
+        assert_eq!(parse_line::<u32>(b"1 2", at_line(2), &options, &mut summary), Ok(Some(vec![1, 2])));
+        assert_eq!(parse_line::<u32>(b"  ", at_line(3), &options, &mut summary), Err(ParseError::TooShort {
+            line: 3,
+            levels: 0
+        }));
+        assert_eq!(parse_line::<u32>(b"  ", at_line(3), &ParseOptions {
+            strict: false,
+            ..options
+        }, &mut summary), Ok(None));
+        assert_eq!((summary.blank_lines, summary.short_lines), (0, vec![3]));
+    }
+}
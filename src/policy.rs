@@ -0,0 +1,22 @@
+//! Rules that look at a whole report at once, for safety definitions the
+//! built-in [`SafetyRules`] cannot express.
+
+use crate::{is_safe_slice, SafetyRules};
+
+/// Decides whether a report is safe as-is. Object safe, so policies can be
+/// kept as `Box<dyn SafetyPolicy>`.
+pub trait SafetyPolicy {
+    fn is_safe(&self, levels: &[u32]) -> bool;
+}
+
+impl SafetyPolicy for SafetyRules {
+    fn is_safe(&self, levels: &[u32]) -> bool {
+        is_safe_slice(levels, self)
+    }
+}
+
+impl<F: Fn(&[u32]) -> bool> SafetyPolicy for F {
+    fn is_safe(&self, levels: &[u32]) -> bool {
+        self(levels)
+    }
+}
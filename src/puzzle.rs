@@ -0,0 +1,1453 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt, fs,
+    io::{self, BufRead, Write},
+    path::Path,
+    str::FromStr,
+    sync::{Mutex, PoisonError},
+    time::Instant
+};
+
+use crate::{
+    fingerprint::Fnv1a,
+    markdown::{self, MarkdownOptions},
+    parse::{lines, parse_line, Location},
+    reload::{IndexedLine, LineIndex, ReloadStats},
+    removals::min_removals_to_safe_reusing,
+    report::Report,
+    instrument::{self, Severity},
+    compare_puzzles, json, min_removals_to_safe, Day02Error, DirectionBreakdown, ParseError, ParseOptions, ParseSummary,
+    PuzzleDiff, ReportStatus, SafetyPolicy, SafetyReport, SafetyRules, SafetyStats, SafetySummary, Violation
+};
+
+#[derive(PartialEq, Default)]
+#[derive(Debug)]
+pub struct Puzzle {
+    pub(crate) reports: Vec<Report>,
+    counts: CountCache,
+    lines: LineIndex,
+    source: Source
+}
+
+/// The counts `count_safe` already computed, per rules and tolerance. Every
+/// method changing the reports clears it.
+///
+/// A `Mutex` rather than a `RefCell` so that `count_safe` can take `&self`
+/// and `Puzzle` stays `Sync`. The lock is only held to look up or store a
+/// count, never while counting.
+#[derive(Default)]
+struct CountCache(Mutex<HashMap<(SafetyRules, u32), usize>>);
+
+impl CountCache {
+    fn get(&self, rules: &SafetyRules, tolerance: u32) -> Option<usize> {
+        let counts = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+
+        counts.get(&(*rules, tolerance)).copied()
+    }
+
+    fn insert(&self, rules: &SafetyRules, tolerance: u32, count: usize) {
+        let mut counts = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+
+        counts.insert((*rules, tolerance), count);
+    }
+
+    fn clear(&mut self) {
+        self.0.get_mut().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+}
+
+/// Puzzles are equal when their reports are, whatever they have counted.
+impl PartialEq for CountCache {
+    fn eq(&self, _: &CountCache) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for CountCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CountCache")
+    }
+}
+
+/// The text the reports were parsed from, with whether each of its lines
+/// made the next report, for [`Puzzle::write_classification`] to write it
+/// back as it was. Methods changing the reports forget it.
+#[derive(Default)]
+struct Source(Option<SourceText>);
+
+struct SourceText {
+    text: Vec<u8>,
+    made_report: Vec<bool>
+}
+
+impl Source {
+    fn of(text: &[u8], made_report: Vec<bool>) -> Source {
+        Source(Some(SourceText {
+            text: text.to_vec(),
+            made_report
+        }))
+    }
+}
+
+/// Puzzles are equal when their reports are, whatever they were parsed from.
+impl PartialEq for Source {
+    fn eq(&self, _: &Source) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Source")
+    }
+}
+
+impl Puzzle {
+    pub fn new() -> Puzzle {
+        Puzzle::from_reports(Vec::new())
+    }
+
+    pub fn from_reports(reports: Vec<Report>) -> Puzzle {
+        Puzzle {
+            reports,
+            counts: CountCache::default(),
+            lines: LineIndex::default(),
+            source: Source::default()
+        }
+    }
+
+    pub fn reports(&self) -> &[Report] {
+        &self.reports
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Report> {
+        self.reports.iter()
+    }
+
+    /// The reports safe once up to `tolerance` levels may be removed, in
+    /// order.
+    pub fn safe_reports(&self, tolerance: u32) -> impl Iterator<Item = &Report> + '_ {
+        self.reports.iter().filter(move |report| report.is_safe(tolerance))
+    }
+
+    pub fn len(&self) -> usize {
+        self.reports.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reports.is_empty()
+    }
+
+    /// One report per inner vector, skipping empty ones the way
+    /// [`Puzzle::add_report`] skips lines without levels.
+    pub fn from_values(values: Vec<Vec<u32>>) -> Puzzle {
+        Puzzle::from_reports(values
+        .into_iter()
+        .filter(|levels| !levels.is_empty())
+        .map(Report::from)
+        .collect())
+    }
+
+    /// Like [`Puzzle::from_values`], checking each report against `options`
+    /// as if it were the line at the same 1-based position: strict options
+    /// turn a report that is empty or shorter than `min_levels` into an
+    /// error, lenient ones drop it.
+    pub fn try_from_values(values: Vec<Vec<u32>>, options: ParseOptions) -> Result<Puzzle, ParseError> {
+        let mut reports = Vec::with_capacity(values.len());
+        for (index, levels) in values.into_iter().enumerate() {
+            if levels.is_empty() || levels.len() < options.min_levels {
+                if options.strict {
+                    return Err(ParseError::TooShort {
+                        line: index + 1,
+                        levels: levels.len()
+                    });
+                }
+                continue;
+            }
+            reports.push(Report::from(levels));
+        }
+        Ok(Puzzle::from_reports(reports))
+    }
+
+    /// The levels of every report, in order.
+    pub fn into_values(self) -> Vec<Vec<u32>> {
+        self.reports.into_iter().map(|report| report.values).collect()
+    }
+
+    /// Parses every line of `input`, reporting what lenient parsing skipped.
+    pub fn parse(input: &str, options: ParseOptions) -> Result<(Puzzle, ParseSummary), ParseError> {
+        Puzzle::from_bytes(input.as_bytes(), options)
+    }
+
+    /// Like [`Puzzle::parse`], without requiring the input to be UTF-8.
+    pub fn from_bytes(input: &[u8], options: ParseOptions) -> Result<(Puzzle, ParseSummary), ParseError> {
+        let mut reports = Vec::new();
+        let mut made_report = Vec::new();
+        let mut summary = ParseSummary::default();
+        for (location, line) in lines(input) {
+            summary.lines += 1;
+            let numbers = parse_line(line, location, &options, &mut summary)?;
+            made_report.push(numbers.is_some());
+            reports.extend(numbers.map(Report::new));
+        }
+        summary.reports = reports.len();
+        let mut puzzle = Puzzle::from_reports(reports);
+        puzzle.source = Source::of(input, made_report);
+
+        Ok((puzzle, summary))
+    }
+
+    /// Parses strictly but goes on past bad lines, failing at the end with
+    /// every error found, in line order.
+    pub fn parse_strict_all(input: &str) -> Result<Puzzle, Vec<ParseError>> {
+        Puzzle::from_bytes_strict_all(input.as_bytes())
+    }
+
+    /// Like [`Puzzle::parse_strict_all`], without requiring the input to be
+    /// UTF-8.
+    pub fn from_bytes_strict_all(input: &[u8]) -> Result<Puzzle, Vec<ParseError>> {
+        Puzzle::from_bytes_strict_all_with(input, ParseOptions::strict())
+    }
+
+    /// Like [`Puzzle::from_bytes_strict_all`], splitting lines as `options`
+    /// say. Parsing is strict whatever `options.strict`.
+    pub fn from_bytes_strict_all_with(input: &[u8], options: ParseOptions) -> Result<Puzzle, Vec<ParseError>> {
+        let options = ParseOptions {
+            strict: true,
+            ..options
+        };
+        let mut reports = Vec::new();
+        let mut made_report = Vec::new();
+        let mut errors = Vec::new();
+        for (location, line) in lines(input) {
+            match parse_line(line, location, &options, &mut ParseSummary::default()) {
+                Ok(Some(numbers)) => {
+                    reports.push(Report::new(numbers));
+                    made_report.push(true);
+                }
+                Ok(None) => made_report.push(false),
+                Err(error) => {
+                    errors.push(error);
+                    made_report.push(false);
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let mut puzzle = Puzzle::from_reports(reports);
+        puzzle.source = Source::of(input, made_report);
+
+        Ok(puzzle)
+    }
+
+    /// Like [`Puzzle::from_bytes`], reading `reader` one line at a time so
+    /// that the whole input is never held in memory.
+    pub fn from_reader<R: BufRead>(mut reader: R, options: ParseOptions) -> Result<(Puzzle, ParseSummary), Day02Error> {
+        let mut reports = Vec::new();
+        let mut summary = ParseSummary::default();
+        let mut location = Location {
+            line: 1,
+            offset: 0
+        };
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line)?;
+            if read == 0 {
+                break;
+            }
+            let content = line.strip_suffix(b"\n").unwrap_or(&line);
+            let content = content.strip_suffix(b"\r").unwrap_or(content);
+            summary.lines += 1;
+            if let Some(numbers) = parse_line(content, location, &options, &mut summary)? {
+                reports.push(Report::new(numbers));
+            }
+            location = Location {
+                line: location.line + 1,
+                offset: location.offset + read
+            };
+        }
+        summary.reports = reports.len();
+
+        Ok((Puzzle::from_reports(reports), summary))
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P, options: ParseOptions) -> Result<(Puzzle, ParseSummary), Day02Error> {
+        let start = Instant::now();
+        let input = fs::read(&path)?;
+        let (puzzle, summary) = Puzzle::from_bytes(&input, options)?;
+        instrument::span("extract_puzzle", &[
+            ("path", &path.as_ref().display()),
+            ("bytes", &input.len()),
+            ("reports", &puzzle.reports.len()),
+            ("duration_us", &start.elapsed().as_micros())
+        ]);
+
+        Ok((puzzle, summary))
+    }
+
+    /// Like [`Puzzle::from_path`], parsing a read-only memory map of the file
+    /// rather than a copy of it, so that huge inputs never take their size
+    /// in heap. The file must not be truncated while it is parsed.
+    #[cfg(feature = "mmap")]
+    pub fn from_path_mmap<P: AsRef<Path>>(path: P, options: ParseOptions) -> Result<(Puzzle, ParseSummary), Day02Error> {
+        let start = Instant::now();
+        let input = crate::mmap::map(&fs::File::open(&path)?)?;
+        let (puzzle, summary) = Puzzle::from_bytes(&input, options)?;
+        instrument::span("extract_puzzle", &[
+            ("path", &path.as_ref().display()),
+            ("bytes", &input.len()),
+            ("reports", &puzzle.reports.len()),
+            ("duration_us", &start.elapsed().as_micros())
+        ]);
+
+        Ok((puzzle, summary))
+    }
+
+    /// Adds the report on `line`, parsed leniently. Lines that would not make
+    /// a faithful report are ignored.
+    pub fn add_report(&mut self, line: &str) {
+        let _ = self.try_add_report(line, ParseOptions::lenient());
+    }
+
+    /// Adds the report on `line` parsed with `options`, telling whether the
+    /// line made one. With strict options a malformed token fails with the
+    /// token, found on the line numbered as the next report, rather than
+    /// being dropped. `skip_header` is ignored, since a line on its own
+    /// cannot tell whether it is the first of its input: a header is
+    /// parsed like any other line.
+    pub fn try_add_report(&mut self, line: &str, options: ParseOptions) -> Result<bool, ParseError> {
+        let location = Location {
+            line: self.reports.len() + 1,
+            offset: 0
+        };
+        let options = ParseOptions {
+            skip_header: false,
+            ..options
+        };
+        let Some(numbers) = parse_line(line.as_bytes(), location, &options, &mut ParseSummary::default())? else {
+            return Ok(false);
+        };
+        self.reports.push(Report::new(numbers));
+        self.forget_counts();
+
+        Ok(true)
+    }
+
+    /// Keeps only the reports whose levels satisfy `keep`.
+    pub fn retain(&mut self, mut keep: impl FnMut(&[u32]) -> bool) {
+        self.reports.retain(|report| keep(&report.values));
+        self.forget_counts();
+    }
+
+    /// Appends the reports of `other`.
+    pub fn merge(&mut self, other: Puzzle) {
+        self.reports.extend(other.reports);
+        self.forget_counts();
+    }
+
+    /// Removes the reports whose levels already appeared in an earlier
+    /// report.
+    pub fn dedup_reports(&mut self) {
+        let mut seen = HashSet::new();
+        self.reports.retain(|report| seen.insert(report.values.clone()));
+        self.forget_counts();
+    }
+
+    /// What the reports were counted or loaded from no longer holds.
+    fn forget_counts(&mut self) {
+        self.counts.clear();
+        self.lines.0 = None;
+        self.source.0 = None;
+    }
+
+    /// Replaces the reports with those of `new_text`, parsed leniently, as
+    /// if it were parsed from scratch. Lines already in the text of the last
+    /// reload keep their report and status, matched by content in order;
+    /// only the others are parsed and classified. The counts of the default
+    /// rules, with and without the Problem Dampener, are then known without
+    /// counting.
+    ///
+    /// The first reload, or one after the reports were changed any other
+    /// way, parses every line.
+    pub fn reload_incremental(&mut self, new_text: &str) -> ReloadStats {
+        let rules = SafetyRules::default();
+        let old_lines = self.lines.0.take().unwrap_or_default();
+        let mut old_reports: Vec<Option<Report>> = std::mem::take(&mut self.reports).into_iter().map(Some).collect();
+        // Each line with its report, if it made one, queued by content so
+        // that repeated lines are reused in order.
+        let mut known = HashMap::<_, VecDeque<_>>::new();
+        let mut next_report = 0;
+        for line in &old_lines {
+            let report = line.status.map(|_| {
+                next_report += 1;
+                next_report - 1
+            });
+            known.entry((line.hash, line.len)).or_default().push_back((report, *line));
+        }
+
+        let mut stats = ReloadStats::default();
+        let mut new_lines = Vec::new();
+        for (location, line) in lines(new_text.as_bytes()) {
+            let key = (line_hash(line), line.len());
+            if let Some((report, indexed)) = known.get_mut(&key).and_then(VecDeque::pop_front) {
+                if let Some(report) = report.and_then(|report| old_reports[report].take()) {
+                    self.reports.push(report);
+                }
+                new_lines.push(indexed);
+                stats.reused += 1;
+                continue;
+            }
+            let levels = parse_line(line, location, &ParseOptions::lenient(), &mut ParseSummary::default()).unwrap_or_default();
+            let status = levels.map(|levels| {
+                let report = Report::new(levels);
+                let status = report.classify(&rules, 1);
+                self.reports.push(report);
+                status
+            });
+            new_lines.push(IndexedLine {
+                hash: key.0,
+                len: key.1,
+                status
+            });
+            stats.reparsed += 1;
+        }
+        stats.removed = old_lines.len() - stats.reused;
+
+        self.counts.clear();
+        let statuses = || new_lines.iter().filter_map(|line| line.status);
+        self.counts.insert(&rules, 0, statuses().filter(|&status| status == ReportStatus::Safe).count());
+        self.counts.insert(&rules, 1, statuses().filter(|&status| status != ReportStatus::Unsafe).count());
+        self.source = Source::of(new_text.as_bytes(), new_lines.iter().map(|line| line.status.is_some()).collect());
+        self.lines.0 = Some(new_lines);
+
+        stats
+    }
+
+    /// Counts the reports that `policy` finds safe as-is or once up to
+    /// `tolerance` levels are removed, every set of removals being checked
+    /// with the policy. Unlike [`Puzzle::count_safe`] it is not cached, and
+    /// it is exponential in `tolerance`: a report of n levels may take every
+    /// set of up to `tolerance` of them, each checked in O(n), where
+    /// [`Puzzle::count_safe`] is O(n·tolerance). Removals past the length of
+    /// a report are not tried.
+    pub fn count_safe_with_policy(&self, policy: &dyn SafetyPolicy, tolerance: u32) -> usize {
+        self.reports
+        .iter()
+        .filter(|report| is_safe_with_policy(&report.values, policy, tolerance))
+        .count()
+    }
+
+    /// Counts the reports that are safe under `rules` once up to `tolerance`
+    /// levels may be removed from each of them.
+    pub fn count_safe(&self, rules: &SafetyRules, tolerance: u32) -> usize {
+        let result = self.counts.get(rules, tolerance).unwrap_or_else(|| {
+            let count = count_safe(&self.reports, rules, tolerance);
+            self.counts.insert(rules, tolerance, count);
+            count
+        });
+        instrument::span("count_safe", &[("tolerance", &tolerance), ("result", &result)]);
+
+        result
+    }
+
+    /// Like [`Puzzle::count_safe`], classifying the reports on `threads`
+    /// threads, or as many as the machine can run at once when 0.
+    #[cfg(feature = "parallel")]
+    pub fn count_safe_parallel(&self, rules: &SafetyRules, tolerance: u32, threads: usize) -> usize {
+        self.counts.get(rules, tolerance).unwrap_or_else(|| {
+            let count = crate::parallel::count_safe_parallel(&self.reports, rules, tolerance, threads);
+            self.counts.insert(rules, tolerance, count);
+            count
+        })
+    }
+
+    /// Which reports changed status, with the Problem Dampener on, from
+    /// `self` to `other`, such as last night's input and tonight's; see
+    /// [`compare_puzzles`](crate::compare_puzzles) for other tolerances.
+    pub fn diff(&self, other: &Puzzle) -> PuzzleDiff {
+        compare_puzzles(self, other, 1)
+    }
+
+    /// The status of every report, in input order.
+    pub fn statuses(&self, rules: &SafetyRules, tolerance: u32) -> Vec<ReportStatus> {
+        self.reports
+        .iter()
+        .map(|report| report.classify(rules, tolerance))
+        .collect()
+    }
+
+    /// Why each report is unsafe before any removal, in input order: `None`
+    /// for the safe ones, else the first rule it breaks.
+    pub fn classify_all(&self, rules: &SafetyRules) -> Vec<Option<Violation>> {
+        self.reports
+        .iter()
+        .map(|report| report.violation(rules))
+        .collect()
+    }
+
+    /// A hash of the levels of every report, in order. It is FNV-1a (64
+    /// bits) over each report's level count as a little-endian `u64` followed
+    /// by its levels as little-endian `u32`s, so it only changes with the
+    /// reports themselves: not with spacing, blank lines or skipped garbage,
+    /// nor across platforms and releases.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        for report in &self.reports {
+            hasher.write(&(report.values.len() as u64).to_le_bytes());
+            for level in &report.values {
+                hasher.write(&level.to_le_bytes());
+            }
+        }
+        hasher.finish()
+    }
+
+    /// The reports by direction, with how many of each are safe as-is and
+    /// with the Problem Dampener, under the default rules.
+    pub fn group_by_direction(&self) -> DirectionBreakdown {
+        let rules = SafetyRules::default();
+        let mut breakdown = DirectionBreakdown::default();
+        for (index, report) in self.reports.iter().enumerate() {
+            let group = breakdown.group_mut(report.direction());
+            group.indices.push(index);
+            match report.classify(&rules, 1) {
+                ReportStatus::Safe => {
+                    group.safe += 1;
+                    group.safe_with_dampener += 1;
+                }
+                ReportStatus::SafeWithDampener => group.safe_with_dampener += 1,
+                ReportStatus::Unsafe => {}
+            }
+        }
+        breakdown
+    }
+
+    /// Counts by status and failure reason, step sizes and report lengths,
+    /// under the default rules with the Problem Dampener on.
+    pub fn stats(&self) -> SafetyStats {
+        let rules = SafetyRules::default();
+        let mut stats = SafetyStats {
+            total: self.reports.len(),
+            ..SafetyStats::default()
+        };
+        for report in &self.reports {
+            match report.classify(&rules, 1) {
+                ReportStatus::Safe => stats.safe += 1,
+                ReportStatus::SafeWithDampener => stats.rescued_by_dampener += 1,
+                ReportStatus::Unsafe => stats.unsafe_reports += 1
+            }
+            if let Some(violation) = report.violation(&rules) {
+                stats.failures.count(violation);
+            }
+            for pair in report.values.windows(2) {
+                *stats.step_sizes.entry(pair[0].abs_diff(pair[1])).or_default() += 1;
+            }
+            let levels = report.values.len();
+            stats.shortest = Some(stats.shortest.map_or(levels, |shortest| shortest.min(levels)));
+            stats.longest = Some(stats.longest.map_or(levels, |longest| longest.max(levels)));
+        }
+        stats
+    }
+
+    /// Both answers in one pass: part 1 as-is, part 2 with up to `tolerance`
+    /// removals.
+    pub fn summary(&self, rules: &SafetyRules, tolerance: u32) -> SafetySummary {
+        let mut summary = SafetySummary {
+            total: self.reports.len(),
+            tolerance,
+            ..SafetySummary::default()
+        };
+        for report in &self.reports {
+            match report.classify(rules, tolerance) {
+                ReportStatus::Safe => {
+                    summary.part1 += 1;
+                    summary.part2 += 1;
+                }
+                ReportStatus::SafeWithDampener => summary.part2 += 1,
+                ReportStatus::Unsafe => {}
+            }
+        }
+        summary
+    }
+
+    /// Every report classified with the Problem Dampener on, in input order.
+    pub fn safety_reports(&self, rules: &SafetyRules) -> Vec<SafetyReport> {
+        self.reports
+        .iter()
+        .map(|report| SafetyReport {
+            levels: report.values.clone(),
+            status: report.classify(rules, 1),
+            violation: report.violation(rules),
+            fix: report.dampener_fix(rules)
+        })
+        .collect()
+    }
+
+    /// `{"summary": SafetySummary, "reports": [SafetyReport]}` under the
+    /// default rules, see the `summary` module for the schema.
+    pub fn classification_json(&self) -> String {
+        let rules = SafetyRules::default();
+
+        json::object(&[
+            ("summary", self.summary(&rules, 1).to_json()),
+            ("reports", json::array(self.safety_reports(&rules).iter().map(SafetyReport::to_json)))
+        ])
+    }
+
+    /// Writes the text the puzzle was parsed from back as it was, each
+    /// report's line followed by its status under the default rules, e.g.
+    /// `1 2 7 8 9    # unsafe: diff 5 at pair 2`, and the other lines that are
+    /// not blank by `# skipped`. Pairs and levels are numbered from 1.
+    ///
+    /// Puzzles not parsed from a whole text, such as those read with
+    /// [`Puzzle::from_reader`] or changed since, have their reports written
+    /// with single spaces between levels instead.
+    pub fn write_classification<W: Write>(&self, mut w: W, tolerance: u32) -> io::Result<()> {
+        let rules = SafetyRules::default();
+        let Some(source) = &self.source.0 else {
+            for report in &self.reports {
+                writeln!(w, "{}    # {}", report.levels_text(), annotation(report, &rules, tolerance))?;
+            }
+            return Ok(());
+        };
+        let mut reports = self.reports.iter().peekable();
+        for (line, &made_report) in source.text.split_inclusive(|&byte| byte == b'\n').zip(&source.made_report) {
+            let content = line.strip_suffix(b"\n").unwrap_or(line);
+            let content = content.strip_suffix(b"\r").unwrap_or(content);
+            w.write_all(content)?;
+            match reports.next_if(|_| made_report) {
+                Some(report) => write!(w, "    # {}", annotation(report, &rules, tolerance))?,
+                None if !content.iter().all(u8::is_ascii_whitespace) => write!(w, "    # skipped")?,
+                None => {}
+            }
+            // The last line may have had no line ending.
+            w.write_all(line.get(content.len()..).filter(|ending| !ending.is_empty()).unwrap_or(b"\n"))?;
+        }
+        Ok(())
+    }
+
+    /// A summary table of both parts, then a table of the reports still
+    /// unsafe with the Problem Dampener, for pasting into issues.
+    pub fn to_markdown(&self, options: &MarkdownOptions) -> String {
+        markdown::render(self, options)
+    }
+
+    /// The first `limit` reports as sparklines, one per line, each followed
+    /// by its status with the Problem Dampener on.
+    pub fn render_sparklines(&self, limit: usize) -> String {
+        let rules = SafetyRules::default();
+
+        self.reports
+        .iter()
+        .take(limit)
+        .map(|report| format!("{}  {}\n", report.sparkline(), report.classify(&rules, 1).as_str()))
+        .collect()
+    }
+
+    /// The 1-based line of the input each report was parsed from, in order,
+    /// past the blank, header and skipped lines. Puzzles not parsed from a
+    /// whole text, or changed since, number their reports by position
+    /// instead.
+    fn input_lines(&self) -> Vec<usize> {
+        match &self.source.0 {
+            Some(source) => source.made_report
+            .iter()
+            .enumerate()
+            .filter(|(_, &made_report)| made_report)
+            .map(|(index, _)| index + 1)
+            .collect(),
+            None => (1..=self.reports.len()).collect()
+        }
+    }
+
+    /// The reports the Problem Dampener fixes by removing one level, as CSV
+    /// with a header row. `line` is the 1-based line of the report, see
+    /// [`Puzzle::input_lines`], and `removed_index` is 0-based like in the
+    /// JSON.
+    pub fn export_dampener_fixes_csv<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let rules = SafetyRules::default();
+        writeln!(w, "line,levels,removed_index,removed_level,repaired_levels\r")?;
+        for (report, line) in self.reports.iter().zip(self.input_lines()) {
+            if let Some(fix) = report.dampener_fix(&rules) {
+                let repaired = report.without_level(fix.removed_index);
+                writeln!(
+                    w,
+                    "{},\"{}\",{},{},\"{}\"\r",
+                    line,
+                    report.levels_text(),
+                    fix.removed_index,
+                    fix.removed_level,
+                    repaired.levels_text()
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Every report of [`Puzzle::safety_reports`] as a CSV row, with the
+    /// columns of its JSON form. `line` is the 1-based line of the report,
+    /// see [`Puzzle::input_lines`], the other indices 0-based, and missing
+    /// values are left empty.
+    pub fn write_classification_csv<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "line,levels,status,violation,violation_index,removed_index,removed_level\r")?;
+        for (report, line) in self.safety_reports(&SafetyRules::default()).iter().zip(self.input_lines()) {
+            let levels: Vec<String> = report.levels.iter().map(u32::to_string).collect();
+            writeln!(
+                w,
+                "{},\"{}\",{},{},{},{},{}\r",
+                line,
+                levels.join(" "),
+                report.status.as_str(),
+                report.violation.map_or("", |violation| violation.kind()),
+                csv_field(report.violation.and_then(|violation| violation.index())),
+                csv_field(report.fix.map(|fix| fix.removed_index)),
+                csv_field(report.fix.map(|fix| fix.removed_level))
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The unsafe reports as `(index, removals)`, most easily fixed first
+    /// then by index, where `removals` is the fewest levels to remove to
+    /// make the report safe. Reports needing more than `cap` removals come
+    /// last with `cap + 1`.
+    pub fn rank_by_fixability(&self, cap: u32) -> Vec<(usize, u32)> {
+        let rules = SafetyRules::default();
+        let mut ranking: Vec<(usize, u32)> = self.reports
+        .iter()
+        .enumerate()
+        .filter(|(_, report)| !report.is_safe_with(&rules))
+        .map(|(index, report)| (index, min_removals_to_safe(&report.values, &rules, cap).unwrap_or(cap.saturating_add(1))))
+        .collect();
+        ranking.sort_by_key(|&(index, removals)| (removals, index));
+
+        ranking
+    }
+}
+
+fn annotation(report: &Report, rules: &SafetyRules, tolerance: u32) -> String {
+    match report.classify(rules, tolerance) {
+        ReportStatus::Safe => "safe".to_string(),
+        ReportStatus::SafeWithDampener => match report.minimal_fix(rules, tolerance).as_deref() {
+            Some([index]) => format!("safe with dampener (remove level {})", index + 1),
+            Some(indices) => {
+                let levels: Vec<String> = indices.iter().map(|index| (index + 1).to_string()).collect();
+                format!("safe with dampener (remove levels {})", levels.join(", "))
+            }
+            None => "safe with dampener".to_string()
+        },
+        ReportStatus::Unsafe => match report.violation(rules) {
+            Some(violation) => format!("unsafe: {}", violation_text(violation)),
+            None => "unsafe".to_string()
+        }
+    }
+}
+
+/// The broken rule in words, with pairs numbered from 1.
+pub(crate) fn violation_text(violation: Violation) -> String {
+    match violation {
+        Violation::Step { index, diff } => format!("diff {diff} at pair {}", index + 1),
+        Violation::Direction { index } => format!("direction change at pair {}", index + 1),
+        Violation::TooShort { levels } => format!("only {levels} level(s)")
+    }
+}
+
+/// Parses strictly, so that `"1 x 3".parse::<Puzzle>()` fails rather than
+/// giving the report `1 3`.
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Puzzle, ParseError> {
+        let (puzzle, _) = Puzzle::parse(input, ParseOptions::strict())?;
+
+        Ok(puzzle)
+    }
+}
+
+impl<'a> IntoIterator for &'a Puzzle {
+    type Item = &'a Report;
+    type IntoIter = std::slice::Iter<'a, Report>;
+
+    fn into_iter(self) -> std::slice::Iter<'a, Report> {
+        self.reports.iter()
+    }
+}
+
+impl From<Vec<Vec<u32>>> for Puzzle {
+    fn from(values: Vec<Vec<u32>>) -> Puzzle {
+        Puzzle::from_values(values)
+    }
+}
+
+/// Tries every set of up to `tolerance` removals, the levels left by each
+/// being gathered into one buffer shared by the whole search.
+fn is_safe_with_policy(levels: &[u32], policy: &dyn SafetyPolicy, tolerance: u32) -> bool {
+    let tolerance = tolerance.min(u32::try_from(levels.len()).unwrap_or(u32::MAX));
+    search_with_policy(levels, policy, tolerance, 0, &mut Vec::new(), &mut Vec::with_capacity(levels.len()))
+}
+
+/// Adds removals at `from` or after only to those of `removed`, ascending,
+/// so each set of removals is checked once and the levels left are gathered
+/// in one pass along both.
+fn search_with_policy(levels: &[u32], policy: &dyn SafetyPolicy, tolerance: u32, from: usize, removed: &mut Vec<usize>, kept: &mut Vec<u32>) -> bool {
+    kept.clear();
+    let mut next_removed = removed.iter().peekable();
+    kept.extend(levels.iter().enumerate().filter(|&(index, _)| next_removed.next_if_eq(&&index).is_none()).map(|(_, &level)| level));
+    if policy.is_safe(kept) {
+        return true;
+    }
+    tolerance > 0 && (from..levels.len()).any(|index| {
+        removed.push(index);
+        let safe = search_with_policy(levels, policy, tolerance - 1, index + 1, removed, kept);
+        removed.pop();
+        safe
+    })
+}
+
+fn csv_field(value: Option<impl fmt::Display>) -> String {
+    value.map_or(String::new(), |value| value.to_string())
+}
+
+fn line_hash(line: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.write(line);
+    hasher.finish()
+}
+
+/// Counts in `usize`, so more than `u32::MAX` safe reports cannot wrap. The
+/// removal search shares one buffer across all reports.
+pub(crate) fn count_safe<'a>(reports: impl IntoIterator<Item = &'a Report>, rules: &SafetyRules, tolerance: u32) -> usize {
+    let mut scratch = Vec::new();
+    let traced = instrument::enabled(Severity::Trace);
+    reports
+    .into_iter()
+    .enumerate()
+    .filter(|(index, report)| {
+        let removals = min_removals_to_safe_reusing(report.levels(), rules, tolerance, &mut scratch);
+        if traced {
+            let status = match removals {
+                Some(0) => ReportStatus::Safe,
+                Some(_) => ReportStatus::SafeWithDampener,
+                None => ReportStatus::Unsafe
+            };
+            instrument::event(Severity::Trace, "classified report", &[("index", index), ("status", &status.as_str())]);
+        }
+        removals.is_some()
+    })
+    .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, fs::read_to_string};
+
+    use super::*;
+    use crate::{
+        generate::{self, GeneratorConfig},
+        Delimiter, DirectionGroup, FailureReasons, StatusChange
+    };
+
+    #[test]
+    fn should_from_reader_match_from_bytes() {
+        let input = "7 6 4 2 1\r\n\n1 x 2 4 5\n9 7 6 2 1";
+
+        let (puzzle, summary) = Puzzle::from_reader(input.as_bytes(), ParseOptions::lenient()).unwrap();
+
+        assert_eq!((puzzle, summary), Puzzle::from_bytes(input.as_bytes(), ParseOptions::lenient()).unwrap());
+        assert!(matches!(
+            Puzzle::from_reader(input.as_bytes(), ParseOptions::strict()),
+            Err(Day02Error::Parse(ParseError::InvalidToken { line: 3, .. }))
+        ));
+    }
+
+    #[test]
+    fn should_from_str_parse_strictly() {
+        let puzzle: Puzzle = "7 6 4 2 1\n1 3 2 4 5".parse().unwrap();
+
+        assert_eq!(puzzle.count_safe(&SafetyRules::default(), 1), 2);
+        assert_eq!("1 x 3".parse::<Puzzle>(), Err(ParseError::InvalidToken {
+            line: 1,
+            token: "x".to_string()
+        }));
+    }
+
+    #[test]
+    fn should_try_add_report_reject_garbage_when_strict() {
+        let mut puzzle = Puzzle::new();
+
+        assert_eq!(puzzle.try_add_report("7 6 4 2 1", ParseOptions::strict()), Ok(true));
+        assert_eq!(puzzle.try_add_report("1 x 3", ParseOptions::strict()), Err(ParseError::InvalidToken {
+            line: 2,
+            token: "x".to_string()
+        }));
+        assert_eq!(puzzle.try_add_report("  ", ParseOptions::strict()), Ok(false));
+        assert_eq!(puzzle.len(), 1);
+
+        assert_eq!(puzzle.try_add_report("1 x 3", ParseOptions::lenient()), Ok(true));
+        assert_eq!(puzzle.reports()[1].levels(), &[1, 3]);
+    }
+
+    #[test]
+    fn should_try_add_report_keep_every_report_when_skipping_header() {
+        let options = ParseOptions {
+            delimiter: Delimiter::Comma,
+            skip_header: true,
+            ..ParseOptions::lenient()
+        };
+        let mut puzzle = Puzzle::new();
+
+        assert_eq!(puzzle.try_add_report("first,second,third", options), Ok(false));
+        assert_eq!(puzzle.try_add_report("7,6,4,2,1", options), Ok(true));
+        assert_eq!(puzzle.try_add_report("1,3,2,4,5", options), Ok(true));
+        assert_eq!(puzzle.len(), 2);
+    }
+
+    #[test]
+    fn should_add_report_skip_blank_lines() {
+        let mut puzzle = Puzzle::new();
+        puzzle.add_report("");
+        puzzle.add_report("   ");
+        puzzle.add_report("1 2");
+
+        assert_eq!(puzzle, Puzzle::from_reports(vec![Report {
+            values: vec![1, 2]
+        }]));
+    }
+
+    #[test]
+    fn should_parse_fail_with_overflow_line_when_strict() {
+        let input = read_to_string("tests/resources/puzzle_overflow.txt").unwrap();
+
+        assert_eq!(Puzzle::parse(&input, ParseOptions::strict()), Err(ParseError::Overflow {
+            line: 6,
+            token: "99999999999".to_string()
+        }));
+    }
+
+    #[test]
+    fn should_parse_skip_overflowed_report_when_lenient() {
+        let input = read_to_string("tests/resources/puzzle_overflow.txt").unwrap();
+
+        let (puzzle, summary) = Puzzle::parse(&input, ParseOptions::lenient()).unwrap();
+
+        assert_eq!(puzzle.reports.len(), 5);
+        assert_eq!(puzzle.count_safe(&SafetyRules::default(), 0), 1);
+        assert_eq!(summary, ParseSummary {
+            lines: 6,
+            reports: 5,
+            blank_lines: 0,
+            overflowed_lines: vec![6],
+            short_lines: vec![],
+            invalid_bytes: vec![]
+        });
+    }
+
+    #[test]
+    fn should_from_path_fail_with_offset_on_invalid_byte_when_strict() {
+        let result = Puzzle::from_path("tests/resources/puzzle_invalid_byte.txt", ParseOptions::strict());
+
+        assert!(matches!(result, Err(Day02Error::Parse(ParseError::InvalidByte {
+            line: 3,
+            offset: 30,
+            byte: 0xFF
+        }))));
+    }
+
+    #[test]
+    fn should_from_path_skip_invalid_byte_when_lenient() {
+        let (puzzle, summary) = Puzzle::from_path("tests/resources/puzzle_invalid_byte.txt", ParseOptions::lenient()).unwrap();
+        let (expected, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::lenient()).unwrap();
+
+        assert_eq!(puzzle, expected);
+        assert_eq!(summary.invalid_bytes, vec![30]);
+    }
+
+    #[test]
+    fn should_from_path_fail_with_io_error_when_file_is_missing() {
+        let result = Puzzle::from_path("tests/resources/missing.txt", ParseOptions::lenient());
+
+        assert!(matches!(result, Err(Day02Error::Io(_))));
+    }
+
+    #[test]
+    fn should_diff_compare_statuses_with_dampener() {
+        let old = Puzzle::from_values(vec![vec![1, 3, 2, 4, 5], vec![1, 2, 3]]);
+        let new = Puzzle::from_values(vec![vec![1, 3, 2, 4, 9]]);
+
+        assert_eq!(old.diff(&new), PuzzleDiff {
+            only_in_a: vec![1],
+            changed: vec![StatusChange {
+                index: 0,
+                old: ReportStatus::SafeWithDampener,
+                new: ReportStatus::Unsafe
+            }],
+            ..PuzzleDiff::default()
+        });
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn should_from_path_mmap_agree_with_from_path() {
+        for path in ["tests/resources/puzzle.txt", "tests/resources/puzzle_invalid_byte.txt"] {
+            assert_eq!(Puzzle::from_path_mmap(path, ParseOptions::lenient()).unwrap(), Puzzle::from_path(path, ParseOptions::lenient()).unwrap());
+        }
+        assert!(matches!(Puzzle::from_path_mmap("tests/resources/missing.txt", ParseOptions::lenient()), Err(Day02Error::Io(_))));
+    }
+
+    #[test]
+    fn should_add_report_not_shorten_overflowed_report() {
+        let mut puzzle = Puzzle::new();
+        puzzle.add_report("1 3 99999999999 6 7 9");
+
+        assert_eq!(puzzle, Puzzle::new());
+    }
+
+    #[test]
+    fn should_parse_skip_short_reports_when_min_levels_set() {
+        let options = ParseOptions {
+            min_levels: 2,
+            ..ParseOptions::lenient()
+        };
+
+        let (puzzle, summary) = Puzzle::parse("1 2\n5\n\n3 4 5", options).unwrap();
+
+        assert_eq!(puzzle.reports.len(), 2);
+        assert_eq!(summary.short_lines, vec![2]);
+        assert_eq!(summary.blank_lines, 1);
+    }
+
+    #[test]
+    fn should_count_safe_apply_min_levels_and_tolerance() {
+        let (puzzle, _) = Puzzle::parse("5\n1 9\n1 3 2 4 5", ParseOptions::lenient()).unwrap();
+
+        assert_eq!(puzzle.count_safe(&SafetyRules::default(), 0), 0);
+        assert_eq!(puzzle.count_safe(&SafetyRules::default(), 1), 1);
+        assert_eq!(puzzle.count_safe(&SafetyRules { min_levels: 1, ..SafetyRules::default() }, 0), 1);
+        assert_eq!(puzzle.count_safe(&SafetyRules { min_levels: 1, ..SafetyRules::default() }, 1), 3);
+    }
+
+    #[test]
+    fn should_statuses_classify_every_report_in_order() {
+        let (puzzle, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::lenient()).unwrap();
+
+        assert_eq!(puzzle.statuses(&SafetyRules::default(), 1), vec![
+            ReportStatus::Safe,
+            ReportStatus::Unsafe,
+            ReportStatus::Unsafe,
+            ReportStatus::SafeWithDampener,
+            ReportStatus::SafeWithDampener,
+            ReportStatus::Safe
+        ]);
+    }
+
+    #[test]
+    fn should_classify_all_explain_every_unsafe_report() {
+        let (mut puzzle, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::lenient()).unwrap();
+        puzzle.merge(Puzzle::from_values(vec![vec![4]]));
+
+        assert_eq!(puzzle.classify_all(&SafetyRules::default()), vec![
+            None,
+            Some(Violation::Step {
+                index: 1,
+                diff: 5
+            }),
+            Some(Violation::Step {
+                index: 2,
+                diff: 4
+            }),
+            Some(Violation::Direction {
+                index: 1
+            }),
+            Some(Violation::Step {
+                index: 2,
+                diff: 0
+            }),
+            None,
+            Some(Violation::TooShort {
+                levels: 1
+            })
+        ]);
+    }
+
+    #[test]
+    fn should_write_classification_csv_row_per_report() {
+        let (puzzle, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::lenient()).unwrap();
+        let mut csv = Vec::new();
+
+        puzzle.write_classification_csv(&mut csv).unwrap();
+
+        assert_eq!(String::from_utf8(csv).unwrap(), "\
+            line,levels,status,violation,violation_index,removed_index,removed_level\r\n\
+            1,\"7 6 4 2 1\",safe,,,,\r\n\
+            2,\"1 2 7 8 9\",unsafe,step,1,,\r\n\
+            3,\"9 7 6 2 1\",unsafe,step,2,,\r\n\
+            4,\"1 3 2 4 5\",safe_with_dampener,direction,1,1,3\r\n\
+            5,\"8 6 4 4 1\",safe_with_dampener,step,2,2,4\r\n\
+            6,\"1 3 6 7 9\",safe,,,,\r\n");
+    }
+
+    #[test]
+    fn should_write_classification_csv_number_reports_by_input_line() {
+        let (puzzle, _) = Puzzle::parse("7 6 4 2 1\n\n1 2 7 8 9", ParseOptions::lenient()).unwrap();
+        let mut csv = Vec::new();
+
+        puzzle.write_classification_csv(&mut csv).unwrap();
+
+        assert_eq!(String::from_utf8(csv).unwrap(), "\
+            line,levels,status,violation,violation_index,removed_index,removed_level\r\n\
+            1,\"7 6 4 2 1\",safe,,,,\r\n\
+            3,\"1 2 7 8 9\",unsafe,step,1,,\r\n");
+    }
+
+    #[test]
+    fn should_stats_describe_sample_puzzle() {
+        let (puzzle, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::lenient()).unwrap();
+
+        assert_eq!(puzzle.stats(), SafetyStats {
+            total: 6,
+            safe: 2,
+            rescued_by_dampener: 2,
+            unsafe_reports: 2,
+            failures: FailureReasons {
+                too_short: 0,
+                step: 3,
+                direction: 1
+            },
+            step_sizes: BTreeMap::from([(0, 1), (1, 10), (2, 9), (3, 2), (4, 1), (5, 1)]),
+            shortest: Some(5),
+            longest: Some(5)
+        });
+    }
+
+    #[test]
+    fn should_stats_track_lengths_and_short_reports() {
+        let stats = Puzzle::from_values(vec![vec![4], vec![1, 2, 3, 4, 5, 6, 7]]).stats();
+
+        assert_eq!((stats.shortest, stats.longest), (Some(1), Some(7)));
+        assert_eq!(stats.failures.too_short, 1);
+        assert_eq!(Puzzle::new().stats(), SafetyStats::default());
+    }
+
+    #[test]
+    fn should_summary_count_both_parts_in_one_pass() {
+        let (puzzle, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::lenient()).unwrap();
+
+        assert_eq!(puzzle.summary(&SafetyRules::default(), 1), SafetySummary {
+            total: 6,
+            part1: 2,
+            part2: 4,
+            tolerance: 1
+        });
+    }
+
+    #[test]
+    fn should_classification_json_match_sample_schema() {
+        let (puzzle, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::lenient()).unwrap();
+
+        assert_eq!(puzzle.classification_json(), concat!(
+            r#"{"summary":{"total":6,"part1":2,"part2":4,"tolerance":1},"reports":["#,
+            r#"{"levels":[7,6,4,2,1],"status":"safe","violation":null,"violation_index":null,"fix":null},"#,
+            r#"{"levels":[1,2,7,8,9],"status":"unsafe","violation":"step","violation_index":1,"fix":null},"#,
+            r#"{"levels":[9,7,6,2,1],"status":"unsafe","violation":"step","violation_index":2,"fix":null},"#,
+            r#"{"levels":[1,3,2,4,5],"status":"safe_with_dampener","violation":"direction","violation_index":1,"fix":{"removed_index":1,"removed_level":3}},"#,
+            r#"{"levels":[8,6,4,4,1],"status":"safe_with_dampener","violation":"step","violation_index":2,"fix":{"removed_index":2,"removed_level":4}},"#,
+            r#"{"levels":[1,3,6,7,9],"status":"safe","violation":null,"violation_index":null,"fix":null}"#,
+            r#"]}"#
+        ));
+    }
+
+    #[test]
+    fn should_write_classification_match_golden_file() {
+        let (puzzle, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::lenient()).unwrap();
+        let mut output = Vec::new();
+
+        puzzle.write_classification(&mut output, 1).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), read_to_string("tests/resources/puzzle_classification.txt").unwrap());
+    }
+
+    #[test]
+    fn should_write_classification_report_unfixable_reports_when_no_tolerance() {
+        let (puzzle, _) = Puzzle::parse("1 3 2 4 5\n5", ParseOptions::lenient()).unwrap();
+        let mut output = Vec::new();
+
+        puzzle.write_classification(&mut output, 0).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "1 3 2 4 5    # unsafe: direction change at pair 2\n5    # unsafe: only 1 level(s)\n");
+    }
+
+    #[test]
+    fn should_write_classification_keep_source_lines_and_name_every_removed_level() {
+        let (puzzle, _) = Puzzle::parse("7  6 4 2 1\r\n\nfoo\n1 9 2 8 3", ParseOptions::lenient()).unwrap();
+        let mut output = Vec::new();
+
+        puzzle.write_classification(&mut output, 2).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "7  6 4 2 1    # safe\r\n\nfoo    # skipped\n1 9 2 8 3    # safe with dampener (remove levels 2, 4)\n"
+        );
+    }
+
+    #[test]
+    fn should_render_sparklines_with_status_up_to_limit() {
+        let (puzzle, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::lenient()).unwrap();
+
+        assert_eq!(puzzle.render_sparklines(3), "█▆▄▂▁  safe\n▁▁▆▇█  unsafe\n█▆▅▁▁  unsafe\n");
+    }
+
+    #[test]
+    fn should_export_dampener_fixes_csv_list_only_fixed_reports() {
+        let (puzzle, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::lenient()).unwrap();
+        let mut output = Vec::new();
+
+        puzzle.export_dampener_fixes_csv(&mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), concat!(
+            "line,levels,removed_index,removed_level,repaired_levels\r\n",
+            "4,\"1 3 2 4 5\",1,3,\"1 2 4 5\"\r\n",
+            "5,\"8 6 4 4 1\",2,4,\"8 6 4 1\"\r\n"
+        ));
+    }
+
+    #[test]
+    fn should_export_dampener_fixes_csv_number_reports_by_input_line() {
+        let (puzzle, _) = Puzzle::parse("7 6 4 2 1\n\n1 x\n1 3 2 4 5", ParseOptions::lenient()).unwrap();
+        let mut output = Vec::new();
+
+        puzzle.export_dampener_fixes_csv(&mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), concat!(
+            "line,levels,removed_index,removed_level,repaired_levels\r\n",
+            "4,\"1 3 2 4 5\",1,3,\"1 2 4 5\"\r\n"
+        ));
+    }
+
+    #[test]
+    fn should_count_safe_remember_count_per_rules_and_tolerance() {
+        let (puzzle, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::lenient()).unwrap();
+
+        assert_eq!(puzzle.counts.get(&SafetyRules::default(), 1), None);
+        assert_eq!(puzzle.count_safe(&SafetyRules::default(), 1), 4);
+        assert_eq!(puzzle.counts.get(&SafetyRules::default(), 1), Some(4));
+        assert_eq!(puzzle.counts.get(&SafetyRules::default(), 0), None);
+        assert_eq!(puzzle.count_safe(&SafetyRules::default(), 1), 4);
+    }
+
+    #[test]
+    fn should_mutation_forget_remembered_counts() {
+        let rules = SafetyRules::default();
+        let mut puzzle = Puzzle::parse("1 2 3\n1 2 3\n1 5 9", ParseOptions::lenient()).unwrap().0;
+        assert_eq!(puzzle.count_safe(&rules, 0), 2);
+
+        puzzle.add_report("4 5 6");
+        assert_eq!(puzzle.count_safe(&rules, 0), 3);
+
+        puzzle.dedup_reports();
+        assert_eq!(puzzle.count_safe(&rules, 0), 2);
+
+        puzzle.merge(Puzzle::parse("9 8 7", ParseOptions::lenient()).unwrap().0);
+        assert_eq!(puzzle.count_safe(&rules, 0), 3);
+
+        puzzle.retain(|levels| levels[0] != 1);
+        assert_eq!(puzzle.count_safe(&rules, 0), 2);
+    }
+
+    #[test]
+    fn should_parse_strict_all_report_every_bad_line() {
+        let input = read_to_string("tests/resources/puzzle_three_errors.txt").unwrap();
+
+        let errors = Puzzle::parse_strict_all(&input).unwrap_err();
+
+        assert_eq!(errors, vec![
+            ParseError::InvalidToken {
+                line: 2,
+                token: "x".to_string()
+            },
+            ParseError::Overflow {
+                line: 4,
+                token: "99999999999".to_string()
+            },
+            ParseError::InvalidToken {
+                line: 7,
+                token: "3.5".to_string()
+            }
+        ]);
+    }
+
+    #[test]
+    fn should_parse_strict_all_match_strict_parse_when_valid() {
+        let input = read_to_string("tests/resources/puzzle.txt").unwrap();
+
+        assert_eq!(Puzzle::parse_strict_all(&input), Ok(Puzzle::parse(&input, ParseOptions::strict()).unwrap().0));
+    }
+
+    #[test]
+    fn should_reload_incremental_match_fresh_parse() {
+        let mut text = Vec::new();
+        generate::write_to(&generate::generate_puzzle(&GeneratorConfig::default()), &mut text).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        let mut lines: Vec<&str> = text.lines().collect();
+        let mut puzzle = Puzzle::new();
+
+        assert_eq!(puzzle.reload_incremental(&text), ReloadStats {
+            reused: 0,
+            reparsed: 1000,
+            removed: 0
+        });
+
+        lines[10] = "1 2 3 4 5";
+        lines.remove(500);
+        lines.insert(700, "x");
+        lines.insert(0, "9 7 6 2 1");
+        let edited = lines.join("\n");
+        let stats = puzzle.reload_incremental(&edited);
+
+        assert_eq!(stats, ReloadStats {
+            reused: 998,
+            reparsed: 3,
+            removed: 2
+        });
+        let (fresh, _) = Puzzle::parse(&edited, ParseOptions::lenient()).unwrap();
+        assert_eq!(puzzle, fresh);
+        for tolerance in 0..3 {
+            assert_eq!(puzzle.count_safe(&SafetyRules::default(), tolerance), fresh.count_safe(&SafetyRules::default(), tolerance));
+        }
+        assert_eq!(puzzle.statuses(&SafetyRules::default(), 1), fresh.statuses(&SafetyRules::default(), 1));
+    }
+
+    #[test]
+    fn should_reload_incremental_reparse_after_other_changes() {
+        let mut puzzle = Puzzle::new();
+        puzzle.reload_incremental("7 6 4 2 1\n1 3 2 4 5");
+        puzzle.add_report("1 2 7 8 9");
+
+        let stats = puzzle.reload_incremental("7 6 4 2 1\n1 3 2 4 5");
+
+        assert_eq!(stats.reparsed, 2);
+        assert_eq!(puzzle.count_safe(&SafetyRules::default(), 1), 2);
+    }
+
+    #[test]
+    fn should_count_safe_with_policy_use_custom_policy() {
+        let (puzzle, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::strict()).unwrap();
+        let even_sum = |levels: &[u32]| levels.iter().sum::<u32>() % 2 == 0;
+
+        assert_eq!(puzzle.count_safe_with_policy(&even_sum, 0), 2);
+        assert_eq!(puzzle.count_safe_with_policy(&even_sum, 1), 6);
+    }
+
+    #[test]
+    fn should_count_safe_with_policy_match_count_safe_for_builtin_rules() {
+        let puzzle = generate::generate_puzzle(&GeneratorConfig::default());
+        let policies: Vec<Box<dyn SafetyPolicy>> = vec![Box::new(SafetyRules::default()), Box::new(SafetyRules {
+            min_levels: 6,
+            ..SafetyRules::default()
+        })];
+
+        for (policy, rules) in policies.iter().zip([SafetyRules::default(), SafetyRules { min_levels: 6, ..SafetyRules::default() }]) {
+            for tolerance in 0..3 {
+                assert_eq!(puzzle.count_safe_with_policy(policy.as_ref(), tolerance), puzzle.count_safe(&rules, tolerance));
+            }
+        }
+    }
+
+    #[test]
+    fn should_iterate_reports_built_from_own_data() {
+        let puzzle = Puzzle::from_reports(vec![Report::new(vec![7, 6, 4, 2, 1]), Report::new(vec![1, 3, 2, 4, 5]), Report::new(vec![1, 2, 7, 8, 9])]);
+
+        assert_eq!(puzzle.len(), 3);
+        assert_eq!(puzzle.iter().map(Report::levels).collect::<Vec<_>>(), vec![&[7, 6, 4, 2, 1][..], &[1, 3, 2, 4, 5], &[1, 2, 7, 8, 9]]);
+        assert_eq!(puzzle.safe_reports(0).count(), 1);
+        assert_eq!((&puzzle).into_iter().filter(|report| report.is_safe(1)).count(), 2);
+        assert_eq!(puzzle.reports()[1].clone().into_levels(), vec![1, 3, 2, 4, 5]);
+    }
+
+    #[test]
+    fn should_values_round_trip_and_count_like_parsed_puzzle() {
+        let (parsed, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::strict()).unwrap();
+        let values = vec![
+            vec![7, 6, 4, 2, 1],
+            vec![1, 2, 7, 8, 9],
+            vec![9, 7, 6, 2, 1],
+            vec![1, 3, 2, 4, 5],
+            vec![8, 6, 4, 4, 1],
+            vec![1, 3, 6, 7, 9]
+        ];
+
+        let puzzle = Puzzle::from(values.clone());
+
+        assert_eq!(puzzle, parsed);
+        assert_eq!(puzzle.count_safe(&SafetyRules::default(), 0), parsed.count_safe(&SafetyRules::default(), 0));
+        assert_eq!(puzzle.count_safe(&SafetyRules::default(), 1), 4);
+        assert_eq!(puzzle.into_values(), values);
+    }
+
+    #[test]
+    fn should_from_values_treat_empty_reports_like_blank_lines() {
+        let values = vec![vec![1, 2], vec![], vec![3]];
+
+        assert_eq!(Puzzle::from_values(values.clone()).into_values(), vec![vec![1, 2], vec![3]]);
+        assert_eq!(Puzzle::try_from_values(values.clone(), ParseOptions::strict()), Err(ParseError::TooShort {
+            line: 2,
+            levels: 0
+        }));
+        assert_eq!(Puzzle::try_from_values(values, ParseOptions {
+            min_levels: 2,
+            ..ParseOptions::lenient()
+        }).unwrap().into_values(), vec![vec![1, 2]]);
+    }
+
+    #[test]
+    fn should_fingerprint_depend_on_levels_only() {
+        let (puzzle, _) = Puzzle::parse("7 6 4 2 1\n1 3 2 4 5", ParseOptions::strict()).unwrap();
+        let (spaced, _) = Puzzle::parse("  7 6  4 2 1\n\n1 3 2 4 5\n\n", ParseOptions::lenient()).unwrap();
+        let (changed, _) = Puzzle::parse("7 6 4 2 1\n1 3 2 4 6", ParseOptions::strict()).unwrap();
+        let (regrouped, _) = Puzzle::parse("7 6 4 2\n1 1 3 2 4 5", ParseOptions::strict()).unwrap();
+
+        assert_eq!(puzzle.fingerprint(), spaced.fingerprint());
+        assert_ne!(puzzle.fingerprint(), changed.fingerprint());
+        assert_ne!(puzzle.fingerprint(), regrouped.fingerprint());
+        assert_eq!(Puzzle::new().fingerprint(), 0xCBF2_9CE4_8422_2325);
+    }
+
+    #[test]
+    fn should_group_by_direction_split_sample() {
+        let (puzzle, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::strict()).unwrap();
+
+        let breakdown = puzzle.group_by_direction();
+
+        assert_eq!(breakdown.descending, DirectionGroup {
+            indices: vec![0, 2, 4],
+            safe: 1,
+            safe_with_dampener: 2
+        });
+        assert_eq!(breakdown.ascending, DirectionGroup {
+            indices: vec![1, 5],
+            safe: 1,
+            safe_with_dampener: 1
+        });
+        assert_eq!(breakdown.mixed.indices, vec![3]);
+        assert_eq!(breakdown.flat, DirectionGroup::default());
+        let groups = [&breakdown.ascending, &breakdown.descending, &breakdown.mixed, &breakdown.flat];
+        assert_eq!(groups.iter().map(|group| group.safe).sum::<usize>(), 2);
+        assert_eq!(groups.iter().map(|group| group.safe_with_dampener).sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn should_rank_by_fixability_put_closest_to_safe_first() {
+        let (puzzle, _) = Puzzle::parse("1 9 20 30 40\n1 9 2 8 3\n7 6 4 2 1\n1 3 2 4 5", ParseOptions::lenient()).unwrap();
+
+        assert_eq!(puzzle.rank_by_fixability(2), vec![(3, 1), (1, 2), (0, 3)]);
+    }
+
+    #[test]
+    #[ignore = "walks u32::MAX + 10 reports, run with --release"]
+    fn should_count_safe_not_wrap_past_u32_max() {
+        let report = Report::new(vec![1, 2]);
+        let total = u32::MAX as usize + 10;
+
+        assert_eq!(count_safe(std::iter::repeat_n(&report, total), &SafetyRules::default(), 0), total);
+    }
+}
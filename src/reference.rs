@@ -0,0 +1,121 @@
+//! A deliberately naive implementation of the rules, to cross-check the real
+//! one against. Every subset of up to `tolerance` removals is tried, each
+//! with a plain loop over the pairs; nothing is clever and nothing is fast.
+
+/// Under the default rules: at least two levels, all increasing or all
+/// decreasing by 1 to 3.
+pub fn is_safe(levels: &[u32]) -> bool {
+    let increasing = levels.windows(2).all(|pair| pair[0] < pair[1] && pair[1] - pair[0] <= 3);
+    let decreasing = levels.windows(2).all(|pair| pair[0] > pair[1] && pair[0] - pair[1] <= 3);
+
+    levels.len() >= 2 && (increasing || decreasing)
+}
+
+pub fn is_safe_with_tolerance(levels: &[u32], tolerance: u32) -> bool {
+    is_safe_removing_from(levels, 0, tolerance)
+}
+
+/// Removes levels at `from` or after only, so each subset is tried once.
+fn is_safe_removing_from(levels: &[u32], from: usize, tolerance: u32) -> bool {
+    if is_safe(levels) {
+        return true;
+    }
+    tolerance > 0 && (from..levels.len()).any(|index| {
+        let mut rest = levels.to_vec();
+        rest.remove(index);
+        is_safe_removing_from(&rest, index, tolerance - 1)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        classify_slice, is_safe_iter, is_safe_iter_trimming_edges, is_safe_slice, is_safe_slice_with_tolerance, report::Report, rng::Rng,
+        ReportStatus, SafetyRules
+    };
+
+    const CASES: usize = 5000;
+
+    /// Small values and short lengths, so equal pairs, big steps, direction
+    /// changes and too short reports all come up often.
+    fn random_reports(seed: u64) -> impl Iterator<Item = Vec<u32>> {
+        let mut rng = Rng::seeded(seed);
+        (0..CASES).map(move |_| {
+            let len = rng.below(0..10) as usize;
+            (0..len).map(|_| rng.below(0..12) as u32).collect()
+        })
+    }
+
+    #[test]
+    fn should_is_safe_slice_agree_with_reference() {
+        for levels in random_reports(1) {
+            assert_eq!(is_safe_slice(&levels, &SafetyRules::default()), is_safe(&levels), "report {levels:?}");
+        }
+    }
+
+    #[test]
+    fn should_is_safe_iter_agree_with_reference() {
+        for levels in random_reports(4) {
+            assert_eq!(is_safe_iter(levels.iter().copied(), &SafetyRules::default()), is_safe(&levels), "report {levels:?}");
+        }
+    }
+
+    #[test]
+    fn should_is_safe_iter_trimming_edges_agree_with_reference() {
+        for levels in random_reports(5) {
+            let expected = is_safe(&levels)
+                || levels.len() > 1 && (is_safe(&levels[1..]) || is_safe(&levels[..levels.len() - 1]));
+
+            assert_eq!(is_safe_iter_trimming_edges(levels.iter().copied(), &SafetyRules::default()), expected, "report {levels:?}");
+        }
+    }
+
+    #[test]
+    fn should_is_safe_slice_with_tolerance_agree_with_reference() {
+        for levels in random_reports(2) {
+            for tolerance in 0..4 {
+                assert_eq!(
+                    is_safe_slice_with_tolerance(&levels, &SafetyRules::default(), tolerance),
+                    is_safe_with_tolerance(&levels, tolerance),
+                    "report {levels:?} with tolerance {tolerance}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn should_report_is_safe_with_tolerance_agree_with_reference() {
+        for levels in random_reports(6) {
+            let report = Report::new(levels.clone());
+            for tolerance in 0..4 {
+                assert_eq!(
+                    report.is_safe_with_tolerance(&SafetyRules::default(), tolerance),
+                    is_safe_with_tolerance(&levels, tolerance),
+                    "report {levels:?} with tolerance {tolerance}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn should_classify_and_dampener_fix_agree_with_reference() {
+        let rules = SafetyRules::default();
+        for levels in random_reports(3) {
+            let expected = match (is_safe(&levels), is_safe_with_tolerance(&levels, 1)) {
+                (true, _) => ReportStatus::Safe,
+                (false, true) => ReportStatus::SafeWithDampener,
+                (false, false) => ReportStatus::Unsafe
+            };
+            assert_eq!(classify_slice(&levels, &rules, 1), expected, "report {levels:?}");
+
+            let fix = Report::new(levels.clone()).dampener_fix(&rules);
+            assert_eq!(fix.is_some(), expected == ReportStatus::SafeWithDampener, "report {levels:?}");
+            if let Some(fix) = fix {
+                let mut rest = levels.clone();
+                assert_eq!(rest.remove(fix.removed_index), fix.removed_level);
+                assert!(is_safe(&rest), "report {levels:?} without level {}", fix.removed_index);
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! What a puzzle remembers of its input lines between reloads.
+
+use std::fmt;
+
+use crate::ReportStatus;
+
+/// How much of the previous input a reload could keep.
+#[derive(PartialEq, Eq, Clone, Copy, Default)]
+#[derive(Debug)]
+pub struct ReloadStats {
+    /// Lines found unchanged in the previous input, reports and statuses
+    /// kept as they were.
+    pub reused: usize,
+    /// Lines parsed and classified again.
+    pub reparsed: usize,
+    /// Lines of the previous input that are gone.
+    pub removed: usize
+}
+
+/// One input line, known by the hash and length of its bytes.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub(crate) struct IndexedLine {
+    pub(crate) hash: u64,
+    pub(crate) len: usize,
+    /// The status of the line's report under the default rules with the
+    /// Problem Dampener, or `None` for lines that make no report.
+    pub(crate) status: Option<ReportStatus>
+}
+
+/// The lines of the last reload, in order. Methods changing the reports
+/// any other way forget them.
+#[derive(Default)]
+pub(crate) struct LineIndex(pub(crate) Option<Vec<IndexedLine>>);
+
+/// Puzzles are equal when their reports are, however they were loaded.
+impl PartialEq for LineIndex {
+    fn eq(&self, _: &LineIndex) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for LineIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LineIndex")
+    }
+}
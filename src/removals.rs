@@ -0,0 +1,273 @@
+//! How many levels a report has to lose to become safe.
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+use crate::{is_safe_slice, Level, SafetyRules};
+#[cfg(feature = "std")]
+use crate::ReportStatus;
+
+/// The fewest removals that make `levels` safe, or `None` when it takes more
+/// than `cap`.
+///
+/// A safe report is a run of levels whose steps the rules allow, all going
+/// one way when they require it, so this keeps the longest such
+/// subsequence. Two kept levels can only be
+/// more than `cap + 1` apart after more than `cap` removals, so each level
+/// looks back that far at most: O(n·cap), where trying every set of removals
+/// would grow as O(n^cap).
+#[cfg(feature = "alloc")]
+pub fn min_removals_to_safe<L: Level>(levels: &[L], rules: &SafetyRules, cap: u32) -> Option<u32> {
+    min_removals_to_safe_reusing(levels, rules, cap, &mut Vec::new())
+}
+
+/// Like [`min_removals_to_safe`], keeping its working state in `scratch` so
+/// that callers going through many reports allocate only once.
+#[cfg(feature = "alloc")]
+pub(crate) fn min_removals_to_safe_reusing<L: Level>(levels: &[L], rules: &SafetyRules, cap: u32, scratch: &mut Vec<usize>) -> Option<u32> {
+    scratch.resize(removal_window(levels.len(), cap), 0);
+    min_removals_to_safe_in(levels, rules, cap, scratch)
+}
+
+/// Like [`classify_slice`](crate::classify_slice), reusing `scratch` as
+/// [`min_removals_to_safe_reusing`] does.
+#[cfg(feature = "std")]
+pub(crate) fn classify_reusing<L: Level>(levels: &[L], rules: &SafetyRules, tolerance: u32, scratch: &mut Vec<usize>) -> ReportStatus {
+    match min_removals_to_safe_reusing(levels, rules, tolerance, scratch) {
+        Some(0) => ReportStatus::Safe,
+        Some(_) => ReportStatus::SafeWithDampener,
+        None => ReportStatus::Unsafe
+    }
+}
+
+/// How many entries of scratch [`min_removals_to_safe_in`] needs for a
+/// report of `len` levels: only the levels a level may look back to, as
+/// that is all the search remembers.
+pub(crate) fn removal_window(len: usize, cap: u32) -> usize {
+    len.min(reach(cap))
+}
+
+/// How far back a kept level may look for the previous one.
+fn reach(cap: u32) -> usize {
+    usize::try_from(cap).unwrap_or(usize::MAX).saturating_add(1)
+}
+
+/// Like [`min_removals_to_safe`], keeping its working state in `scratch`,
+/// which must hold at least [`removal_window`] entries, so that it needs no
+/// allocator at all.
+pub(crate) fn min_removals_to_safe_in<L: Level>(levels: &[L], rules: &SafetyRules, cap: u32, scratch: &mut [usize]) -> Option<u32> {
+    // Most reports are settled by one pass over their pairs, without the
+    // search below.
+    if is_safe_slice(levels, rules) {
+        return Some(0);
+    }
+    if cap == 0 {
+        return None;
+    }
+    let reach = reach(cap);
+    let scratch = &mut scratch[..removal_window(levels.len(), cap)];
+    let longest = if rules.require_monotonic {
+        longest_safe_run(levels, rules, reach, |value, next_value| next_value >= value, scratch)
+        .max(longest_safe_run(levels, rules, reach, |value, next_value| next_value <= value, scratch))
+    } else {
+        longest_safe_run(levels, rules, reach, |_, _| true, scratch)
+    };
+    if longest == 0 || longest < rules.min_levels {
+        return None;
+    }
+    u32::try_from(levels.len() - longest).ok().filter(|&removals| removals <= cap)
+}
+
+/// The indices, ascending, of one of the smallest sets of levels whose
+/// removal makes `levels` safe: empty when it is safe already, `None` when it
+/// takes more than `cap` removals. The search is that of
+/// [`min_removals_to_safe`], remembering which levels the longest safe
+/// subsequence kept.
+#[cfg(feature = "alloc")]
+pub(crate) fn min_removal_indices<L: Level>(levels: &[L], rules: &SafetyRules, cap: u32) -> Option<Vec<usize>> {
+    if is_safe_slice(levels, rules) {
+        return Some(Vec::new());
+    }
+    if cap == 0 {
+        return None;
+    }
+    let cap = usize::try_from(cap).unwrap_or(usize::MAX);
+    let reach = cap.saturating_add(1);
+    let kept = if rules.require_monotonic {
+        let ascending = longest_safe_run_indices(levels, rules, reach, |value, next_value| next_value >= value);
+        let descending = longest_safe_run_indices(levels, rules, reach, |value, next_value| next_value <= value);
+        if descending.len() > ascending.len() { descending } else { ascending }
+    } else {
+        longest_safe_run_indices(levels, rules, reach, |_, _| true)
+    };
+    if kept.is_empty() || kept.len() < rules.min_levels || levels.len() - kept.len() > cap {
+        return None;
+    }
+    Some((0..levels.len()).filter(|index| kept.binary_search(index).is_err()).collect())
+}
+
+/// The indices, ascending, of a subsequence as long as the one
+/// [`longest_safe_run`] measures.
+#[cfg(feature = "alloc")]
+fn longest_safe_run_indices<L: Level>(levels: &[L], rules: &SafetyRules, reach: usize, forward: impl Fn(L, L) -> bool) -> Vec<usize> {
+    let mut longest_ending_at = vec![0; levels.len()];
+    let mut kept_before: Vec<Option<usize>> = vec![None; levels.len()];
+    for (index, &level) in levels.iter().enumerate() {
+        let best = (index.saturating_sub(reach)..index)
+        .filter(|&previous| forward(levels[previous], level) && rules.allows_step(levels[previous], level))
+        .max_by_key(|&previous| longest_ending_at[previous]);
+        longest_ending_at[index] = best.map_or(0, |previous| longest_ending_at[previous]) + 1;
+        kept_before[index] = best;
+    }
+    let mut kept = Vec::new();
+    let mut next = (0..levels.len()).max_by_key(|&index| longest_ending_at[index]);
+    while let Some(index) = next {
+        kept.push(index);
+        next = kept_before[index];
+    }
+    kept.reverse();
+    kept
+}
+
+/// The longest subsequence whose pairs all go `forward` by steps `rules`
+/// allow and are at most `reach` positions apart. `longest_ending_at` keeps
+/// the last levels' runs in a ring, each level reading the runs it may
+/// extend before overwriting the oldest.
+fn longest_safe_run<L: Level>(levels: &[L], rules: &SafetyRules, reach: usize, forward: impl Fn(L, L) -> bool, longest_ending_at: &mut [usize]) -> usize {
+    let window = longest_ending_at.len();
+    let mut longest = 0;
+    for (index, &level) in levels.iter().enumerate() {
+        let run = (index.saturating_sub(reach)..index)
+        .filter(|&previous| forward(levels[previous], level) && rules.allows_step(levels[previous], level))
+        .map(|previous| longest_ending_at[previous % window])
+        .max()
+        .unwrap_or(0)
+            + 1;
+        longest_ending_at[index % window] = run;
+        longest = longest.max(run);
+    }
+    longest
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// Whether removing some set of at most `tolerance` levels makes
+    /// `levels` safe, trying every set: the definition the search must match.
+    fn brute_force_safe(levels: &[u32], rules: &SafetyRules, tolerance: u32) -> bool {
+        if is_safe_slice(levels, rules) {
+            return true;
+        }
+        tolerance > 0 && (0..levels.len()).any(|index| {
+            let mut rest = levels.to_vec();
+            rest.remove(index);
+            brute_force_safe(&rest, rules, tolerance - 1)
+        })
+    }
+
+    #[test]
+    fn should_min_removals_to_safe_count_removals() {
+        let rules = SafetyRules::default();
+
+        assert_eq!(min_removals_to_safe(&[7, 6, 4, 2, 1], &rules, 3), Some(0));
+        assert_eq!(min_removals_to_safe(&[1, 3, 2, 4, 5], &rules, 3), Some(1));
+        assert_eq!(min_removals_to_safe(&[1, 9, 2, 8, 3], &rules, 3), Some(2));
+        assert_eq!(min_removals_to_safe(&[1, 9, 2, 8, 3], &rules, 1), None);
+    }
+
+    #[test]
+    fn should_min_removals_to_safe_respect_min_levels() {
+        assert_eq!(min_removals_to_safe(&[1, 9], &SafetyRules::default(), 5), None);
+        assert_eq!(min_removals_to_safe(&[1, 9], &SafetyRules { min_levels: 1, ..SafetyRules::default() }, 5), Some(1));
+        assert_eq!(min_removals_to_safe::<u32>(&[], &SafetyRules { min_levels: 0, ..SafetyRules::default() }, 5), None);
+    }
+
+    #[test]
+    fn should_min_removals_to_safe_handle_long_hopeless_report() {
+        let levels: Vec<u32> = (0..1000).map(|index| if index % 2 == 0 { 0 } else { 100 }).collect();
+
+        assert_eq!(min_removals_to_safe(&levels, &SafetyRules::default(), 3), None);
+    }
+
+    #[test]
+    fn should_min_removals_to_safe_in_need_only_scratch_for_its_window() {
+        let levels = [1, 9, 2, 3, 8, 4, 5, 6, 7];
+        let rules = SafetyRules::default();
+
+        assert_eq!(removal_window(levels.len(), 2), 3);
+        assert_eq!(min_removals_to_safe_in(&levels, &rules, 2, &mut [0; 3]), Some(2));
+        assert_eq!(min_removals_to_safe_in(&levels, &rules, 1, &mut [0; 2]), None);
+        assert_eq!(removal_window(4, u32::MAX), 4);
+    }
+
+    #[test]
+    fn should_min_removals_to_safe_handle_large_cap_on_long_report() {
+        let mut levels: Vec<u32> = (0..1000).map(|level| 1000 + level).collect();
+        for index in (0..1000).step_by(25) {
+            levels[index] = 0;
+        }
+
+        assert_eq!(min_removals_to_safe(&levels, &SafetyRules::default(), 50), Some(40));
+        assert_eq!(min_removals_to_safe(&levels, &SafetyRules::default(), 39), None);
+    }
+
+    #[test]
+    fn should_min_removals_to_safe_agree_with_tolerance_search() {
+        let rules = SafetyRules::default();
+        let mut rng = Rng::seeded(5);
+        for _ in 0..2000 {
+            let len = rng.below(0..9) as usize;
+            let levels: Vec<u32> = (0..len).map(|_| rng.below(0..10) as u32).collect();
+            let expected = (0..=3).find(|&tolerance| brute_force_safe(&levels, &rules, tolerance));
+
+            assert_eq!(min_removals_to_safe(&levels, &rules, 3), expected, "report {levels:?}");
+        }
+    }
+
+    #[test]
+    fn should_min_removal_indices_name_levels_whose_removal_makes_report_safe() {
+        let rules = SafetyRules::default();
+        let mut rng = Rng::seeded(7);
+        for _ in 0..2000 {
+            let len = rng.below(0..12) as usize;
+            let levels: Vec<u32> = (0..len).map(|_| rng.below(0..10) as u32).collect();
+            let indices = min_removal_indices(&levels, &rules, 4);
+
+            assert_eq!(indices.as_ref().map(|indices| indices.len() as u32), min_removals_to_safe(&levels, &rules, 4), "report {levels:?}");
+            if let Some(indices) = indices {
+                let kept: Vec<u32> = levels.iter().enumerate().filter(|(index, _)| !indices.contains(index)).map(|(_, &level)| level).collect();
+
+                assert!(is_safe_slice(&kept, &rules), "report {levels:?} without {indices:?}");
+                assert!(indices.windows(2).all(|pair| pair[0] < pair[1]), "{indices:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn should_min_removals_to_safe_agree_with_tolerance_search_under_other_rules() {
+        let variants = [
+            SafetyRules {
+                max_step: 5,
+                allow_equal: true,
+                ..SafetyRules::default()
+            },
+            SafetyRules {
+                min_step: 2,
+                require_monotonic: false,
+                ..SafetyRules::default()
+            }
+        ];
+        let mut rng = Rng::seeded(6);
+        for rules in variants {
+            for _ in 0..2000 {
+                let len = rng.below(0..9) as usize;
+                let levels: Vec<u32> = (0..len).map(|_| rng.below(0..10) as u32).collect();
+                let expected = (0..=3).find(|&tolerance| brute_force_safe(&levels, &rules, tolerance));
+
+                assert_eq!(min_removals_to_safe(&levels, &rules, 3), expected, "report {levels:?} under {rules:?}");
+            }
+        }
+    }
+}
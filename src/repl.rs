@@ -0,0 +1,174 @@
+//! `day_02 repl`: type a report, get its classification and why.
+//!
+//! Lines starting with `:` are commands: `:rules min=A max=B min_levels=N
+//! monotonic=true|false equal=true|false` changes the rules of the following
+//! reports, any of the settings being optional, `min` and `max` bounding the
+//! steps between levels, `monotonic` requiring them to go one way and
+//! `equal` allowing equal neighbours, and `:quit` leaves, as does the end of
+//! the input. Reports are classified with the Problem Dampener on.
+
+use std::io::{self, BufRead, Write};
+
+use crate::{
+    parse::{parse_line, Location},
+    puzzle::violation_text,
+    report::Report,
+    ParseOptions, ParseSummary, ReportStatus, SafetyRules
+};
+
+const PROMPT: &str = "> ";
+
+/// Answers every line of `input` on `out` until `:quit` or the end of the
+/// input. Lines that do not parse are answered with the reason.
+pub fn repl(input: impl BufRead, mut out: impl Write) -> io::Result<()> {
+    let mut rules = SafetyRules::default();
+    write!(out, "{PROMPT}")?;
+    out.flush()?;
+    for (index, line) in input.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        match line.strip_prefix(':') {
+            Some("quit") => return Ok(()),
+            Some(command) => match command.strip_prefix("rules") {
+                Some(settings) => match parse_rules(settings, rules) {
+                    Ok(new_rules) => {
+                        rules = new_rules;
+                        writeln!(out, "rules: {}", rules_text(&rules))?;
+                    }
+                    Err(message) => writeln!(out, "{message}")?
+                },
+                None => writeln!(out, "unknown command `:{command}`")?
+            },
+            None => {
+                let location = Location {
+                    line: index + 1,
+                    offset: 0
+                };
+                match parse_line(line.as_bytes(), location, &ParseOptions::strict(), &mut ParseSummary::default()) {
+                    Ok(Some(levels)) => writeln!(out, "{}", explain(&Report::new(levels), &rules))?,
+                    Ok(None) => {}
+                    Err(error) => writeln!(out, "cannot parse: {error}")?
+                }
+            }
+        }
+        write!(out, "{PROMPT}")?;
+        out.flush()?;
+    }
+    writeln!(out)
+}
+
+/// `rules` with the `key=value` settings applied. `min_step`, `max_step`,
+/// `require_monotonic` and `allow_equal` may also be spelled out.
+fn parse_rules(settings: &str, mut rules: SafetyRules) -> Result<SafetyRules, String> {
+    for setting in settings.split_whitespace() {
+        let (key, value) = setting.split_once('=').ok_or_else(|| format!("expected `key=value`, got `{setting}`"))?;
+        let invalid = || format!("invalid value `{value}` for `{key}`");
+        match key {
+            "min_levels" => rules.min_levels = value.parse().map_err(|_| invalid())?,
+            "min" | "min_step" => rules.min_step = value.parse().map_err(|_| invalid())?,
+            "max" | "max_step" => rules.max_step = value.parse().map_err(|_| invalid())?,
+            "monotonic" | "require_monotonic" => rules.require_monotonic = value.parse().map_err(|_| invalid())?,
+            "equal" | "allow_equal" => rules.allow_equal = value.parse().map_err(|_| invalid())?,
+            _ => return Err(format!("unknown rule `{key}`"))
+        }
+    }
+    if rules.min_step > rules.max_step {
+        return Err(format!("`min` ({}) cannot exceed `max` ({})", rules.min_step, rules.max_step));
+    }
+    Ok(rules)
+}
+
+/// The settings of `rules`, as `:rules` takes them.
+fn rules_text(rules: &SafetyRules) -> String {
+    format!(
+        "min={} max={} min_levels={} monotonic={} equal={}",
+        rules.min_step,
+        rules.max_step,
+        rules.min_levels,
+        rules.require_monotonic,
+        rules.allow_equal
+    )
+}
+
+fn explain(report: &Report, rules: &SafetyRules) -> String {
+    let violation = report.violation(rules).map(violation_text);
+    match (report.classify(rules, 1), violation) {
+        (ReportStatus::Safe, _) => "safe".to_string(),
+        (ReportStatus::SafeWithDampener, violation) => match report.dampener_fix(rules) {
+            Some(fix) => format!(
+                "safe with dampener: {}, fixed by removing level {} ({})",
+                violation.unwrap_or_default(),
+                fix.removed_index + 1,
+                fix.removed_level
+            ),
+            None => "safe with dampener".to_string()
+        },
+        (ReportStatus::Unsafe, Some(violation)) => format!("unsafe: {violation}"),
+        (ReportStatus::Unsafe, None) => "unsafe".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcript(input: &str) -> String {
+        let mut out = Vec::new();
+        repl(input.as_bytes(), &mut out).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn should_repl_explain_each_report() {
+        assert_eq!(transcript("7 6 4 2 1\n1 3 2 4 5\n9 7 6 2 1\n\n"), "\
+            > safe\n\
+            > safe with dampener: direction change at pair 2, fixed by removing level 2 (3)\n\
+            > unsafe: diff 4 at pair 3\n\
+            > > \n");
+    }
+
+    #[test]
+    fn should_repl_change_rules_and_quit() {
+        assert_eq!(transcript("5\n:rules min_levels=1\n5\n:quit\n5\n"), "\
+            > unsafe: only 1 level(s)\n\
+            > rules: min=1 max=3 min_levels=1 monotonic=true equal=false\n\
+            > safe\n\
+            > ");
+    }
+
+    #[test]
+    fn should_repl_change_step_bounds() {
+        assert_eq!(transcript("1 2 7 8 9\n:rules min=1 max=5\n1 2 7 8 9\n1 3 5\n:rules min_step=2\n1 3 5\n1 2 4\n"), "\
+            > unsafe: diff 5 at pair 2\n\
+            > rules: min=1 max=5 min_levels=2 monotonic=true equal=false\n\
+            > safe\n\
+            > safe\n\
+            > rules: min=2 max=5 min_levels=2 monotonic=true equal=false\n\
+            > safe\n\
+            > safe with dampener: diff 1 at pair 1, fixed by removing level 1 (1)\n\
+            > \n");
+    }
+
+    #[test]
+    fn should_repl_switch_direction_and_equal_rules() {
+        assert_eq!(transcript(":rules monotonic=false\n1 3 2 4 5\n:rules equal=true monotonic=true\n8 6 4 4 1\n:rules equal=maybe\n"), "\
+            > rules: min=1 max=3 min_levels=2 monotonic=false equal=false\n\
+            > safe\n\
+            > rules: min=1 max=3 min_levels=2 monotonic=true equal=true\n\
+            > safe\n\
+            > invalid value `maybe` for `equal`\n\
+            > \n");
+    }
+
+    #[test]
+    fn should_repl_answer_bad_lines_and_commands() {
+        assert_eq!(transcript("1 x 2\n:rules steps=3\n:rules min_levels=x\n:rules min=4\n:nope\n"), "\
+            > cannot parse: line 1: `x` is not a level\n\
+            > unknown rule `steps`\n\
+            > invalid value `x` for `min_levels`\n\
+            > `min` (4) cannot exceed `max` (3)\n\
+            > unknown command `:nope`\n\
+            > \n");
+    }
+}
@@ -0,0 +1,351 @@
+#[cfg(feature = "std")]
+use std::str::FromStr;
+
+use alloc::vec::Vec;
+
+use crate::{direction_of, first_violation, is_safe_slice, min_removals_to_safe, removals::min_removal_indices, Direction, Level, ReportStatus, SafetyRules, Violation};
+#[cfg(feature = "std")]
+use crate::{
+    parse::{parse_line_into, Location},
+    sparkline, DampenerFix, ParseError, ParseOptions, ParseSummary
+};
+
+/// The levels of one line of a puzzle. Puzzles are made of `u32` levels;
+/// reports on their own can be of any [`Level`], signed or wider.
+#[derive(PartialEq, Eq, Clone)]
+#[derive(Debug)]
+pub struct Report<L = u32> {
+    pub(crate) values: Vec<L>
+}
+
+impl<L: Level> Report<L> {
+    pub fn new(values: Vec<L>) -> Report<L> {
+        Report {
+            values
+        }
+    }
+
+    pub fn levels(&self) -> &[L] {
+        &self.values
+    }
+
+    pub fn into_levels(self) -> Vec<L> {
+        self.values
+    }
+
+    /// Safe under the default rules once up to `tolerance` levels are
+    /// removed: 0 for part 1, 1 for part 2.
+    pub fn is_safe(&self, tolerance: u32) -> bool {
+        self.is_safe_with_tolerance(&SafetyRules::default(), tolerance)
+    }
+
+    /// Like [`is_safe_slice_with_tolerance`](crate::is_safe_slice_with_tolerance)
+    /// in O(n·tolerance) rather than by trying every set of removals, see
+    /// [`min_removals_to_safe`].
+    pub fn is_safe_with_tolerance(&self, rules: &SafetyRules, tolerance: u32) -> bool {
+        min_removals_to_safe(&self.values, rules, tolerance).is_some()
+    }
+
+    /// Safe as-is under `rules`.
+    pub fn is_safe_with(&self, rules: &SafetyRules) -> bool {
+        is_safe_slice(&self.values, rules)
+    }
+
+    /// The first rule broken before any removal.
+    pub fn violation(&self, rules: &SafetyRules) -> Option<Violation> {
+        first_violation(&self.values, rules)
+    }
+
+    pub fn direction(&self) -> Direction {
+        direction_of(&self.values)
+    }
+
+    pub fn classify(&self, rules: &SafetyRules, tolerance: u32) -> ReportStatus {
+        if self.is_safe_with(rules) {
+            return ReportStatus::Safe;
+        }
+        if self.is_safe_with_tolerance(rules, tolerance) {
+            return ReportStatus::SafeWithDampener;
+        }
+        ReportStatus::Unsafe
+    }
+
+    /// `Some(None)` when safe as-is, `Some(Some(index))` when removing the
+    /// level at `index` makes it safe, and `None` when no single removal
+    /// does. The removal is the one [`Report::minimal_fix`] finds with a
+    /// budget of 1.
+    pub fn safe_with_dampener(&self, rules: &SafetyRules) -> Option<Option<usize>> {
+        min_removal_indices(&self.values, rules, 1).map(|removed| removed.first().copied())
+    }
+
+    /// The indices, ascending, of the fewest levels to remove for the report
+    /// to be safe under `rules`: empty when it already is, `None` when it
+    /// would take more than `budget` removals. Unlike the Problem Dampener,
+    /// any number of levels may go, for repairing noisy traces.
+    pub fn minimal_fix(&self, rules: &SafetyRules, budget: u32) -> Option<Vec<usize>> {
+        min_removal_indices(&self.values, rules, budget)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Report {
+    /// The single removal that makes an unsafe report safe, that of
+    /// [`Report::safe_with_dampener`].
+    pub fn dampener_fix(&self, rules: &SafetyRules) -> Option<DampenerFix> {
+        self.safe_with_dampener(rules)
+        .flatten()
+        .map(|index| DampenerFix {
+            removed_index: index,
+            removed_level: self.values[index]
+        })
+    }
+
+    pub(crate) fn without_level(&self, index: usize) -> Report {
+        let mut values = self.values.clone();
+        values.remove(index);
+
+        Report::new(values)
+    }
+
+    pub(crate) fn sparkline(&self) -> String {
+        sparkline(&self.values)
+    }
+
+    /// The levels as parsed, separated by single spaces.
+    pub(crate) fn levels_text(&self) -> String {
+        let levels: Vec<String> = self.values.iter().map(u32::to_string).collect();
+
+        levels.join(" ")
+    }
+}
+
+impl<L: Level> From<Vec<L>> for Report<L> {
+    fn from(values: Vec<L>) -> Report<L> {
+        Report::new(values)
+    }
+}
+
+/// Parses one line strictly, as line 1 of a puzzle. Signed levels may start
+/// with `-`.
+#[cfg(feature = "std")]
+impl<L: Level> FromStr for Report<L> {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<Report<L>, ParseError> {
+        let location = Location {
+            line: 1,
+            offset: 0
+        };
+        let mut values = Vec::new();
+        if !parse_line_into(line.as_bytes(), location, &ParseOptions::strict(), &mut ParseSummary::default(), &mut values)? {
+            return Err(ParseError::TooShort {
+                line: 1,
+                levels: 0
+            });
+        }
+        Ok(Report::new(values))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    fn rules_with_min_levels(min_levels: usize) -> SafetyRules {
+        SafetyRules {
+            min_levels,
+            ..SafetyRules::default()
+        }
+    }
+
+    #[test]
+    fn should_report_is_safe_return_true_when_one_element() {
+        let report = Report {
+            values: vec![1]
+        };
+        assert!(report.is_safe_with(&rules_with_min_levels(1)))
+    }
+
+    #[test]
+    fn should_report_is_safe_with_tolerance_remove_levels() {
+        assert!(!Report::new(vec![1, 3, 2, 4, 5]).is_safe(0));
+        assert!(Report::new(vec![1, 3, 2, 4, 5]).is_safe(1));
+        assert!(!Report::new(vec![1, 9, 2, 8, 3]).is_safe(1));
+        assert!(Report::new(vec![1, 9, 2, 8, 3]).is_safe(2));
+    }
+
+    #[test]
+    fn should_report_is_not_safe_when_empty() {
+        assert!(!Report::<u32>::new(vec![]).is_safe(0))
+    }
+
+    #[test]
+    fn should_report_is_not_safe_when_empty_whatever_min_levels() {
+        assert!(!Report::<u32>::new(vec![]).is_safe_with(&rules_with_min_levels(0)));
+        assert!(!Report::<u32>::new(vec![]).is_safe_with(&rules_with_min_levels(1)));
+        assert!(!Report::<u32>::new(vec![]).is_safe_with(&rules_with_min_levels(2)));
+    }
+
+    #[test]
+    fn should_report_with_one_level_be_safe_only_when_min_levels_1() {
+        assert!(Report::new(vec![5]).is_safe_with(&rules_with_min_levels(1)));
+        assert!(!Report::new(vec![5]).is_safe_with(&rules_with_min_levels(2)));
+    }
+
+    #[test]
+    fn should_report_with_two_levels_be_safe_when_min_levels_1_or_2() {
+        assert!(Report::new(vec![5, 6]).is_safe_with(&rules_with_min_levels(1)));
+        assert!(Report::new(vec![5, 6]).is_safe_with(&rules_with_min_levels(2)));
+    }
+
+    #[test]
+    fn should_default_rules_reject_one_level_report() {
+        assert!(!Report::new(vec![5]).is_safe(0));
+    }
+
+    #[test]
+    fn should_violation_blame_leading_equal_pair() {
+        assert_eq!(Report::new(vec![2, 2, 3, 4, 5]).violation(&SafetyRules::default()), Some(Violation::Step {
+            index: 0,
+            diff: 0
+        }));
+        assert_eq!(Report::new(vec![5, 5, 4, 3]).violation(&SafetyRules::default()), Some(Violation::Step {
+            index: 0,
+            diff: 0
+        }));
+    }
+
+    #[test]
+    fn should_violation_report_direction_change() {
+        assert_eq!(Report::new(vec![1, 3, 2, 4, 5]).violation(&SafetyRules::default()), Some(Violation::Direction {
+            index: 1
+        }));
+    }
+
+    #[test]
+    fn should_violation_report_too_short() {
+        assert_eq!(Report::new(vec![1]).violation(&SafetyRules::default()), Some(Violation::TooShort {
+            levels: 1
+        }));
+    }
+
+    #[test]
+    fn should_classify_leading_equal_pair_as_safe_with_dampener() {
+        assert_eq!(Report::new(vec![2, 2, 3, 4, 5]).classify(&SafetyRules::default(), 1), ReportStatus::SafeWithDampener);
+        assert_eq!(Report::new(vec![5, 5, 4, 3]).classify(&SafetyRules::default(), 1), ReportStatus::SafeWithDampener);
+    }
+
+    #[test]
+    fn should_classify_safe_and_unsafe_reports() {
+        assert_eq!(Report::new(vec![7, 6, 4, 2, 1]).classify(&SafetyRules::default(), 1), ReportStatus::Safe);
+        assert_eq!(Report::new(vec![1, 2, 7, 8, 9]).classify(&SafetyRules::default(), 1), ReportStatus::Unsafe);
+        assert_eq!(Report::new(vec![2, 2, 3, 4, 5]).classify(&SafetyRules::default(), 0), ReportStatus::Unsafe);
+    }
+
+    #[test]
+    fn should_dampener_fix_remove_first_level_that_makes_report_safe() {
+        assert_eq!(Report::new(vec![1, 3, 2, 4, 5]).dampener_fix(&SafetyRules::default()), Some(DampenerFix {
+            removed_index: 1,
+            removed_level: 3
+        }));
+        assert_eq!(Report::new(vec![8, 6, 4, 4, 1]).dampener_fix(&SafetyRules::default()), Some(DampenerFix {
+            removed_index: 2,
+            removed_level: 4
+        }));
+    }
+
+    #[test]
+    fn should_dampener_fix_be_none_when_safe_or_unfixable() {
+        assert_eq!(Report::new(vec![7, 6, 4, 2, 1]).dampener_fix(&SafetyRules::default()), None);
+        assert_eq!(Report::new(vec![1, 2, 7, 8, 9]).dampener_fix(&SafetyRules::default()), None);
+    }
+
+    #[test]
+    fn should_safe_with_dampener_tell_which_level_was_removed() {
+        assert_eq!(Report::new(vec![7, 6, 4, 2, 1]).safe_with_dampener(&SafetyRules::default()), Some(None));
+        assert_eq!(Report::new(vec![1, 3, 2, 4, 5]).safe_with_dampener(&SafetyRules::default()), Some(Some(1)));
+        assert_eq!(Report::new(vec![3, 1, 2, 4, 5]).safe_with_dampener(&SafetyRules::default()), Some(Some(0)));
+        assert_eq!(Report::new(vec![1, 2, 7, 8, 9]).safe_with_dampener(&SafetyRules::default()), None);
+    }
+
+    #[test]
+    fn should_minimal_fix_name_fewest_levels_to_remove() {
+        let rules = SafetyRules::default();
+
+        assert_eq!(Report::new(vec![7, 6, 4, 2, 1]).minimal_fix(&rules, 3), Some(vec![]));
+        assert_eq!(Report::new(vec![1, 3, 2, 4, 5]).minimal_fix(&rules, 3), Some(vec![1]));
+        assert_eq!(Report::new(vec![1, 9, 2, 8, 3]).minimal_fix(&rules, 3), Some(vec![1, 3]));
+        assert_eq!(Report::new(vec![1, 9, 2, 8, 3]).minimal_fix(&rules, 1), None);
+        assert_eq!(Report::new(vec![50, 1, 2, 3, 90, 4, 5]).minimal_fix(&rules, 2), Some(vec![0, 4]));
+        assert_eq!(Report::new(vec![1, 9]).minimal_fix(&rules, 5), None);
+    }
+
+    #[test]
+    fn should_parse_signed_and_wide_levels() {
+        let report: Report<i64> = "-3 -1 0 2".parse().unwrap();
+
+        assert_eq!(report.levels(), &[-3, -1, 0, 2]);
+        assert!(report.is_safe(0));
+        assert!("5000000000 5000000002 5000000005".parse::<Report<u64>>().unwrap().is_safe(0));
+        assert_eq!("-3 -1 0 2".parse::<Report>(), Err(ParseError::InvalidToken {
+            line: 1,
+            token: "-3".to_string()
+        }));
+        assert_eq!("1 -".parse::<Report<i64>>(), Err(ParseError::InvalidToken {
+            line: 1,
+            token: "-".to_string()
+        }));
+        assert_eq!("".parse::<Report<i64>>(), Err(ParseError::TooShort {
+            line: 1,
+            levels: 0
+        }));
+    }
+
+    #[test]
+    fn should_signed_report_measure_steps_across_zero() {
+        assert_eq!(Report::<i64>::new(vec![-2, 2, 3]).violation(&SafetyRules::default()), Some(Violation::Step {
+            index: 0,
+            diff: 4
+        }));
+        assert_eq!(Report::<i64>::new(vec![i64::MIN, i64::MAX]).violation(&SafetyRules::default()), Some(Violation::Step {
+            index: 0,
+            diff: u32::MAX
+        }));
+        assert_eq!(Report::<i64>::new(vec![-1, 2, 1, 0, -2]).classify(&SafetyRules::default(), 1), ReportStatus::SafeWithDampener);
+        assert_eq!(Report::<i64>::new(vec![-1, 2, 1, 0, -2]).safe_with_dampener(&SafetyRules::default()), Some(Some(0)));
+    }
+
+    mod head_removal_direction_flips {
+        use super::*;
+
+        fn assert_flip_is_rescued(values: Vec<u32>) {
+            let report = Report::new(values);
+
+            assert_eq!(report.classify(&SafetyRules::default(), 0), ReportStatus::Unsafe);
+            assert_eq!(report.classify(&SafetyRules::default(), 1), ReportStatus::SafeWithDampener);
+        }
+
+        #[test]
+        fn should_rescue_descending_head_before_ascending_tail() {
+            assert_flip_is_rescued(vec![3, 1, 2, 4, 5]);
+        }
+
+        #[test]
+        fn should_rescue_ascending_head_before_descending_tail() {
+            assert_flip_is_rescued(vec![9, 12, 11, 10, 8]);
+        }
+
+        #[test]
+        fn should_rescue_ascending_head_jump_before_descending_tail() {
+            assert_flip_is_rescued(vec![1, 5, 4, 3, 2]);
+        }
+
+        #[test]
+        fn should_not_rescue_flip_that_needs_two_removals() {
+            let report = Report::new(vec![3, 1, 0, 4, 5]);
+
+            assert_eq!(report.classify(&SafetyRules::default(), 1), ReportStatus::Unsafe);
+            assert_eq!(report.classify(&SafetyRules::default(), 2), ReportStatus::SafeWithDampener);
+        }
+    }
+}
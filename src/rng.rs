@@ -0,0 +1,37 @@
+//! The deterministic random numbers behind the generators: the same seed
+//! always yields the same sequence, on every platform.
+
+use std::ops::Range;
+
+/// SplitMix64: tiny, fast and good enough to spread test cases around.
+#[derive(PartialEq, Eq, Clone)]
+#[derive(Debug)]
+pub struct Rng {
+    state: u64
+}
+
+impl Rng {
+    pub fn seeded(seed: u64) -> Rng {
+        Rng {
+            state: seed
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        value ^ (value >> 31)
+    }
+
+    /// A value in `range`, which must not be empty.
+    pub fn below(&mut self, range: Range<u64>) -> u64 {
+        assert!(range.start < range.end, "empty range {range:?}");
+        range.start + self.next_u64() % (range.end - range.start)
+    }
+
+    pub fn coin(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+}
@@ -0,0 +1,236 @@
+//! Safety rules as separate predicates that can be mixed with custom ones,
+//! e.g. "no level above 90", and checked together as a [`RuleSet`].
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{SafetyPolicy, SafetyRules};
+
+/// What a [`SafetyRule`] makes of a report.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub enum RuleOutcome {
+    Pass,
+    /// `rule` names the rule that failed. `index` is the first level it
+    /// blames, if any.
+    Fail { rule: &'static str, index: Option<usize> }
+}
+
+impl RuleOutcome {
+    pub fn passed(&self) -> bool {
+        *self == RuleOutcome::Pass
+    }
+}
+
+/// One condition a safe report has to meet. Object safe, so rules can be
+/// kept as `Box<dyn SafetyRule>`.
+pub trait SafetyRule {
+    fn check(&self, levels: &[u32]) -> RuleOutcome;
+}
+
+impl<F: Fn(&[u32]) -> RuleOutcome> SafetyRule for F {
+    fn check(&self, levels: &[u32]) -> RuleOutcome {
+        self(levels)
+    }
+}
+
+/// At least this many levels, and never none.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub struct MinLevels(pub usize);
+
+impl SafetyRule for MinLevels {
+    fn check(&self, levels: &[u32]) -> RuleOutcome {
+        if levels.is_empty() || levels.len() < self.0 {
+            return RuleOutcome::Fail {
+                rule: "too_short",
+                index: None
+            };
+        }
+        RuleOutcome::Pass
+    }
+}
+
+/// Adjacent levels differ by `min` to `max`, or are equal when
+/// `allow_equal` is set.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub struct StepRange {
+    pub min: u32,
+    pub max: u32,
+    pub allow_equal: bool
+}
+
+impl SafetyRule for StepRange {
+    fn check(&self, levels: &[u32]) -> RuleOutcome {
+        let allows = |value: u32, next_value: u32| match value.abs_diff(next_value) {
+            0 => self.allow_equal,
+            diff => diff >= self.min && diff <= self.max
+        };
+        match levels.windows(2).position(|pair| !allows(pair[0], pair[1])) {
+            Some(index) => RuleOutcome::Fail {
+                rule: "step",
+                index: Some(index)
+            },
+            None => RuleOutcome::Pass
+        }
+    }
+}
+
+/// All levels go the way of the first pair that differs. Equal pairs go
+/// neither way.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub struct Monotonic;
+
+impl SafetyRule for Monotonic {
+    fn check(&self, levels: &[u32]) -> RuleOutcome {
+        let mut ascending = None;
+        for (index, pair) in levels.windows(2).enumerate() {
+            if pair[0] == pair[1] {
+                continue;
+            }
+            if *ascending.get_or_insert(pair[1] > pair[0]) != (pair[1] > pair[0]) {
+                return RuleOutcome::Fail {
+                    rule: "direction",
+                    index: Some(index)
+                };
+            }
+        }
+        RuleOutcome::Pass
+    }
+}
+
+/// Rules checked in order, a report being safe when it passes all of them.
+#[derive(Default)]
+pub struct RuleSet {
+    rules: Vec<Box<dyn SafetyRule>>
+}
+
+impl RuleSet {
+    pub fn new() -> RuleSet {
+        RuleSet::default()
+    }
+
+    pub fn with(mut self, rule: impl SafetyRule + 'static) -> RuleSet {
+        self.push(rule);
+        self
+    }
+
+    pub fn push(&mut self, rule: impl SafetyRule + 'static) {
+        self.rules.push(Box::new(rule));
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The outcome of the first rule that fails, or [`RuleOutcome::Pass`].
+    pub fn check(&self, levels: &[u32]) -> RuleOutcome {
+        self.rules
+        .iter()
+        .map(|rule| rule.check(levels))
+        .find(|outcome| !outcome.passed())
+        .unwrap_or(RuleOutcome::Pass)
+    }
+}
+
+/// The built-in rules equivalent to `rules`, to which more can be added.
+impl From<SafetyRules> for RuleSet {
+    fn from(rules: SafetyRules) -> RuleSet {
+        let set = RuleSet::new()
+        .with(MinLevels(rules.min_levels))
+        .with(StepRange {
+            min: rules.min_step,
+            max: rules.max_step,
+            allow_equal: rules.allow_equal
+        });
+        if rules.require_monotonic {
+            return set.with(Monotonic);
+        }
+        set
+    }
+}
+
+impl SafetyPolicy for RuleSet {
+    fn is_safe(&self, levels: &[u32]) -> bool {
+        self.check(levels).passed()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{is_safe_slice, rng::Rng, ParseOptions, Puzzle};
+
+    #[test]
+    fn should_builtin_rules_agree_with_safety_rules() {
+        let variants = [
+            SafetyRules::default(),
+            SafetyRules {
+                min_levels: 1,
+                max_step: 5,
+                allow_equal: true,
+                ..SafetyRules::default()
+            },
+            SafetyRules {
+                min_step: 2,
+                require_monotonic: false,
+                ..SafetyRules::default()
+            }
+        ];
+        let mut rng = Rng::seeded(11);
+        for rules in variants {
+            let set = RuleSet::from(rules);
+            for _ in 0..2000 {
+                let len = rng.below(0..8) as usize;
+                let levels: Vec<u32> = (0..len).map(|_| rng.below(0..10) as u32).collect();
+
+                assert_eq!(set.is_safe(&levels), is_safe_slice(&levels, &rules), "report {levels:?} under {rules:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn should_check_report_first_failing_rule() {
+        let set = RuleSet::from(SafetyRules::default());
+
+        assert_eq!(set.check(&[7, 6, 4, 2, 1]), RuleOutcome::Pass);
+        assert_eq!(set.check(&[1]), RuleOutcome::Fail {
+            rule: "too_short",
+            index: None
+        });
+        assert_eq!(set.check(&[1, 2, 7, 8, 9]), RuleOutcome::Fail {
+            rule: "step",
+            index: Some(1)
+        });
+        assert_eq!(set.check(&[1, 3, 2, 4, 5]), RuleOutcome::Fail {
+            rule: "direction",
+            index: Some(1)
+        });
+    }
+
+    #[test]
+    fn should_custom_rules_compose_with_builtin_ones() {
+        let no_level_above_8 = |levels: &[u32]| match levels.iter().position(|&level| level > 8) {
+            Some(index) => RuleOutcome::Fail {
+                rule: "above_8",
+                index: Some(index)
+            },
+            None => RuleOutcome::Pass
+        };
+        let set = RuleSet::from(SafetyRules::default()).with(no_level_above_8);
+        let (puzzle, _) = Puzzle::from_path("tests/resources/puzzle.txt", ParseOptions::lenient()).unwrap();
+
+        assert_eq!(set.len(), 4);
+        assert_eq!(set.check(&[1, 3, 6, 7, 9]), RuleOutcome::Fail {
+            rule: "above_8",
+            index: Some(4)
+        });
+        assert_eq!(puzzle.count_safe_with_policy(&set, 0), 1);
+        assert_eq!(puzzle.count_safe_with_policy(&set, 1), 4);
+    }
+}
@@ -0,0 +1,46 @@
+use crate::Level;
+
+/// The rules a report has to follow to be considered safe. The defaults are
+/// the puzzle's: at least two levels, all increasing or all decreasing, by
+/// 1 to 3 at a time.
+#[derive(PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug)]
+pub struct SafetyRules {
+    /// Reports with fewer levels are unsafe. Empty reports are never safe,
+    /// so `1` keeps single-level reports safe.
+    pub min_levels: usize,
+    /// The smallest difference allowed between two adjacent levels that are
+    /// not equal. Equal levels are only governed by `allow_equal`.
+    pub min_step: u32,
+    /// The largest difference allowed between two adjacent levels.
+    pub max_step: u32,
+    /// Whether all levels have to go the same way, as given by the first
+    /// pair that differs.
+    pub require_monotonic: bool,
+    /// Whether two adjacent levels may be equal. They never change the
+    /// direction of a report.
+    pub allow_equal: bool
+}
+
+impl SafetyRules {
+    /// Whether `value` followed by `next_value` is a step the rules allow,
+    /// whatever the direction.
+    pub fn allows_step<L: Level>(&self, value: L, next_value: L) -> bool {
+        match value.distance(next_value) {
+            0 => self.allow_equal,
+            diff => diff >= self.min_step && diff <= self.max_step
+        }
+    }
+}
+
+impl Default for SafetyRules {
+    fn default() -> SafetyRules {
+        SafetyRules {
+            min_levels: 2,
+            min_step: 1,
+            max_step: 3,
+            require_monotonic: true,
+            allow_equal: false
+        }
+    }
+}
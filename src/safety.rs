@@ -0,0 +1,443 @@
+//! The safety rules evaluated on plain level slices. Nothing here needs `std`
+//! or an allocator, so it is what `no_std` builds of the crate are made of.
+
+#[cfg(feature = "alloc")]
+use crate::min_removals_to_safe;
+use crate::{
+    removals::{min_removals_to_safe_in, removal_window},
+    Level, SafetyRules
+};
+
+/// Scratch kept on the stack by [`is_safe_slice_with_tolerance`], enough
+/// for reports of this many levels, or tolerances below it, without
+/// allocating.
+const STACK_WINDOW: usize = 64;
+
+/// How a report fares once the dampener is allowed to remove levels.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub enum ReportStatus {
+    Safe,
+    SafeWithDampener,
+    Unsafe
+}
+
+impl ReportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportStatus::Safe => "safe",
+            ReportStatus::SafeWithDampener => "safe_with_dampener",
+            ReportStatus::Unsafe => "unsafe"
+        }
+    }
+}
+
+/// The first rule a report breaks. `index` is the position of the first
+/// level of the offending pair.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub enum Violation {
+    TooShort { levels: usize },
+    Step { index: usize, diff: u32 },
+    Direction { index: usize }
+}
+
+impl Violation {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Violation::TooShort { .. } => "too_short",
+            Violation::Step { .. } => "step",
+            Violation::Direction { .. } => "direction"
+        }
+    }
+
+    /// The index of the first level of the offending pair, if any.
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            Violation::TooShort { .. } => None,
+            Violation::Step { index, .. } | Violation::Direction { index } => Some(*index)
+        }
+    }
+}
+
+/// Which way a report goes. Equal pairs are step violations, not changes of
+/// direction, so they are ignored here.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub enum Direction {
+    Ascending,
+    Descending,
+    /// Both increasing and decreasing pairs.
+    Mixed,
+    /// No two levels differ, including reports of fewer than two levels.
+    Flat
+}
+
+pub fn direction_of<L: Level>(levels: &[L]) -> Direction {
+    let pairs = levels.iter().zip(levels.iter().skip(1));
+    let ascending = pairs.clone().any(|(value, next_value)| next_value > value);
+    let descending = pairs.clone().any(|(value, next_value)| next_value < value);
+    match (ascending, descending) {
+        (true, false) => Direction::Ascending,
+        (false, true) => Direction::Descending,
+        (true, true) => Direction::Mixed,
+        (false, false) => Direction::Flat
+    }
+}
+
+pub fn is_safe_slice<L: Level>(levels: &[L], rules: &SafetyRules) -> bool {
+    first_violation(levels, rules).is_none()
+}
+
+pub fn first_violation<L: Level>(levels: &[L], rules: &SafetyRules) -> Option<Violation> {
+    violation_in(levels.iter().copied(), rules)
+}
+
+/// Safe as-is, or after removing up to `tolerance` levels. Removals can
+/// never take a report below `rules.min_levels`, and the levels kept may go
+/// either way: removing the head can flip a report from descending to
+/// ascending. See [`min_removals_to_safe`](crate::min_removals_to_safe) for
+/// the O(n·tolerance) search.
+///
+/// Without the `alloc` feature, reports longer than 64 levels are only
+/// searched for up to 63 removals.
+pub fn is_safe_slice_with_tolerance<L: Level>(levels: &[L], rules: &SafetyRules, tolerance: u32) -> bool {
+    // Any more removals would leave too few levels to be safe, and would
+    // only widen the window searched.
+    let useful = levels.len().saturating_sub(rules.min_levels.max(1));
+    let tolerance = tolerance.min(u32::try_from(useful).unwrap_or(u32::MAX));
+    if removal_window(levels.len(), tolerance) <= STACK_WINDOW {
+        return min_removals_to_safe_in(levels, rules, tolerance, &mut [0; STACK_WINDOW]).is_some();
+    }
+    #[cfg(feature = "alloc")]
+    return min_removals_to_safe(levels, rules, tolerance).is_some();
+    #[cfg(not(feature = "alloc"))]
+    return min_removals_to_safe_in(levels, rules, STACK_WINDOW as u32 - 1, &mut [0; STACK_WINDOW]).is_some();
+}
+
+pub fn classify_slice<L: Level>(levels: &[L], rules: &SafetyRules, tolerance: u32) -> ReportStatus {
+    if is_safe_slice(levels, rules) {
+        return ReportStatus::Safe;
+    }
+    if is_safe_slice_with_tolerance(levels, rules, tolerance) {
+        return ReportStatus::SafeWithDampener;
+    }
+    ReportStatus::Unsafe
+}
+
+/// Like [`is_safe_slice`], in one forward pass that keeps only the previous
+/// level and the direction. It stops at the first violation, leaving the
+/// rest of `levels` unread.
+pub fn is_safe_iter<L: Level, I: IntoIterator<Item = L>>(levels: I, rules: &SafetyRules) -> bool {
+    let mut run = Run::default();
+    for level in levels {
+        run.push(level, rules);
+        if run.broken {
+            return false;
+        }
+    }
+    run.levels >= rules.min_levels.max(1)
+}
+
+/// Safe as-is or once the first or the last level is removed, in one
+/// forward pass like [`is_safe_iter`]. The Problem Dampener may remove any
+/// level, which needs the levels after the offending pair to be read again;
+/// use [`is_safe_slice_with_tolerance`] for that.
+pub fn is_safe_iter_trimming_edges<L: Level, I: IntoIterator<Item = L>>(levels: I, rules: &SafetyRules) -> bool {
+    let min_levels = rules.min_levels.max(1);
+    let mut levels = levels.into_iter();
+    let mut full = Run::default();
+    let mut without_first = Run::default();
+    match levels.next() {
+        Some(level) => full.push(level, rules),
+        None => return false
+    }
+    // Removing the last level only helps while it is the one that broke
+    // the report.
+    let mut without_last = true;
+    for level in levels {
+        if full.broken {
+            without_last = false;
+        }
+        full.push(level, rules);
+        without_first.push(level, rules);
+        if !without_last && without_first.broken {
+            return false;
+        }
+    }
+    // A report broken by its last level kept every level but that one.
+    let without_last_levels = if full.broken { full.levels } else { full.levels - 1 };
+
+    (!full.broken && full.levels >= min_levels)
+        || (without_last && without_last_levels >= min_levels)
+        || (!without_first.broken && without_first.levels >= min_levels)
+}
+
+/// The state of [`is_safe_iter`]: every pair so far is a safe step, in one
+/// direction if the rules require it, or `broken` is set.
+struct Run<L> {
+    previous: Option<L>,
+    ascending: Option<bool>,
+    /// Levels pushed before the report broke.
+    levels: usize,
+    broken: bool
+}
+
+impl<L: Level> Default for Run<L> {
+    fn default() -> Run<L> {
+        Run {
+            previous: None,
+            ascending: None,
+            levels: 0,
+            broken: false
+        }
+    }
+}
+
+impl<L: Level> Run<L> {
+    fn push(&mut self, level: L, rules: &SafetyRules) {
+        if self.broken {
+            return;
+        }
+        if let Some(previous) = self.previous {
+            // Equal pairs, when allowed, leave the direction to the first
+            // pair that differs.
+            let ascending = level > previous;
+            let turns = rules.require_monotonic && level != previous && *self.ascending.get_or_insert(ascending) != ascending;
+            if !rules.allows_step(previous, level) || turns {
+                self.broken = true;
+                return;
+            }
+        }
+        self.previous = Some(level);
+        self.levels += 1;
+    }
+}
+
+/// One pass over `levels`. The direction is given by the first pair of
+/// levels that differ, which is known by the time any pair can break it, so
+/// a leading equal pair is blamed as a step violation rather than turning the
+/// rest of the report into direction violations. A report too short is
+/// blamed for that first, so after a violation only as many more levels are
+/// read as it takes to rule that out.
+fn violation_in<L: Level, I: Iterator<Item = L>>(levels: I, rules: &SafetyRules) -> Option<Violation> {
+    let min_levels = rules.min_levels.max(1);
+    let mut previous = None;
+    let mut ascending = None;
+    let mut count = 0;
+    let mut violation = None;
+    for level in levels {
+        count += 1;
+        if let (None, Some(previous)) = (violation, previous) {
+            let index = count - 2;
+            if !rules.allows_step(previous, level) {
+                violation = Some(Violation::Step {
+                    index,
+                    diff: previous.distance(level)
+                });
+            } else if rules.require_monotonic && level != previous && *ascending.get_or_insert(level > previous) != (level > previous) {
+                violation = Some(Violation::Direction {
+                    index
+                });
+            }
+        }
+        if violation.is_some() && count >= min_levels {
+            return violation;
+        }
+        previous = Some(level);
+    }
+    if count < min_levels {
+        return Some(Violation::TooShort {
+            levels: count
+        });
+    }
+    violation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_infer_direction_from_first_non_equal_pair() {
+        let rules = SafetyRules {
+            allow_equal: true,
+            ..SafetyRules::default()
+        };
+
+        assert_eq!(first_violation(&[2, 2, 3, 4, 5], &rules), None);
+        assert_eq!(first_violation(&[5, 5, 4, 3], &rules), None);
+        assert_eq!(first_violation(&[1, 1, 2, 3, 2], &rules), Some(Violation::Direction {
+            index: 3
+        }));
+        assert_eq!(first_violation(&[5, 7, 3, 1], &SafetyRules::default()), Some(Violation::Step {
+            index: 1,
+            diff: 4
+        }));
+        assert_eq!(first_violation(&[5, 7, 6, 4], &SafetyRules::default()), Some(Violation::Direction {
+            index: 1
+        }));
+    }
+
+    #[test]
+    fn should_violation_stop_once_report_is_long_enough() {
+        assert_eq!(violation_in(stopping_after(&[1, 2, 9]), &SafetyRules::default()), Some(Violation::Step {
+            index: 1,
+            diff: 7
+        }));
+        assert_eq!(violation_in(stopping_after(&[1, 9, 2, 3, 4]), &SafetyRules {
+            min_levels: 5,
+            ..SafetyRules::default()
+        }), Some(Violation::Step {
+            index: 0,
+            diff: 8
+        }));
+        assert_eq!(violation_in([1, 9, 2].into_iter(), &SafetyRules {
+            min_levels: 5,
+            ..SafetyRules::default()
+        }), Some(Violation::TooShort {
+            levels: 3
+        }));
+    }
+
+    #[test]
+    fn should_direction_of_ignore_equal_pairs() {
+        assert_eq!(direction_of(&[8, 6, 4, 4, 1]), Direction::Descending);
+        assert_eq!(direction_of(&[1, 2, 7, 8, 9]), Direction::Ascending);
+        assert_eq!(direction_of(&[1, 3, 2, 4, 5]), Direction::Mixed);
+        assert_eq!(direction_of(&[3, 3]), Direction::Flat);
+        assert_eq!(direction_of::<u32>(&[]), Direction::Flat);
+    }
+
+    /// `levels`, then a panic if read any further.
+    fn stopping_after(levels: &[u32]) -> impl Iterator<Item = u32> + '_ {
+        levels.iter().copied().chain(core::iter::from_fn(|| panic!("read past the violation")))
+    }
+
+    #[test]
+    fn should_is_safe_iter_stop_at_first_violation() {
+        assert!(!is_safe_iter(stopping_after(&[1, 2, 9]), &SafetyRules::default()));
+        assert!(!is_safe_iter(stopping_after(&[1, 3, 2]), &SafetyRules::default()));
+        assert!(!is_safe_iter(stopping_after(&[4, 4]), &SafetyRules::default()));
+    }
+
+    #[test]
+    fn should_is_safe_iter_follow_slice_rules() {
+        assert!(is_safe_iter([7, 6, 4, 2, 1], &SafetyRules::default()));
+        assert!(!is_safe_iter([5], &SafetyRules::default()));
+        assert!(is_safe_iter([5], &SafetyRules { min_levels: 1, ..SafetyRules::default() }));
+        assert!(!is_safe_iter::<u32, _>([], &SafetyRules { min_levels: 0, ..SafetyRules::default() }));
+    }
+
+    #[test]
+    fn should_is_safe_iter_trimming_edges_remove_first_or_last_level() {
+        assert!(is_safe_iter_trimming_edges([9, 1, 2, 3], &SafetyRules::default()));
+        assert!(is_safe_iter_trimming_edges([1, 2, 3, 9], &SafetyRules::default()));
+        assert!(is_safe_iter_trimming_edges([7, 6, 4, 2, 1], &SafetyRules::default()));
+        assert!(!is_safe_iter_trimming_edges([1, 3, 2, 4, 5], &SafetyRules::default()));
+        assert!(!is_safe_iter_trimming_edges([1, 9], &SafetyRules::default()));
+        assert!(!is_safe_iter_trimming_edges(stopping_after(&[1, 9, 2]), &SafetyRules::default()));
+    }
+
+    #[test]
+    fn should_is_safe_slice_follow_step_and_direction_rules() {
+        assert!(is_safe_slice(&[7, 6, 4, 2, 1], &SafetyRules::default()));
+        assert!(!is_safe_slice(&[1, 2, 7, 8, 9], &SafetyRules::default()));
+        assert!(!is_safe_slice(&[1, 3, 2, 4, 5], &SafetyRules::default()));
+    }
+
+    #[test]
+    fn should_dampener_not_rescue_two_levels_down_to_one_when_min_levels_2() {
+        let levels = [1, 9];
+
+        assert!(!is_safe_slice_with_tolerance(&levels, &SafetyRules { min_levels: 2, ..SafetyRules::default() }, 1));
+        assert!(is_safe_slice_with_tolerance(&levels, &SafetyRules { min_levels: 1, ..SafetyRules::default() }, 1));
+    }
+
+    #[test]
+    fn should_dampener_rescue_report_by_removing_one_level() {
+        let levels = [1, 3, 2, 4, 5];
+
+        assert!(!is_safe_slice_with_tolerance(&levels, &SafetyRules::default(), 0));
+        assert!(is_safe_slice_with_tolerance(&levels, &SafetyRules::default(), 1));
+    }
+
+    #[test]
+    fn should_tolerance_only_remove_up_to_tolerance_levels() {
+        let levels = [1, 9, 2, 8, 3];
+
+        assert!(!is_safe_slice_with_tolerance(&levels, &SafetyRules::default(), 1));
+        assert!(is_safe_slice_with_tolerance(&levels, &SafetyRules::default(), 2));
+    }
+
+    #[test]
+    fn should_tolerance_beyond_report_length_not_change_answer() {
+        let levels = [5; 12];
+
+        assert!(!is_safe_slice_with_tolerance(&levels, &SafetyRules::default(), u32::MAX));
+        assert!(is_safe_slice_with_tolerance(&levels, &SafetyRules { min_levels: 1, ..SafetyRules::default() }, u32::MAX));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn should_tolerance_search_long_report_beyond_stack_window() {
+        let mut levels = [0u32; 200];
+        for (index, level) in levels.iter_mut().enumerate() {
+            *level = if index % 3 == 0 { 1000 } else { index as u32 };
+        }
+
+        assert!(is_safe_slice_with_tolerance(&levels, &SafetyRules::default(), 67));
+        assert!(!is_safe_slice_with_tolerance(&levels, &SafetyRules::default(), 66));
+    }
+
+    #[test]
+    fn should_classify_slice_sample_reports() {
+        assert_eq!(classify_slice(&[7, 6, 4, 2, 1], &SafetyRules::default(), 1), ReportStatus::Safe);
+        assert_eq!(classify_slice(&[8, 6, 4, 4, 1], &SafetyRules::default(), 1), ReportStatus::SafeWithDampener);
+        assert_eq!(classify_slice(&[9, 7, 6, 2, 1], &SafetyRules::default(), 1), ReportStatus::Unsafe);
+    }
+
+    fn variant_rules() -> SafetyRules {
+        SafetyRules {
+            max_step: 5,
+            allow_equal: true,
+            ..SafetyRules::default()
+        }
+    }
+
+    #[test]
+    fn should_rules_change_allowed_steps() {
+        assert!(is_safe_slice(&[1, 2, 7, 8, 9], &variant_rules()));
+        assert!(is_safe_slice(&[8, 6, 4, 4, 1], &variant_rules()));
+        assert!(!is_safe_slice(&[1, 2, 8], &variant_rules()));
+        assert_eq!(first_violation(&[1, 2, 4, 8], &SafetyRules {
+            min_step: 2,
+            ..SafetyRules::default()
+        }), Some(Violation::Step {
+            index: 0,
+            diff: 1
+        }));
+    }
+
+    #[test]
+    fn should_equal_pairs_not_set_direction_when_allowed() {
+        assert!(is_safe_slice(&[4, 4, 3, 1], &variant_rules()));
+        assert!(is_safe_iter([4, 4, 3, 1], &variant_rules()));
+        assert_eq!(first_violation(&[4, 4, 5, 3], &variant_rules()), Some(Violation::Direction {
+            index: 2
+        }));
+        assert!(!is_safe_iter([4, 4, 5, 3], &variant_rules()));
+    }
+
+    #[test]
+    fn should_rules_not_require_monotonic_when_disabled() {
+        let rules = SafetyRules {
+            require_monotonic: false,
+            ..SafetyRules::default()
+        };
+
+        assert!(is_safe_slice(&[1, 3, 2, 4, 5], &rules));
+        assert!(is_safe_iter([1, 3, 2, 4, 5], &rules));
+        assert!(!is_safe_slice(&[1, 3, 3, 4, 5], &rules));
+    }
+}
@@ -0,0 +1,230 @@
+//! Estimating the answers from a uniform sample of the reports, for inputs
+//! too large to classify in full.
+
+use crate::{
+    parse::{parse_line, Location},
+    removals::classify_reusing,
+    rng::Rng, ParseError, ParseOptions, ParseSummary, ReportStatus, SafetyRules
+};
+
+/// Keeps a reservoir of `size` reports out of those pushed, each of them
+/// equally likely to be in it, and classifies only those.
+#[derive(PartialEq, Eq, Clone)]
+#[derive(Debug)]
+pub struct SampledCounter {
+    rules: SafetyRules,
+    tolerance: u32,
+    options: ParseOptions,
+    size: usize,
+    reservoir: Vec<Vec<u32>>,
+    seen: usize,
+    rng: Rng,
+    next_location: Location
+}
+
+/// An estimate of one part's answer.
+#[derive(PartialEq, Clone, Copy)]
+#[derive(Debug)]
+pub struct EstimatedCount {
+    /// Fraction of the sampled reports that are safe.
+    pub fraction: f64,
+    /// `fraction` of all the reports, rounded.
+    pub count: usize,
+    /// A naive 95% confidence interval around `count`, from the normal
+    /// approximation, narrowing to `count` alone when every report was
+    /// sampled.
+    pub low: usize,
+    pub high: usize
+}
+
+#[derive(PartialEq, Clone, Copy)]
+#[derive(Debug)]
+pub struct Estimate {
+    /// Reports pushed so far.
+    pub total: usize,
+    /// Reports actually classified.
+    pub sample_size: usize,
+    /// Reports safe as-is.
+    pub part1: EstimatedCount,
+    /// Reports safe once up to `tolerance` levels may be removed.
+    pub part2: EstimatedCount,
+    pub tolerance: u32
+}
+
+impl SampledCounter {
+    /// Parses strictly, like [`IncrementalCounter::new`](crate::IncrementalCounter::new).
+    /// The same `seed` and lines always give the same sample.
+    pub fn new(rules: SafetyRules, tolerance: u32, size: usize, seed: u64) -> SampledCounter {
+        SampledCounter::with_options(rules, tolerance, size, seed, ParseOptions::strict())
+    }
+
+    pub fn with_options(rules: SafetyRules, tolerance: u32, size: usize, seed: u64, options: ParseOptions) -> SampledCounter {
+        SampledCounter {
+            rules,
+            tolerance,
+            options,
+            size,
+            reservoir: Vec::with_capacity(size),
+            seen: 0,
+            rng: Rng::seeded(seed),
+            next_location: Location {
+                line: 1,
+                offset: 0
+            }
+        }
+    }
+
+    /// Parses one line, without its line ending, and may keep its report in
+    /// the sample. Every line is parsed, so bad lines are reported whether
+    /// sampled or not.
+    pub fn push_line(&mut self, line: &str) -> Result<(), ParseError> {
+        self.push_line_bytes(line.as_bytes())
+    }
+
+    /// Like [`SampledCounter::push_line`], without requiring the line to be
+    /// UTF-8.
+    pub fn push_line_bytes(&mut self, line: &[u8]) -> Result<(), ParseError> {
+        let location = self.next_location;
+        self.next_location = Location {
+            line: location.line + 1,
+            offset: location.offset + line.len() + 1
+        };
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let Some(levels) = parse_line(line, location, &self.options, &mut ParseSummary::default())? else {
+            return Ok(());
+        };
+        // Algorithm R: the report in position `seen` replaces a sampled one
+        // with probability `size / (seen + 1)`.
+        if self.reservoir.len() < self.size {
+            self.reservoir.push(levels);
+        } else {
+            let slot = self.rng.below(0..self.seen as u64 + 1) as usize;
+            if slot < self.size {
+                self.reservoir[slot] = levels;
+            }
+        }
+        self.seen += 1;
+        Ok(())
+    }
+
+    pub fn estimate(&self) -> Estimate {
+        let (mut part1, mut part2) = (0, 0);
+        let mut scratch = Vec::new();
+        for levels in &self.reservoir {
+            match classify_reusing(levels, &self.rules, self.tolerance, &mut scratch) {
+                ReportStatus::Safe => {
+                    part1 += 1;
+                    part2 += 1;
+                }
+                ReportStatus::SafeWithDampener => part2 += 1,
+                ReportStatus::Unsafe => {}
+            }
+        }
+        Estimate {
+            total: self.seen,
+            sample_size: self.reservoir.len(),
+            part1: self.extrapolate(part1),
+            part2: self.extrapolate(part2),
+            tolerance: self.tolerance
+        }
+    }
+
+    fn extrapolate(&self, safe: usize) -> EstimatedCount {
+        let (sample_size, total) = (self.reservoir.len() as f64, self.seen as f64);
+        if self.reservoir.is_empty() {
+            return EstimatedCount {
+                fraction: 0.0,
+                count: 0,
+                low: 0,
+                high: 0
+            };
+        }
+        let fraction = safe as f64 / sample_size;
+        // Shrinks to nothing as the sample grows to the whole input.
+        let finite_population = if total > 1.0 { ((total - sample_size) / (total - 1.0)).sqrt() } else { 0.0 };
+        let margin = 1.96 * (fraction * (1.0 - fraction) / sample_size).sqrt() * finite_population;
+        EstimatedCount {
+            fraction,
+            count: (fraction * total).round() as usize,
+            low: ((fraction - margin).max(0.0) * total).floor() as usize,
+            high: ((fraction + margin).min(1.0) * total).ceil() as usize
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+    use crate::generate::{generate_puzzle, write_to, GeneratorConfig};
+
+    fn sampled(input: &str, size: usize, seed: u64) -> Estimate {
+        let mut counter = SampledCounter::new(SafetyRules::default(), 1, size, seed);
+        input.lines().for_each(|line| counter.push_line(line).unwrap());
+        counter.estimate()
+    }
+
+    #[test]
+    fn should_sample_of_whole_input_be_exact() {
+        let estimate = sampled(&read_to_string("tests/resources/puzzle.txt").unwrap(), 10, 3);
+
+        assert_eq!((estimate.total, estimate.sample_size), (6, 6));
+        assert_eq!((estimate.part1.count, estimate.part1.low, estimate.part1.high), (2, 2, 2));
+        assert_eq!((estimate.part2.count, estimate.part2.low, estimate.part2.high), (4, 4, 4));
+    }
+
+    #[test]
+    fn should_same_seed_give_same_estimate() {
+        let mut input = Vec::new();
+        write_to(&generate_puzzle(&GeneratorConfig::default()), &mut input).unwrap();
+        let input = String::from_utf8(input).unwrap();
+
+        let estimate = sampled(&input, 100, 7);
+
+        assert_eq!(estimate, sampled(&input, 100, 7));
+        assert_eq!((estimate.total, estimate.sample_size), (1000, 100));
+        assert!(estimate.part1.low <= 250 && 250 <= estimate.part1.high, "{estimate:?}");
+        assert!(estimate.part2.low <= 500 && 500 <= estimate.part2.high, "{estimate:?}");
+    }
+
+    #[test]
+    fn should_push_line_report_bad_lines_outside_sample() {
+        let mut counter = SampledCounter::new(SafetyRules::default(), 1, 1, 0);
+
+        assert_eq!(counter.push_line("7 6 4 2 1"), Ok(()));
+        assert_eq!(counter.push_line("1 x 2"), Err(ParseError::InvalidToken {
+            line: 2,
+            token: "x".to_string()
+        }));
+    }
+
+    #[test]
+    fn should_push_line_bytes_sample_lines_that_are_not_utf8() {
+        let mut counter = SampledCounter::with_options(SafetyRules::default(), 1, 10, 0, ParseOptions::lenient());
+
+        assert_eq!(counter.push_line_bytes(b"7 6 4 2 1 \xff\r"), Ok(()));
+        assert_eq!(counter.push_line_bytes(b"1 3 2 4 5"), Ok(()));
+
+        let estimate = counter.estimate();
+        assert_eq!((estimate.total, estimate.part1.count, estimate.part2.count), (2, 1, 2));
+    }
+
+    #[test]
+    fn should_estimate_long_reports_at_large_tolerance() {
+        let mut counter = SampledCounter::new(SafetyRules::default(), 20, 10, 0);
+        let levels: Vec<String> = (0..200).map(|index| if index % 10 == 0 { 999 } else { index }).map(|level| level.to_string()).collect();
+        counter.push_line(&levels.join(" ")).unwrap();
+
+        let estimate = counter.estimate();
+
+        assert_eq!((estimate.part1.count, estimate.part2.count), (0, 1));
+    }
+
+    #[test]
+    fn should_estimate_nothing_from_empty_input() {
+        let estimate = sampled("", 10, 0);
+
+        assert_eq!((estimate.total, estimate.part1.count, estimate.part2.high), (0, 0, 0));
+    }
+}
@@ -0,0 +1,78 @@
+//! Files holding several puzzles, separated by blank lines.
+
+use std::fs;
+
+use crate::{
+    parse::{lines, parse_line},
+    report::Report,
+    ParseError, ParseOptions, ParseSummary, Puzzle, SafetyRules
+};
+
+/// One puzzle per run of non-blank lines, in order, each parsed strictly.
+/// Errors give the line in the whole input. Blank lines before the first
+/// section or after the last one make no empty sections.
+pub fn parse_sections(input: &str) -> Result<Vec<Puzzle>, ParseError> {
+    let mut sections = Vec::new();
+    let mut reports = Vec::new();
+    for (location, line) in lines(input.as_bytes()) {
+        match parse_line(line, location, &ParseOptions::strict(), &mut ParseSummary::default())? {
+            Some(levels) => reports.push(Report::new(levels)),
+            None if !reports.is_empty() => sections.push(Puzzle::from_reports(std::mem::take(&mut reports))),
+            None => {}
+        }
+    }
+    if !reports.is_empty() {
+        sections.push(Puzzle::from_reports(reports));
+    }
+    Ok(sections)
+}
+
+/// The reports of each section of the file at `input_path` that are safe
+/// with up to `tolerance` removals.
+pub fn find_safe_reports_per_section(input_path: &str, tolerance: u32) -> Vec<usize> {
+    let input = fs::read_to_string(input_path).unwrap();
+
+    parse_sections(&input)
+    .unwrap()
+    .iter()
+    .map(|section| section.count_safe(&SafetyRules::default(), tolerance))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+
+    const SECTIONS: &str = "tests/resources/puzzle_sections.txt";
+
+    #[test]
+    fn should_parse_sections_keep_order_and_skip_outer_blank_lines() {
+        let mut sections = parse_sections(&read_to_string(SECTIONS).unwrap()).unwrap();
+
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[0], Puzzle::parse(&read_to_string("tests/resources/puzzle.txt").unwrap(), ParseOptions::strict()).unwrap().0);
+        assert_eq!(sections.pop().unwrap().into_values(), vec![vec![10, 11, 12, 13], vec![20, 20, 21]]);
+    }
+
+    #[test]
+    fn should_find_safe_reports_per_section() {
+        let part1 = find_safe_reports_per_section(SECTIONS, 0);
+        let part2 = find_safe_reports_per_section(SECTIONS, 1);
+
+        assert_eq!(part1, vec![2, 2, 1]);
+        assert_eq!(part2, vec![4, 3, 2]);
+        assert_eq!(part1.iter().sum::<usize>(), 5);
+        assert_eq!(part2.iter().sum::<usize>(), 9);
+    }
+
+    #[test]
+    fn should_parse_sections_report_line_in_whole_input() {
+        assert_eq!(parse_sections("1 2\n\n3 4\n\n5 x"), Err(ParseError::InvalidToken {
+            line: 5,
+            token: "x".to_string()
+        }));
+        assert_eq!(parse_sections("\n \n"), Ok(Vec::new()));
+    }
+}
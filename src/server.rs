@@ -0,0 +1,227 @@
+//! A tiny HTTP service around the classifier, see `examples/server.rs`.
+//!
+//! `POST /safe-reports` takes the raw puzzle text as its body, with an
+//! optional `?tolerance=` (1 by default), and answers with the JSON
+//! [`SafetySummary`](crate::SafetySummary). The input is parsed strictly:
+//! malformed input gets a 400 whose JSON body holds the message and the
+//! 1-based `line`. Only plain HTTP/1.1 with a `Content-Length` is spoken, one
+//! request per connection.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    thread
+};
+
+use crate::{json, ParseOptions, Puzzle, SafetyRules};
+
+/// Larger bodies are refused with a 413 before being read.
+pub const MAX_BODY_BYTES: usize = 1 << 20;
+
+const MAX_HEAD_BYTES: usize = 8 << 10;
+
+#[derive(PartialEq, Eq, Clone)]
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    /// The path, with its query string if any.
+    pub target: String,
+    pub body: Vec<u8>
+}
+
+#[derive(PartialEq, Eq, Clone)]
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    /// Always JSON.
+    pub body: String
+}
+
+impl Response {
+    fn json(status: u16, body: String) -> Response {
+        Response {
+            status,
+            body
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Response {
+        Response::json(status, json::object(&[("error", json::string(message))]))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            411 => "Length Required",
+            413 => "Payload Too Large",
+            _ => "Internal Server Error"
+        }
+    }
+
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            self.reason(),
+            self.body.len(),
+            self.body
+        )?;
+        writer.flush()
+    }
+}
+
+pub fn route(request: &Request) -> Response {
+    let (path, query) = request.target.split_once('?').unwrap_or((&request.target, ""));
+    if path != "/safe-reports" {
+        return Response::error(404, "not found");
+    }
+    if request.method != "POST" {
+        return Response::error(405, "use POST");
+    }
+    let tolerance = match query_value(query, "tolerance").map(str::parse::<u32>) {
+        None => 1,
+        Some(Ok(tolerance)) => tolerance,
+        Some(Err(_)) => return Response::error(400, "tolerance must be a non-negative integer")
+    };
+    match Puzzle::from_bytes(&request.body, ParseOptions::strict()) {
+        Ok((puzzle, _)) => Response::json(200, puzzle.summary(&SafetyRules::default(), tolerance).to_json()),
+        Err(error) => Response::json(400, json::object(&[
+            ("error", json::string(&error.to_string())),
+            ("line", error.line().to_string())
+        ]))
+    }
+}
+
+fn query_value<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+    .split('&')
+    .filter_map(|pair| pair.split_once('='))
+    .find(|(key, _)| *key == name)
+    .map(|(_, value)| value)
+}
+
+/// Reads one request, or the response refusing it.
+fn read_request(reader: &mut impl BufRead) -> Result<Request, Response> {
+    let mut head = reader.by_ref().take(MAX_HEAD_BYTES as u64);
+    let mut request_line = String::new();
+    head.read_line(&mut request_line).map_err(|_| Response::error(400, "malformed request"))?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(Response::error(400, "malformed request"));
+    };
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if head.read_line(&mut header).map_err(|_| Response::error(400, "malformed request"))? == 0 {
+            return Err(Response::error(400, "malformed request"));
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let content_length = match content_length {
+        Some(length) if length > MAX_BODY_BYTES => return Err(Response::error(413, "body is too large")),
+        Some(length) => length,
+        None if method == "POST" => return Err(Response::error(411, "Content-Length is required")),
+        None => 0
+    };
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).map_err(|_| Response::error(400, "body is shorter than Content-Length"))?;
+
+    Ok(Request {
+        method: method.to_string(),
+        target: target.to_string(),
+        body
+    })
+}
+
+pub fn handle_connection(stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let response = read_request(&mut reader).map_or_else(|response| response, |request| route(&request));
+
+    response.write_to(&mut &stream)
+}
+
+/// Serves connections from `listener` forever, one thread each.
+pub fn serve(listener: TcpListener) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        thread::spawn(move || handle_connection(stream));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(target: &str, body: &str) -> Request {
+        Request {
+            method: "POST".to_string(),
+            target: target.to_string(),
+            body: body.as_bytes().to_vec()
+        }
+    }
+
+    #[test]
+    fn should_route_answer_summary_with_default_tolerance() {
+        let response = route(&post("/safe-reports", "7 6 4 2 1\n1 3 2 4 5"));
+
+        assert_eq!(response, Response::json(200, r#"{"total":2,"part1":1,"part2":2,"tolerance":1}"#.to_string()));
+    }
+
+    #[test]
+    fn should_route_use_tolerance_from_query() {
+        let response = route(&post("/safe-reports?x=1&tolerance=0", "7 6 4 2 1\n1 3 2 4 5"));
+
+        assert_eq!(response.body, r#"{"total":2,"part1":1,"part2":1,"tolerance":0}"#);
+    }
+
+    #[test]
+    fn should_route_reject_malformed_input_with_line() {
+        let response = route(&post("/safe-reports", "7 6 4\n1 x 2"));
+
+        assert_eq!(response, Response::json(400, r#"{"error":"line 2: `x` is not a level","line":2}"#.to_string()));
+    }
+
+    #[test]
+    fn should_route_reject_bad_tolerance_method_and_path() {
+        assert_eq!(route(&post("/safe-reports?tolerance=-1", "")).status, 400);
+        assert_eq!(route(&post("/other", "")).status, 404);
+        assert_eq!(route(&Request {
+            method: "GET".to_string(),
+            ..post("/safe-reports", "")
+        }).status, 405);
+    }
+
+    #[test]
+    fn should_read_request_refuse_too_large_body() {
+        let head = format!("POST /safe-reports HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_BYTES + 1);
+
+        assert_eq!(read_request(&mut head.as_bytes()).unwrap_err().status, 413);
+    }
+
+    #[test]
+    fn should_read_request_require_content_length_for_post() {
+        let head = "POST /safe-reports HTTP/1.1\r\nHost: localhost\r\n\r\n1 2";
+
+        assert_eq!(read_request(&mut head.as_bytes()).unwrap_err().status, 411);
+    }
+
+    #[test]
+    fn should_read_request_read_body() {
+        let request = "POST /safe-reports?tolerance=0 HTTP/1.1\r\ncontent-length: 3\r\n\r\n1 2";
+
+        assert_eq!(read_request(&mut request.as_bytes()), Ok(post("/safe-reports?tolerance=0", "1 2")));
+    }
+}
@@ -0,0 +1,155 @@
+//! A uniform interface for runners driving one crate per puzzle day: a
+//! [`Solver`] parses the input once, then answers both parts from it, and
+//! [`run_solver`] times each step.
+
+use std::{
+    fmt::{self, Display},
+    time::{Duration, Instant}
+};
+
+use crate::{safe_reports, Day02Error, ParseOptions, Puzzle};
+
+pub trait Solver {
+    /// The parsed puzzle both parts are answered from.
+    type Input;
+    type Answer: Display;
+    type Error;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Self::Error>;
+
+    fn part1(&self, input: &Self::Input) -> Self::Answer;
+
+    fn part2(&self, input: &Self::Input) -> Self::Answer;
+}
+
+/// Part 1 counts the reports safe as-is, part 2 the reports safe once the
+/// Problem Dampener may remove one level. Lines that do not parse are
+/// skipped.
+#[derive(PartialEq, Eq, Clone, Copy, Default)]
+#[derive(Debug)]
+pub struct Day02;
+
+impl Solver for Day02 {
+    type Input = Puzzle;
+    type Answer = usize;
+    type Error = Day02Error;
+
+    fn parse(&self, input: &str) -> Result<Puzzle, Day02Error> {
+        let (puzzle, _) = Puzzle::parse(input, ParseOptions::lenient())?;
+
+        Ok(puzzle)
+    }
+
+    fn part1(&self, puzzle: &Puzzle) -> usize {
+        safe_reports(puzzle, 0)
+    }
+
+    fn part2(&self, puzzle: &Puzzle) -> usize {
+        safe_reports(puzzle, 1)
+    }
+}
+
+/// A value and how long it took to compute.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub struct Timed<T> {
+    pub value: T,
+    pub elapsed: Duration
+}
+
+impl<T> Timed<T> {
+    fn of(compute: impl FnOnce() -> T) -> Timed<T> {
+        let start = Instant::now();
+        let value = compute();
+
+        Timed {
+            value,
+            elapsed: start.elapsed()
+        }
+    }
+}
+
+/// Both answers of a [`Solver`] and how long parsing took. It displays as
+/// one line per step, e.g. `Part 1: 2 (12.3µs)`.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub struct Solution<A> {
+    pub parse: Duration,
+    pub part1: Timed<A>,
+    pub part2: Timed<A>
+}
+
+impl<A: Display> Display for Solution<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Parse: {:?}", self.parse)?;
+        writeln!(f, "Part 1: {} ({:?})", self.part1.value, self.part1.elapsed)?;
+        writeln!(f, "Part 2: {} ({:?})", self.part2.value, self.part2.elapsed)
+    }
+}
+
+/// Parses `input` with `solver` then answers both parts, timing each step.
+pub fn run_solver<S: Solver>(solver: &S, input: &str) -> Result<Solution<S::Answer>, S::Error> {
+    let parsed = Timed::of(|| solver.parse(input));
+    let input = parsed.value?;
+
+    Ok(Solution {
+        parse: parsed.elapsed,
+        part1: Timed::of(|| solver.part1(&input)),
+        part2: Timed::of(|| solver.part2(&input))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+
+    /// Another day, to check that nothing is tied to day 2.
+    struct Sum;
+
+    impl Solver for Sum {
+        type Input = Vec<i64>;
+        type Answer = i64;
+        type Error = std::num::ParseIntError;
+
+        fn parse(&self, input: &str) -> Result<Vec<i64>, Self::Error> {
+            input.split_whitespace().map(str::parse).collect()
+        }
+
+        fn part1(&self, numbers: &Vec<i64>) -> i64 {
+            numbers.iter().sum()
+        }
+
+        fn part2(&self, numbers: &Vec<i64>) -> i64 {
+            numbers.iter().product()
+        }
+    }
+
+    #[test]
+    fn should_run_solver_answer_sample_for_both_parts() {
+        let input = read_to_string("tests/resources/puzzle.txt").unwrap();
+
+        let solution = run_solver(&Day02, &input).unwrap();
+
+        assert_eq!((solution.part1.value, solution.part2.value), (2, 4));
+        assert!(solution.to_string().starts_with("Parse: "));
+        assert!(solution.to_string().contains("\nPart 1: 2 (") && solution.to_string().contains("\nPart 2: 4 ("));
+    }
+
+    #[test]
+    fn should_parse_once_and_answer_parts_from_input() {
+        let puzzle = Day02.parse("1 2 3\n1 9 9 9\n3 2 6").unwrap();
+
+        assert_eq!(Day02.part1(&puzzle), 1);
+        assert_eq!(Day02.part2(&puzzle), 2);
+    }
+
+    #[test]
+    fn should_run_solver_of_other_day_and_fail_on_its_parse_error() {
+        let solution = run_solver(&Sum, "2 3 4").unwrap();
+
+        assert_eq!((solution.part1.value, solution.part2.value), (9, 24));
+        assert!(run_solver(&Sum, "2 x").is_err());
+    }
+}
@@ -0,0 +1,38 @@
+//! Reports drawn as block sparklines, for eyeballing them in a terminal.
+
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// One block per level, scaled between the report's own lowest and highest
+/// level. A report whose levels are all equal is a flat line.
+pub fn sparkline(levels: &[u32]) -> String {
+    let (Some(&min), Some(&max)) = (levels.iter().min(), levels.iter().max()) else {
+        return String::new();
+    };
+    let range = u64::from(max - min);
+    levels
+    .iter()
+    .map(|&level| match range {
+        0 => BLOCKS[0],
+        _ => BLOCKS[(u64::from(level - min) * (BLOCKS.len() as u64 - 1) / range) as usize]
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_sparkline_scale_to_report_min_and_max() {
+        assert_eq!(sparkline(&[1, 2, 3, 4, 5, 6, 7, 8]), "▁▂▃▄▅▆▇█");
+        assert_eq!(sparkline(&[7, 6, 4, 2, 1]), "█▆▄▂▁");
+        assert_eq!(sparkline(&[0, u32::MAX]), "▁█");
+    }
+
+    #[test]
+    fn should_sparkline_be_flat_when_degenerate() {
+        assert_eq!(sparkline(&[5, 5, 5]), "▁▁▁");
+        assert_eq!(sparkline(&[9]), "▁");
+        assert_eq!(sparkline(&[]), "");
+    }
+}
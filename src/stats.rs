@@ -0,0 +1,45 @@
+//! Figures about a whole puzzle, to sanity-check an input before trusting
+//! its answers.
+
+use std::collections::BTreeMap;
+
+use crate::Violation;
+
+/// Why the reports that are unsafe as-is are so, by their first violation.
+#[derive(PartialEq, Eq, Clone, Copy, Default)]
+#[derive(Debug)]
+pub struct FailureReasons {
+    pub too_short: usize,
+    pub step: usize,
+    pub direction: usize
+}
+
+impl FailureReasons {
+    pub(crate) fn count(&mut self, violation: Violation) {
+        match violation {
+            Violation::TooShort { .. } => self.too_short += 1,
+            Violation::Step { .. } => self.step += 1,
+            Violation::Direction { .. } => self.direction += 1
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Default)]
+#[derive(Debug)]
+pub struct SafetyStats {
+    pub total: usize,
+    /// Reports safe as-is.
+    pub safe: usize,
+    /// Reports unsafe as-is but safe once the Problem Dampener removes one
+    /// level.
+    pub rescued_by_dampener: usize,
+    /// Reports unsafe even with the Problem Dampener.
+    pub unsafe_reports: usize,
+    pub failures: FailureReasons,
+    /// How many pairs of adjacent levels differ by each amount, across all
+    /// reports.
+    pub step_sizes: BTreeMap<u32, usize>,
+    /// The fewest levels of any report, `None` without reports.
+    pub shortest: Option<usize>,
+    pub longest: Option<usize>
+}
@@ -0,0 +1,192 @@
+//! Seeded generators of reports and puzzles, for property tests of the crate
+//! and of code built on it. The same seed always yields the same values.
+//!
+//! A [`Strategy`] turns an [`Rng`] into a value; any `Fn(&mut Rng) -> T` is
+//! one, so strategies compose with plain closures. Types with a default
+//! strategy implement [`Arbitrary`], see [`any`].
+
+use std::ops::Range;
+
+pub use crate::rng::Rng;
+use crate::{
+    generate::{generate_report, safe_levels, Kind},
+    report::Report,
+    Puzzle
+};
+
+pub trait Strategy {
+    type Value;
+
+    fn generate(&self, rng: &mut Rng) -> Self::Value;
+}
+
+impl<T, F: Fn(&mut Rng) -> T> Strategy for F {
+    type Value = T;
+
+    fn generate(&self, rng: &mut Rng) -> T {
+        self(rng)
+    }
+}
+
+/// Reports of `len` levels, each drawn from `values`. Most of them are
+/// unsafe.
+pub fn report_strategy(len: Range<usize>, values: Range<u32>) -> impl Strategy<Value = Vec<u32>> {
+    move |rng: &mut Rng| {
+        let len = rng.below(len.start as u64..len.end as u64) as usize;
+        (0..len)
+        .map(|_| rng.below(values.start as u64..values.end as u64) as u32)
+        .collect()
+    }
+}
+
+/// Reports of 5 to 8 levels that are safe by construction: one direction,
+/// steps of 1 to 3.
+pub fn safe_report_strategy() -> impl Strategy<Value = Vec<u32>> {
+    |rng: &mut Rng| {
+        let len = rng.below(5..9) as usize;
+        safe_levels(rng, len)
+    }
+}
+
+/// Reports of 5 to 8 levels that are unsafe as-is but safe once the
+/// Problem Dampener removes one level.
+pub fn dampener_safe_report_strategy() -> impl Strategy<Value = Vec<u32>> {
+    |rng: &mut Rng| {
+        let len = rng.below(5..9) as usize;
+        generate_report(rng, Kind::DampenerSafe, len)
+    }
+}
+
+/// Reports of 5 to 8 levels that stay unsafe with the Problem Dampener.
+pub fn unsafe_report_strategy() -> impl Strategy<Value = Vec<u32>> {
+    |rng: &mut Rng| {
+        let len = rng.below(5..9) as usize;
+        generate_report(rng, Kind::Unsafe, len)
+    }
+}
+
+/// Types with a strategy covering all their values worth testing.
+pub trait Arbitrary: Sized {
+    fn arbitrary(rng: &mut Rng) -> Self;
+}
+
+/// Up to 9 levels from 0 to 19, so that every status and violation turns
+/// up often.
+impl Arbitrary for Report {
+    fn arbitrary(rng: &mut Rng) -> Report {
+        Report::new(report_strategy(0..10, 0..20).generate(rng))
+    }
+}
+
+/// Up to 49 arbitrary reports.
+impl Arbitrary for Puzzle {
+    fn arbitrary(rng: &mut Rng) -> Puzzle {
+        puzzle_strategy(0..50, |rng: &mut Rng| Report::arbitrary(rng).into_levels()).generate(rng)
+    }
+}
+
+/// The strategy of an [`Arbitrary`] type.
+pub fn any<T: Arbitrary>() -> impl Strategy<Value = T> {
+    T::arbitrary
+}
+
+/// Puzzles of `reports` reports, each drawn from `report`.
+pub fn puzzle_strategy<S: Strategy<Value = Vec<u32>>>(reports: Range<usize>, report: S) -> impl Strategy<Value = Puzzle> {
+    move |rng: &mut Rng| {
+        let count = rng.below(reports.start as u64..reports.end as u64) as usize;
+        Puzzle::from_reports((0..count).map(|_| Report::new(report.generate(rng))).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{classify_slice, is_safe_slice, is_safe_slice_with_tolerance, ParseOptions, ReportStatus, SafetyRules};
+
+    const CASES: u64 = 500;
+
+    fn for_each_case<S: Strategy>(strategy: S, mut property: impl FnMut(S::Value)) {
+        let mut rng = Rng::seeded(2024);
+        for _ in 0..CASES {
+            property(strategy.generate(&mut rng));
+        }
+    }
+
+    #[test]
+    fn should_same_seed_generate_same_puzzle() {
+        let strategy = puzzle_strategy(1..20, report_strategy(0..10, 0..100));
+
+        assert_eq!(strategy.generate(&mut Rng::seeded(7)), strategy.generate(&mut Rng::seeded(7)));
+        assert_ne!(strategy.generate(&mut Rng::seeded(7)), strategy.generate(&mut Rng::seeded(8)));
+    }
+
+    #[test]
+    fn should_report_strategy_stay_within_ranges() {
+        for_each_case(report_strategy(2..6, 10..20), |levels| {
+            assert!((2..6).contains(&levels.len()));
+            assert!(levels.iter().all(|level| (10..20).contains(level)));
+        });
+    }
+
+    #[test]
+    fn should_safe_report_stay_safe_after_no_op_operations() {
+        let rules = SafetyRules::default();
+        for_each_case(safe_report_strategy(), |levels| {
+            assert!(is_safe_slice(&levels, &rules), "{levels:?}");
+
+            let reversed: Vec<u32> = levels.iter().rev().copied().collect();
+            assert!(is_safe_slice(&reversed, &rules), "{levels:?} reversed");
+
+            let shifted: Vec<u32> = levels.iter().map(|level| level + 1000).collect();
+            assert!(is_safe_slice(&shifted, &rules), "{levels:?} shifted");
+
+            let line = levels.iter().map(u32::to_string).collect::<Vec<_>>().join(" ");
+            let (puzzle, _) = Puzzle::parse(&line, ParseOptions::default()).unwrap();
+            assert_eq!(puzzle.count_safe(&rules, 0), 1, "{line}");
+
+            assert!(is_safe_slice_with_tolerance(&levels, &rules, 2), "{levels:?} with tolerance");
+        });
+    }
+
+    #[test]
+    fn should_brute_force_dampener_agree_with_dampener_fix() {
+        let rules = SafetyRules::default();
+        for_each_case(report_strategy(2..9, 1..12), |levels| {
+            let brute_force = is_safe_slice(&levels, &rules)
+                || (0..levels.len()).any(|index| {
+                    let mut rest = levels.clone();
+                    rest.remove(index);
+                    is_safe_slice(&rest, &rules)
+                });
+            let report = Report::new(levels.clone());
+            let dampened = report.is_safe_with(&rules) || report.dampener_fix(&rules).is_some();
+
+            assert_eq!(is_safe_slice_with_tolerance(&levels, &rules, 1), brute_force, "{levels:?}");
+            assert_eq!(dampened, brute_force, "{levels:?}");
+        });
+    }
+
+    #[test]
+    fn should_status_strategies_build_reports_of_their_status() {
+        let rules = SafetyRules::default();
+        for_each_case(dampener_safe_report_strategy(), |levels| {
+            assert_eq!(classify_slice(&levels, &rules, 1), ReportStatus::SafeWithDampener, "{levels:?}");
+        });
+        for_each_case(unsafe_report_strategy(), |levels| {
+            assert_eq!(classify_slice(&levels, &rules, 1), ReportStatus::Unsafe, "{levels:?}");
+        });
+    }
+
+    #[test]
+    fn should_arbitrary_puzzle_cover_every_status() {
+        let mut statuses = Vec::new();
+        for_each_case(any::<Puzzle>(), |puzzle| {
+            assert!(puzzle.len() < 50);
+            statuses.extend(puzzle.statuses(&SafetyRules::default(), 1));
+        });
+
+        for status in [ReportStatus::Safe, ReportStatus::SafeWithDampener, ReportStatus::Unsafe] {
+            assert!(statuses.contains(&status), "{status:?}");
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Counting a puzzle straight off a reader, for inputs too large to hold.
+
+use std::{
+    io::BufRead,
+    time::{Duration, Instant}
+};
+
+use crate::{
+    parse::{parse_line_into, Location},
+    removals::min_removals_to_safe_reusing,
+    Day02Error, ParseOptions, ParseSummary, SafetyRules
+};
+
+/// How far counting a reader has got.
+#[derive(PartialEq, Clone, Copy, Default)]
+#[derive(Debug)]
+pub struct Progress {
+    /// The lines read so far, reports or not.
+    pub lines: usize,
+    pub reports: usize,
+    /// The reports found safe so far.
+    pub safe: usize,
+    /// Since counting started.
+    pub elapsed: Duration
+}
+
+impl Progress {
+    /// The reports counted per second since counting started, 0 until any
+    /// time has passed.
+    pub fn reports_per_second(&self) -> f64 {
+        match self.elapsed.as_secs_f64() {
+            0.0 => 0.0,
+            seconds => self.reports as f64 / seconds
+        }
+    }
+}
+
+/// Told how a long count is going, e.g. to print it; any
+/// `FnMut(&Progress)` is one.
+pub trait ProgressSink {
+    fn progress(&mut self, progress: &Progress);
+}
+
+impl<F: FnMut(&Progress)> ProgressSink for F {
+    fn progress(&mut self, progress: &Progress) {
+        self(progress)
+    }
+}
+
+/// The reports read from `reader` that are safe under `rules` once up to
+/// `tolerance` levels may be removed, as [`Puzzle::from_reader`](crate::Puzzle::from_reader)
+/// and [`Report::is_safe_with_tolerance`](crate::Report::is_safe_with_tolerance)
+/// would count them, but one line at a time: the line, its levels and the
+/// removal search reuse the same buffers throughout, so memory stays bounded
+/// by the longest line rather than growing with the input. Lines that do not
+/// parse are skipped.
+pub fn count_safe_from_reader<R: BufRead>(reader: R, rules: &SafetyRules, tolerance: u32) -> Result<usize, Day02Error> {
+    let progress = count_safe_from_reader_with_progress(reader, &ParseOptions::lenient(), rules, tolerance, 0, &mut |_: &Progress| {})?;
+
+    Ok(progress.safe)
+}
+
+/// Like [`count_safe_from_reader`], parsing with `options`, and telling
+/// `sink` how far it got after every `every` reports, never if 0, then once
+/// more with the final totals when the input ends, which it returns.
+pub fn count_safe_from_reader_with_progress<R: BufRead>(
+    mut reader: R,
+    options: &ParseOptions,
+    rules: &SafetyRules,
+    tolerance: u32,
+    every: usize,
+    sink: &mut impl ProgressSink
+) -> Result<Progress, Day02Error> {
+    let start = Instant::now();
+    let mut progress = Progress::default();
+    let mut summary = ParseSummary::default();
+    let mut location = Location {
+        line: 1,
+        offset: 0
+    };
+    let mut line = Vec::new();
+    let mut levels: Vec<u32> = Vec::new();
+    let mut scratch = Vec::new();
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 {
+            break;
+        }
+        progress.lines += 1;
+        let content = line.strip_suffix(b"\n").unwrap_or(&line);
+        let content = content.strip_suffix(b"\r").unwrap_or(content);
+        if parse_line_into(content, location, options, &mut summary, &mut levels)? {
+            progress.reports += 1;
+            if min_removals_to_safe_reusing(&levels, rules, tolerance, &mut scratch).is_some() {
+                progress.safe += 1;
+            }
+            if every != 0 && progress.reports % every == 0 {
+                progress.elapsed = start.elapsed();
+                sink.progress(&progress);
+            }
+        }
+        location = Location {
+            line: location.line + 1,
+            offset: location.offset + read
+        };
+    }
+    progress.elapsed = start.elapsed();
+    sink.progress(&progress);
+
+    Ok(progress)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::BufReader};
+
+    use super::*;
+    use crate::{
+        find_safe_reports,
+        ParseError,
+        generate::{generate_puzzle, write_to, GeneratorConfig}
+    };
+
+    fn count_file(path: &str, tolerance: u32) -> usize {
+        count_safe_from_reader(BufReader::new(File::open(path).unwrap()), &SafetyRules::default(), tolerance).unwrap()
+    }
+
+    #[test]
+    fn should_count_safe_from_reader_agree_with_find_safe_reports() {
+        for path in ["tests/resources/puzzle.txt", "tests/resources/puzzle_trailing_blank_lines.txt", "tests/resources/puzzle_invalid_byte.txt"] {
+            for tolerance in 0..3 {
+                assert_eq!(count_file(path, tolerance), find_safe_reports(path, tolerance), "{path} at tolerance {tolerance}");
+            }
+        }
+    }
+
+    #[test]
+    fn should_count_safe_from_reader_agree_with_generated_puzzle() {
+        let puzzle = generate_puzzle(&GeneratorConfig {
+            reports: 5000,
+            levels: 4..40,
+            seed: 3,
+            ..GeneratorConfig::default()
+        });
+        let mut input = Vec::new();
+        write_to(&puzzle, &mut input).unwrap();
+        let summary = puzzle.summary(&SafetyRules::default(), 1);
+
+        assert_eq!(count_safe_from_reader(input.as_slice(), &SafetyRules::default(), 0).unwrap(), summary.part1);
+        assert_eq!(count_safe_from_reader(input.as_slice(), &SafetyRules::default(), 1).unwrap(), summary.part2);
+    }
+
+    #[test]
+    fn should_report_progress_every_n_reports_then_at_end() {
+        let input = std::fs::read("tests/resources/puzzle_trailing_blank_lines.txt").unwrap();
+        let mut seen = Vec::new();
+
+        let last = count_safe_from_reader_with_progress(input.as_slice(), &ParseOptions::lenient(), &SafetyRules::default(), 1, 4, &mut |progress: &Progress| {
+            seen.push((progress.reports, progress.safe));
+        })
+        .unwrap();
+
+        assert_eq!(seen, vec![(4, 2), (6, 4)]);
+        assert_eq!((last.reports, last.safe), (6, 4));
+        assert!(last.lines > last.reports);
+    }
+
+    #[test]
+    fn should_count_with_progress_fail_on_bad_line_when_strict() {
+        let result = count_safe_from_reader_with_progress(&b"1 2 3\n1 x\n"[..], &ParseOptions::strict(), &SafetyRules::default(), 0, 0, &mut |_: &Progress| {});
+
+        assert!(matches!(result, Err(Day02Error::Parse(ParseError::InvalidToken { line: 2, .. }))));
+    }
+
+    #[test]
+    fn should_count_safe_from_reader_honour_rules_and_crlf() {
+        let input = b"5\r\n1 2\r\n\r\n7 6 4 2 1\r\n";
+
+        assert_eq!(count_safe_from_reader(&input[..], &SafetyRules::default(), 0).unwrap(), 2);
+        assert_eq!(count_safe_from_reader(&input[..], &SafetyRules { min_levels: 1, ..SafetyRules::default() }, 0).unwrap(), 3);
+    }
+}
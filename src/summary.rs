@@ -0,0 +1,130 @@
+//! Result types meant to leave the crate, with a stable JSON form.
+//!
+//! Field names in the JSON are part of the schema and do not change:
+//!
+//! - `SafetySummary`: `total`, `part1`, `part2`, `tolerance`
+//! - `SafetyReport`: `levels`, `status`, `violation`, `violation_index`,
+//!   `fix`
+//! - `DampenerFix`: `removed_index`, `removed_level`
+//!
+//! Indices are 0-based. `violation` is one of `too_short`, `step` or
+//! `direction`, and `status` one of `safe`, `safe_with_dampener` or `unsafe`.
+
+use crate::{json, ReportStatus, Violation};
+
+/// Part 1 and part 2 answers of a puzzle.
+#[derive(PartialEq, Eq, Clone, Copy, Default)]
+#[derive(Debug)]
+pub struct SafetySummary {
+    pub total: usize,
+    /// Reports safe as-is.
+    pub part1: usize,
+    /// Reports safe once up to `tolerance` levels may be removed.
+    pub part2: usize,
+    pub tolerance: u32
+}
+
+impl SafetySummary {
+    pub fn to_json(&self) -> String {
+        json::object(&[
+            ("total", self.total.to_string()),
+            ("part1", self.part1.to_string()),
+            ("part2", self.part2.to_string()),
+            ("tolerance", self.tolerance.to_string())
+        ])
+    }
+}
+
+/// The single level the Problem Dampener removes to make a report safe.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub struct DampenerFix {
+    pub removed_index: usize,
+    pub removed_level: u32
+}
+
+impl DampenerFix {
+    pub fn to_json(&self) -> String {
+        json::object(&[
+            ("removed_index", self.removed_index.to_string()),
+            ("removed_level", self.removed_level.to_string())
+        ])
+    }
+}
+
+/// How one report fares with the Problem Dampener on.
+#[derive(PartialEq, Eq, Clone)]
+#[derive(Debug)]
+pub struct SafetyReport {
+    pub levels: Vec<u32>,
+    pub status: ReportStatus,
+    /// The first rule the report breaks before any removal.
+    pub violation: Option<Violation>,
+    pub fix: Option<DampenerFix>
+}
+
+impl SafetyReport {
+    pub fn to_json(&self) -> String {
+        json::object(&[
+            ("levels", json::array(self.levels.iter().map(u32::to_string))),
+            ("status", json::string(self.status.as_str())),
+            ("violation", json::option(self.violation.map(|violation| json::string(violation.kind())))),
+            ("violation_index", json::option(self.violation.and_then(|violation| violation.index()))),
+            ("fix", json::option(self.fix.map(|fix| fix.to_json())))
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_summary_to_json() {
+        let summary = SafetySummary {
+            total: 6,
+            part1: 2,
+            part2: 4,
+            tolerance: 1
+        };
+
+        assert_eq!(summary.to_json(), r#"{"total":6,"part1":2,"part2":4,"tolerance":1}"#);
+    }
+
+    #[test]
+    fn should_report_to_json_with_fix() {
+        let report = SafetyReport {
+            levels: vec![1, 3, 2, 4, 5],
+            status: ReportStatus::SafeWithDampener,
+            violation: Some(Violation::Direction {
+                index: 1
+            }),
+            fix: Some(DampenerFix {
+                removed_index: 1,
+                removed_level: 3
+            })
+        };
+
+        assert_eq!(
+            report.to_json(),
+            r#"{"levels":[1,3,2,4,5],"status":"safe_with_dampener","violation":"direction","violation_index":1,"fix":{"removed_index":1,"removed_level":3}}"#
+        );
+    }
+
+    #[test]
+    fn should_report_to_json_without_violation_index_when_too_short() {
+        let report = SafetyReport {
+            levels: vec![],
+            status: ReportStatus::Unsafe,
+            violation: Some(Violation::TooShort {
+                levels: 0
+            }),
+            fix: None
+        };
+
+        assert_eq!(
+            report.to_json(),
+            r#"{"levels":[],"status":"unsafe","violation":"too_short","violation_index":null,"fix":null}"#
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Checking computed answers against known ones.
+
+use std::{error::Error, fmt, path::Path};
+
+use crate::{Day02Error, ParseOptions, Puzzle, SafetyRules};
+
+#[derive(Debug)]
+pub enum VerifyError {
+    Puzzle(Day02Error),
+    Part1 { expected: usize, actual: usize },
+    Part2 { expected: usize, actual: usize }
+}
+
+fn mismatch(f: &mut fmt::Formatter<'_>, part: u8, expected: usize, actual: usize) -> fmt::Result {
+    let (difference, direction) = if actual > expected {
+        (actual - expected, "more")
+    } else {
+        (expected - actual, "less")
+    };
+    write!(f, "part {part} is {actual}, {difference} {direction} than the expected {expected}")
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Puzzle(error) => error.fmt(f),
+            VerifyError::Part1 { expected, actual } => mismatch(f, 1, *expected, *actual),
+            VerifyError::Part2 { expected, actual } => mismatch(f, 2, *expected, *actual)
+        }
+    }
+}
+
+impl Error for VerifyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            VerifyError::Puzzle(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<Day02Error> for VerifyError {
+    fn from(error: Day02Error) -> VerifyError {
+        VerifyError::Puzzle(error)
+    }
+}
+
+/// Solves the puzzle at `path` once for both parts and compares the answers,
+/// part 1 first.
+pub fn verify(path: &Path, expected_part1: u32, expected_part2: u32) -> Result<(), VerifyError> {
+    let (puzzle, _) = Puzzle::from_path(path, ParseOptions::lenient())?;
+    let summary = puzzle.summary(&SafetyRules::default(), 1);
+    let (expected_part1, expected_part2) = (expected_part1 as usize, expected_part2 as usize);
+    if summary.part1 != expected_part1 {
+        return Err(VerifyError::Part1 {
+            expected: expected_part1,
+            actual: summary.part1
+        });
+    }
+    if summary.part2 != expected_part2 {
+        return Err(VerifyError::Part2 {
+            expected: expected_part2,
+            actual: summary.part2
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "tests/resources/puzzle.txt";
+
+    #[test]
+    fn should_verify_pass_with_correct_answers() {
+        assert!(verify(Path::new(SAMPLE), 2, 4).is_ok());
+    }
+
+    #[test]
+    fn should_verify_name_mismatched_part() {
+        assert!(matches!(verify(Path::new(SAMPLE), 2, 5), Err(VerifyError::Part2 { expected: 5, actual: 4 })));
+        assert!(matches!(verify(Path::new(SAMPLE), 3, 5), Err(VerifyError::Part1 { expected: 3, actual: 2 })));
+    }
+
+    #[test]
+    fn should_verify_error_say_by_how_much() {
+        assert_eq!(verify(Path::new(SAMPLE), 2, 5).unwrap_err().to_string(), "part 2 is 4, 1 less than the expected 5");
+        assert_eq!(verify(Path::new(SAMPLE), 0, 4).unwrap_err().to_string(), "part 1 is 2, 2 more than the expected 0");
+    }
+
+    #[test]
+    fn should_verify_fail_on_missing_file() {
+        assert!(matches!(verify(Path::new("tests/resources/missing.txt"), 2, 4), Err(VerifyError::Puzzle(Day02Error::Io(_)))));
+    }
+}
@@ -0,0 +1,190 @@
+//! Exports for running the solver in a browser.
+//!
+//! The functions only take and return strings and integers, and nothing here
+//! touches the filesystem, so the module builds for `wasm32-unknown-unknown`.
+//! The `day02_wasm_*` symbols are a plain linear-memory ABI for JavaScript:
+//! copy the input into a buffer from `day02_wasm_alloc`, call the solver,
+//! then release every buffer with `day02_wasm_dealloc`. JSON results are
+//! returned as text, for `JSON.parse` on the JavaScript side.
+
+use std::{ptr, slice};
+
+use crate::{json, ParseOptions, Puzzle, SafetyReport, SafetyRules};
+
+/// Counts the reports of `input` that are safe once up to `tolerance` levels
+/// may be removed from each of them.
+pub fn count_safe(input: &str, tolerance: u32) -> u32 {
+    count_safe_bytes(input.as_bytes(), tolerance)
+}
+
+/// The status of every report of `input` with the dampener on, as a JSON
+/// array such as `["safe","unsafe","safe_with_dampener"]`.
+pub fn classify_reports(input: &str) -> String {
+    classify_reports_bytes(input.as_bytes())
+}
+
+/// Every report of `input` with its verdict under the dampener, for showing
+/// them one by one: a JSON array of objects such as
+/// `{"levels":[1,3,2,4,5],"status":"safe_with_dampener","violation":"direction","violation_index":1,"fix":{"removed_index":1,"removed_level":3}}`.
+/// `violation_index`, the first level of the offending pair, and
+/// `removed_index` are 0-based.
+pub fn classify(input: &str) -> String {
+    classify_bytes(input.as_bytes())
+}
+
+fn count_safe_bytes(input: &[u8], tolerance: u32) -> u32 {
+    let count = lenient_puzzle(input).count_safe(&SafetyRules::default(), tolerance);
+
+    u32::try_from(count).unwrap_or(u32::MAX)
+}
+
+fn classify_reports_bytes(input: &[u8]) -> String {
+    json::array(lenient_puzzle(input)
+    .statuses(&SafetyRules::default(), 1)
+    .iter()
+    .map(|status| json::string(status.as_str())))
+}
+
+fn classify_bytes(input: &[u8]) -> String {
+    json::array(lenient_puzzle(input)
+    .safety_reports(&SafetyRules::default())
+    .iter()
+    .map(SafetyReport::to_json))
+}
+
+fn lenient_puzzle(input: &[u8]) -> Puzzle {
+    // Lenient parsing only skips what it cannot read, it never fails.
+    Puzzle::from_bytes(input, ParseOptions::lenient())
+    .map(|(puzzle, _)| puzzle)
+    .unwrap_or_default()
+}
+
+/// Reserves `len` zeroed bytes for the caller to write into.
+#[no_mangle]
+pub extern "C" fn day02_wasm_alloc(len: usize) -> *mut u8 {
+    Box::into_raw(vec![0u8; len].into_boxed_slice()) as *mut u8
+}
+
+/// Releases a buffer from `day02_wasm_alloc`, `day02_wasm_classify_reports`
+/// or `day02_wasm_classify`.
+///
+/// # Safety
+///
+/// `ptr` and `len` must describe a buffer returned by this module that has
+/// not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn day02_wasm_dealloc(ptr: *mut u8, len: usize) {
+    if !ptr.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, len)));
+    }
+}
+
+/// # Safety
+///
+/// `input` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn day02_wasm_count_safe(input: *const u8, len: usize, tolerance: u32) -> u32 {
+    count_safe_bytes(input_bytes(input, len), tolerance)
+}
+
+/// Returns the JSON of [`classify_reports`] in a new buffer whose length is
+/// written to `out_len`.
+///
+/// # Safety
+///
+/// `input` must point to `len` readable bytes and `out_len` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn day02_wasm_classify_reports(input: *const u8, len: usize, out_len: *mut usize) -> *mut u8 {
+    into_buffer(classify_reports_bytes(input_bytes(input, len)), out_len)
+}
+
+/// Returns the JSON of [`classify`] in a new buffer whose length is written
+/// to `out_len`.
+///
+/// # Safety
+///
+/// `input` must point to `len` readable bytes and `out_len` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn day02_wasm_classify(input: *const u8, len: usize, out_len: *mut usize) -> *mut u8 {
+    into_buffer(classify_bytes(input_bytes(input, len)), out_len)
+}
+
+unsafe fn into_buffer(text: String, out_len: *mut usize) -> *mut u8 {
+    let bytes = text.into_bytes().into_boxed_slice();
+    *out_len = bytes.len();
+
+    Box::into_raw(bytes) as *mut u8
+}
+
+unsafe fn input_bytes<'a>(input: *const u8, len: usize) -> &'a [u8] {
+    if input.is_null() {
+        return &[];
+    }
+    slice::from_raw_parts(input, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+
+    fn sample() -> String {
+        read_to_string("tests/resources/puzzle.txt").unwrap()
+    }
+
+    #[test]
+    fn should_count_safe_sample_for_both_parts() {
+        assert_eq!(count_safe(&sample(), 0), 2);
+        assert_eq!(count_safe(&sample(), 1), 4);
+    }
+
+    #[test]
+    fn should_classify_reports_sample_as_json() {
+        assert_eq!(
+            classify_reports(&sample()),
+            r#"["safe","unsafe","unsafe","safe_with_dampener","safe_with_dampener","safe"]"#
+        );
+    }
+
+    #[test]
+    fn should_classify_give_verdict_of_each_report() {
+        assert_eq!(classify("7 6 4 2 1\nnot a report\n1 3 2 4 5\n"), concat!(
+            r#"[{"levels":[7,6,4,2,1],"status":"safe","violation":null,"violation_index":null,"fix":null},"#,
+            r#"{"levels":[1,3,2,4,5],"status":"safe_with_dampener","violation":"direction","violation_index":1,"fix":{"removed_index":1,"removed_level":3}}]"#
+        ));
+        assert_eq!(classify(""), "[]");
+    }
+
+    #[test]
+    fn should_classify_reports_empty_input_as_empty_array() {
+        assert_eq!(classify_reports(""), "[]");
+    }
+
+    #[test]
+    fn should_exports_round_trip_sample_through_linear_memory() {
+        let input = sample();
+        let buffer = day02_wasm_alloc(input.len());
+        unsafe {
+            ptr::copy_nonoverlapping(input.as_ptr(), buffer, input.len());
+
+            assert_eq!(day02_wasm_count_safe(buffer, input.len(), 1), 4);
+
+            let mut json_len = 0;
+            let json = day02_wasm_classify_reports(buffer, input.len(), &mut json_len);
+            assert_eq!(slice::from_raw_parts(json, json_len), classify_reports(&input).as_bytes());
+            day02_wasm_dealloc(json, json_len);
+
+            let json = day02_wasm_classify(buffer, input.len(), &mut json_len);
+            assert_eq!(slice::from_raw_parts(json, json_len), classify(&input).as_bytes());
+            day02_wasm_dealloc(json, json_len);
+
+            day02_wasm_dealloc(buffer, input.len());
+        }
+    }
+
+    #[test]
+    fn should_count_safe_export_treat_null_input_as_empty() {
+        assert_eq!(unsafe { day02_wasm_count_safe(ptr::null(), 0, 1) }, 0);
+    }
+}
@@ -0,0 +1,205 @@
+//! Re-running something whenever a file changes, for `day_02 --watch`.
+//!
+//! Changes come from a [`ChangeSource`], usually a channel fed by the
+//! polling watcher here, so a test can script them, timeouts included, with
+//! nothing depending on the clock. The file is polled for its modification
+//! time since no watcher crate is available.
+
+use std::{
+    fs, io,
+    path::PathBuf,
+    sync::mpsc::{Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH}
+};
+
+/// A change of the watched file.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub struct Changed;
+
+/// What waiting for the next change gave.
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub enum Wait {
+    Changed,
+    TimedOut,
+    Closed
+}
+
+/// Where [`watch_loop`] waits for changes.
+pub trait ChangeSource {
+    /// The next change, waiting for it at most `timeout` when given.
+    fn wait(&mut self, timeout: Option<Duration>) -> Wait;
+}
+
+impl ChangeSource for &Receiver<Changed> {
+    fn wait(&mut self, timeout: Option<Duration>) -> Wait {
+        match timeout {
+            None => self.recv().map_or(Wait::Closed, |Changed| Wait::Changed),
+            Some(timeout) => match self.recv_timeout(timeout) {
+                Ok(Changed) => Wait::Changed,
+                Err(RecvTimeoutError::Timeout) => Wait::TimedOut,
+                Err(RecvTimeoutError::Disconnected) => Wait::Closed
+            }
+        }
+    }
+}
+
+/// Sends a [`Changed`] whenever the modification time of `path` changes,
+/// checking every `interval`. Stops once nobody listens anymore.
+pub fn poll_changes(path: PathBuf, interval: Duration, changes: Sender<Changed>) {
+    thread::spawn(move || {
+        let modified = || fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+        let mut last = modified();
+        loop {
+            thread::sleep(interval);
+            let current = modified();
+            if current != last {
+                last = current;
+                if changes.send(Changed).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+}
+
+/// Calls `run` once per burst of changes: after a change, further changes
+/// within `debounce` of the previous one are folded into it, so editors
+/// writing a file twice trigger a single run. Returns once `changes` is
+/// closed, or with the first error of `run`.
+pub fn watch_loop(mut changes: impl ChangeSource, debounce: Duration, mut run: impl FnMut() -> io::Result<()>) -> io::Result<()> {
+    while changes.wait(None) == Wait::Changed {
+        loop {
+            match changes.wait(Some(debounce)) {
+                Wait::Changed => continue,
+                Wait::TimedOut => break,
+                Wait::Closed => return run()
+            }
+        }
+        run()?;
+    }
+    Ok(())
+}
+
+/// `YYYY-MM-DD HH:MM:SS UTC`.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    let (days, seconds) = (seconds / 86_400, seconds % 86_400);
+    // Civil date from days since 1970-01-01, after Howard Hinnant.
+    let days = days as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, sync::mpsc::channel};
+
+    use super::*;
+
+    /// Scripted waits, each checked to wait for as long as expected, then
+    /// closed.
+    struct Script(VecDeque<(Option<Duration>, Wait)>);
+
+    impl ChangeSource for &mut Script {
+        fn wait(&mut self, timeout: Option<Duration>) -> Wait {
+            let Some((expected, wait)) = self.0.pop_front() else {
+                return Wait::Closed;
+            };
+            assert_eq!(timeout, expected);
+
+            wait
+        }
+    }
+
+    #[test]
+    fn should_watch_loop_run_once_per_burst_of_changes() {
+        let debounce = Duration::from_millis(50);
+        let mut script = Script(VecDeque::from([
+            (None, Wait::Changed),
+            (Some(debounce), Wait::Changed),
+            (Some(debounce), Wait::TimedOut),
+            (None, Wait::Changed),
+            (Some(debounce), Wait::TimedOut)
+        ]));
+        let mut runs = 0;
+
+        watch_loop(&mut script, debounce, || {
+            runs += 1;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(runs, 2);
+        assert!(script.0.is_empty());
+    }
+
+    #[test]
+    fn should_watch_loop_run_pending_burst_when_closed() {
+        let (sender, changes) = channel();
+        sender.send(Changed).unwrap();
+        sender.send(Changed).unwrap();
+        drop(sender);
+        let mut runs = 0;
+
+        watch_loop(&changes, Duration::from_secs(60), || {
+            runs += 1;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(runs, 1);
+    }
+
+    #[test]
+    fn should_watch_loop_return_without_running_when_closed() {
+        let (sender, changes) = channel::<Changed>();
+        drop(sender);
+        let mut runs = 0;
+
+        watch_loop(&changes, Duration::from_millis(10), || {
+            runs += 1;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(runs, 0);
+    }
+
+    #[test]
+    fn should_watch_loop_stop_on_first_error() {
+        let (sender, changes) = channel();
+        let mut runs = 0;
+        sender.send(Changed).unwrap();
+
+        let result = watch_loop(&changes, Duration::from_millis(10), || {
+            runs += 1;
+            Err(io::Error::other("closed"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(runs, 1);
+    }
+
+    #[test]
+    fn should_format_timestamp_as_utc_date_and_time() {
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_timestamp(UNIX_EPOCH + Duration::from_secs(1_733_112_000)), "2024-12-02 04:00:00 UTC");
+        assert_eq!(format_timestamp(UNIX_EPOCH + Duration::from_secs(951_782_400)), "2000-02-29 00:00:00 UTC");
+    }
+}
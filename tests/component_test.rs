@@ -1,6 +1,32 @@
-use day_02::find_safe_reports;
+use day_02::{find_safe_reports, find_safe_reports_with_rules, SafetyRules};
 
 #[test]
 fn it_should_find_safe_reports() {
-    assert_eq!(find_safe_reports("tests/resources/puzzle.txt"), 2);
-}
\ No newline at end of file
+    assert_eq!(find_safe_reports("tests/resources/puzzle.txt", 0), 2);
+}
+
+#[test]
+fn it_should_find_same_safe_reports_with_trailing_blank_lines() {
+    assert_eq!(find_safe_reports("tests/resources/puzzle_trailing_blank_lines.txt", 0), 2);
+}
+
+#[test]
+fn it_should_find_safe_reports_when_input_is_not_utf8() {
+    assert_eq!(find_safe_reports("tests/resources/puzzle_invalid_byte.txt", 0), 2);
+}
+
+#[test]
+fn it_should_find_safe_reports_with_problem_dampener() {
+    assert_eq!(find_safe_reports("tests/resources/puzzle.txt", 1), 4);
+}
+
+#[test]
+fn it_should_find_safe_reports_with_other_rules() {
+    let rules = SafetyRules {
+        max_step: 5,
+        allow_equal: true,
+        ..SafetyRules::default()
+    };
+
+    assert_eq!(find_safe_reports_with_rules("tests/resources/puzzle.txt", &rules, 0), 5);
+}
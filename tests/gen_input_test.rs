@@ -0,0 +1,56 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::{self, Command, Output}
+};
+
+use day_02::find_safe_reports;
+
+fn gen_input(out: &PathBuf, extra: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_gen_input"))
+    .args(["--reports", "200", "--safe-ratio", "0.4", "--seed", "7", "--out"])
+    .arg(out)
+    .args(extra)
+    .output()
+    .unwrap()
+}
+
+fn temp_file(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("day_02_{name}_{}.txt", process::id()))
+}
+
+#[test]
+fn it_should_print_ground_truth_matching_find_safe_reports() {
+    let out = temp_file("gen_input");
+    let _ = fs::remove_file(&out);
+
+    let output = gen_input(&out, &[]);
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let part1: usize = stdout
+    .lines()
+    .find_map(|line| line.strip_prefix("Part 1 (safe): "))
+    .unwrap()
+    .parse()
+    .unwrap();
+    assert!(output.status.success());
+    assert_eq!(part1, 80);
+    assert_eq!(find_safe_reports(out.to_str().unwrap(), 0), part1);
+    fs::remove_file(&out).unwrap();
+}
+
+#[test]
+fn it_should_refuse_to_overwrite_without_force() {
+    let out = temp_file("gen_input_existing");
+    fs::write(&out, "keep me").unwrap();
+
+    let refused = gen_input(&out, &[]);
+    let kept = fs::read_to_string(&out).unwrap();
+    let forced = gen_input(&out, &["--force"]);
+
+    assert!(!refused.status.success());
+    assert_eq!(kept, "keep me");
+    assert!(forced.status.success());
+    assert_eq!(fs::read_to_string(&out).unwrap().lines().count(), 200);
+    fs::remove_file(&out).unwrap();
+}
@@ -0,0 +1,9 @@
+use day_02::{find_safe_reports, find_safe_reports_parallel};
+
+#[test]
+fn it_should_find_same_safe_reports_in_parallel() {
+    for threads in [0, 1, 4] {
+        assert_eq!(find_safe_reports_parallel("tests/resources/puzzle.txt", 0, threads), find_safe_reports("tests/resources/puzzle.txt", 0));
+        assert_eq!(find_safe_reports_parallel("tests/resources/puzzle.txt", 1, threads), 4);
+    }
+}
@@ -0,0 +1,40 @@
+use std::{
+    fs,
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    thread
+};
+
+use day_02::server::serve;
+
+fn post(body: &str, target: &str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || serve(listener));
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(stream, "POST {target} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn it_should_answer_summary_of_sample() {
+    let input = fs::read_to_string("tests/resources/puzzle.txt").unwrap();
+
+    let response = post(&input, "/safe-reports");
+
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+    assert!(response.ends_with(r#"{"total":6,"part1":2,"part2":4,"tolerance":1}"#), "{response}");
+}
+
+#[test]
+fn it_should_answer_400_with_line_on_malformed_input() {
+    let input = fs::read_to_string("tests/resources/puzzle_overflow.txt").unwrap();
+
+    let response = post(&input, "/safe-reports?tolerance=0");
+
+    assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{response}");
+    assert!(response.ends_with(r#""line":6}"#), "{response}");
+}